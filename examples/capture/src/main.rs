@@ -32,7 +32,7 @@ use nokhwa::{
     query,
     utils::{
         frame_formats, yuyv422_predicted_size, CameraFormat, CameraIndex, FrameFormat,
-        RequestedFormat, RequestedFormatType, Resolution,
+        FrameRate, RequestedFormat, RequestedFormatType, Resolution,
     },
     FrameBuffer, CallbackCamera, Camera,
 };
@@ -107,11 +107,15 @@ enum Commands {
     Stream {
         device: Option<IndexKind>,
         display: Option<bool>,
+        /// Format to request, e.g. `HighestResolution:30` (a frame rate) or `HighestFrameRate:1920,1080`
+        /// (a resolution).
         requested: Option<RequestedCliFormat>,
     },
     Single {
         device: Option<IndexKind>,
         save: Option<String>,
+        /// Format to request, e.g. `HighestResolution:30` (a frame rate) or `HighestFrameRate:1920,1080`
+        /// (a resolution).
         requested: Option<RequestedCliFormat>,
     },
 }
@@ -166,21 +170,21 @@ impl RequestedCliFormat {
                 RequestedFormatType::AbsoluteHighestFrameRate,
             )),
             "HighestResolution" => {
-                let fmtv = self.format_option.unwrap();
-                let values = fmtv.split(",").collect::<Vec<&str>>();
-                let x = values[0].parse::<u32>().unwrap();
-                let y = values[1].parse::<u32>().unwrap();
-                let resolution = Resolution::new(x, y);
+                let fps = self.format_option.unwrap().parse::<i32>().unwrap();
 
                 Some(RequestedFormat::new::<RgbFormat>(
-                    RequestedFormatType::HighestResolution(resolution),
+                    RequestedFormatType::HighestResolution(FrameRate::frame_rate(fps)),
                 ))
             }
             "HighestFrameRate" => {
-                let fps = self.format_option.unwrap().parse::<u32>().unwrap();
+                let fmtv = self.format_option.unwrap();
+                let values = fmtv.split(",").collect::<Vec<&str>>();
+                let x = values[0].parse::<u32>().unwrap();
+                let y = values[1].parse::<u32>().unwrap();
+                let resolution = Resolution::new(x, y);
 
                 Some(RequestedFormat::new::<RgbFormat>(
-                    RequestedFormatType::HighestFrameRate(fps),
+                    RequestedFormatType::HighestFrameRate(resolution),
                 ))
             }
             "Exact" => {
@@ -188,11 +192,12 @@ impl RequestedCliFormat {
                 let values = fmtv.split(",").collect::<Vec<&str>>();
                 let x = values[0].parse::<u32>().unwrap();
                 let y = values[1].parse::<u32>().unwrap();
-                let fps = values[2].parse::<u32>().unwrap();
+                let fps = values[2].parse::<i32>().unwrap();
                 let fourcc = values[3].parse::<FrameFormat>().unwrap();
 
                 let resolution = Resolution::new(x, y);
-                let camera_format = CameraFormat::new(resolution, fourcc, fps);
+                let camera_format =
+                    CameraFormat::new(resolution, fourcc, FrameRate::frame_rate(fps));
                 Some(RequestedFormat::new::<RgbFormat>(
                     RequestedFormatType::Exact(camera_format),
                 ))
@@ -202,11 +207,12 @@ impl RequestedCliFormat {
                 let values = fmtv.split(",").collect::<Vec<&str>>();
                 let x = values[0].parse::<u32>().unwrap();
                 let y = values[1].parse::<u32>().unwrap();
-                let fps = values[2].parse::<u32>().unwrap();
+                let fps = values[2].parse::<i32>().unwrap();
                 let fourcc = values[3].parse::<FrameFormat>().unwrap();
 
                 let resolution = Resolution::new(x, y);
-                let camera_format = CameraFormat::new(resolution, fourcc, fps);
+                let camera_format =
+                    CameraFormat::new(resolution, fourcc, FrameRate::frame_rate(fps));
                 Some(RequestedFormat::new::<RgbFormat>(
                     RequestedFormatType::Closest(camera_format),
                 ))
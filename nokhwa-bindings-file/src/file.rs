@@ -0,0 +1,1006 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A pseudo-camera that plays a raw video file, or a directory of frames, on loop through the [`Camera`] API.
+//!
+//! Raw files have no header, so the format comes from the path: `<name>_<width>x<height>.<ext>`, where `ext` is
+//! `yuyv` (or `yuy2`), `nv12` or `mjpeg` (or `mjpg`), e.g. `clip_640x480.yuyv`. An MJPEG file is a plain
+//! concatenation of JPEGs. A directory follows the same naming, e.g. `frames_1280x720.mjpeg/`, and holds one frame
+//! per file, played in file name order. Use [`FileCamera::with_format`] for files named otherwise.
+
+use flume::{Receiver, Sender};
+#[cfg(feature = "async")]
+use nokhwa_core::camera::{AsyncCamera, AsyncSetting, AsyncStream};
+use nokhwa_core::camera::{Camera, Capture, Setting};
+use nokhwa_core::error::{NokhwaError, NokhwaResult};
+use nokhwa_core::frame_buffer::FrameBuffer;
+use nokhwa_core::format_request::RequestedFormat;
+use nokhwa_core::frame_format::FrameFormat;
+use nokhwa_core::jpeg::jpeg_length;
+#[cfg(feature = "async")]
+use nokhwa_core::platform::AsyncPlatformTrait;
+use nokhwa_core::platform::{Backends, OpenOptions, PlatformTrait};
+use nokhwa_core::properties::{ControlId, ControlValue, Properties, PropertiesSnapshot};
+use nokhwa_core::stream::{IoMethod, Stream, StreamConfig, StreamInnerTrait, StreamStats, StreamStatsRecorder};
+use nokhwa_core::types::{CameraFormat, CameraIndex, CameraInformation, FrameRate, Resolution};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// The [`Backends`] value files are played through.
+pub const FILE_BACKEND: Backends = Backends::Custom("File");
+
+const DEFAULT_FRAME_RATE: FrameRate = FrameRate::frame_rate(30);
+// Frames buffered between the playback thread and the `Stream` before new ones are dropped.
+const CHANNEL_CAPACITY: usize = 4;
+
+/// Lists and opens files as cameras. [`CameraIndex::String`] opens that path; [`CameraIndex::Index`] opens the
+/// path at that position in the list given with [`with_path`](FilePlatform::with_path).
+#[derive(Clone, Debug, Default)]
+pub struct FilePlatform {
+    paths: Vec<PathBuf>,
+}
+
+impl FilePlatform {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `path` to the devices [`query`](PlatformTrait::query) lists.
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+}
+
+impl PlatformTrait for FilePlatform {
+    const PLATFORM: Backends = FILE_BACKEND;
+    type Camera = FileCamera;
+
+    fn block_on_permission(&mut self) -> NokhwaResult<()> {
+        Ok(())
+    }
+
+    fn check_permission_given(&mut self) -> bool {
+        true
+    }
+
+    fn query(&mut self) -> NokhwaResult<Vec<CameraInformation>> {
+        Ok(self
+            .paths
+            .iter()
+            .map(|path| {
+                let name = path
+                    .file_name()
+                    .map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().to_string());
+                CameraInformation::new(
+                    name,
+                    "File playback".to_string(),
+                    path.display().to_string(),
+                    CameraIndex::String(path.display().to_string()),
+                )
+            })
+            .collect())
+    }
+
+    fn open(&mut self, index: &CameraIndex) -> NokhwaResult<Self::Camera> {
+        let camera = match index {
+            CameraIndex::String(path) => FileCamera::open(path),
+            CameraIndex::Index(position) => self
+                .paths
+                .get(*position as usize)
+                .ok_or_else(|| NokhwaError::OpenDeviceError(index.to_string(), "No such file was added".to_string()))
+                .and_then(FileCamera::open),
+        };
+
+        camera.map_err(|why| why.with_context(FILE_BACKEND, index.clone()))
+    }
+
+    /// Files are never held open, so a read-only camera is only barred from streaming.
+    fn open_with(&mut self, index: &CameraIndex, options: OpenOptions) -> NokhwaResult<Self::Camera> {
+        let camera = self.open(index)?;
+        Ok(if options.read_only { camera.read_only() } else { camera })
+    }
+}
+
+/// Files are read in one go when opened, so nothing here waits on I/O.
+#[cfg(feature = "async")]
+impl AsyncPlatformTrait for FilePlatform {
+    const PLATFORM: Backends = FILE_BACKEND;
+    type AsyncCamera = FileCamera;
+
+    async fn await_permission(&mut self) -> NokhwaResult<()> {
+        self.block_on_permission()
+    }
+
+    async fn query_async(&mut self) -> NokhwaResult<Vec<CameraInformation>> {
+        self.query()
+    }
+
+    async fn open_async(&mut self, index: &CameraIndex) -> NokhwaResult<Self::AsyncCamera> {
+        self.open(index)
+    }
+}
+
+/// A file played as a camera, see the [module documentation](self).
+///
+/// The file is read into memory when opened. Its resolution and format are fixed, but the frame rate it plays at
+/// (30 by default) can be changed with [`set_format`](Setting::set_format) before opening a stream.
+///
+/// A file has no controls of its own, but can be given some with [`with_properties`](FileCamera::with_properties).
+///
+/// The file is not kept open, so [`release`](Camera::release) only stops playback until [`reopen`](Camera::reopen).
+/// Like a device opened afresh, a reopened camera starts from the format and control values it was created with
+/// before restoring the ones it had when released.
+pub struct FileCamera {
+    // The frames of each logical stream, the ones it was created with first.
+    streams: Vec<Arc<Vec<Vec<u8>>>>,
+    format: Mutex<CameraFormat>,
+    properties: Properties,
+    // The format and controls the camera was created with, which a reopened camera starts from.
+    initial: (CameraFormat, Properties),
+    // The playback flag of each open logical stream.
+    running: HashMap<usize, Arc<AtomicBool>>,
+    // The format and control values to restore on `reopen`, while released.
+    released: Option<(CameraFormat, PropertiesSnapshot)>,
+    read_only: bool,
+    // Formats listed alongside the played one, which cannot be set.
+    listed_formats: Vec<CameraFormat>,
+    // The stills `capture_still` can return, besides a frame of the played format.
+    stills: Vec<(CameraFormat, Vec<u8>)>,
+}
+
+impl FileCamera {
+    /// Opens the file or directory at `path`, taking its resolution and format from its name.
+    /// # Errors
+    /// Errors if the name does not follow the [naming convention](self), or the file cannot be read.
+    pub fn open(path: impl AsRef<Path>) -> NokhwaResult<Self> {
+        let path = path.as_ref();
+        let (resolution, frame_format) = format_from_name(path)?;
+        Self::with_format(path, CameraFormat::new(resolution, frame_format, DEFAULT_FRAME_RATE))
+    }
+
+    /// Opens the file or directory at `path` as frames of `format`, whatever it is named.
+    /// # Errors
+    /// Errors if the file cannot be read or holds no frames, or a raw file's length is not a whole number of frames.
+    pub fn with_format(path: impl AsRef<Path>, format: CameraFormat) -> NokhwaResult<Self> {
+        let path = path.as_ref();
+        let read_error = |why| NokhwaError::os_error(format!("Could not read {}", path.display()), why);
+
+        let frames = if path.is_dir() {
+            let mut files = std::fs::read_dir(path)
+                .and_then(|entries| entries.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>())
+                .map_err(read_error)?;
+            files.sort();
+            files
+                .iter()
+                .filter(|file| file.is_file())
+                .map(std::fs::read)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(read_error)?
+        } else {
+            split_frames(&std::fs::read(path).map_err(read_error)?, format)?
+        };
+
+        Self::from_frames(frames, format)
+    }
+
+    /// Plays `frames`, each a whole frame of `format`, e.g. a clip embedded with `include_bytes!`.
+    /// # Errors
+    /// Errors if `frames` is empty.
+    pub fn from_frames(frames: Vec<Vec<u8>>, format: CameraFormat) -> NokhwaResult<Self> {
+        if frames.is_empty() {
+            return Err(NokhwaError::OpenDeviceError(
+                "File".to_string(),
+                "The file holds no frames".to_string(),
+            ));
+        }
+
+        Ok(FileCamera {
+            streams: vec![Arc::new(frames)],
+            format: Mutex::new(format),
+            properties: Properties::empty(),
+            initial: (format, Properties::empty()),
+            running: HashMap::new(),
+            released: None,
+            read_only: false,
+            listed_formats: Vec::new(),
+            stills: Vec::new(),
+        })
+    }
+
+    /// Gives the camera `properties` as its controls. They have no effect on the frames played, but are read and set
+    /// like a real camera's, so code that handles controls can be run without one.
+    #[must_use]
+    pub fn with_properties(mut self, properties: Properties) -> Self {
+        self.initial.1 = properties.clone();
+        self.properties = properties;
+        self
+    }
+
+    /// Marks the camera [read-only](Capture::is_read_only): its formats and controls can still be read and set,
+    /// but it refuses to open a stream, like a device opened with [`OpenOptions::read_only`].
+    #[must_use]
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Adds a logical stream that plays `frames` in the camera's format, e.g. to stand in for a stereo camera's second
+    /// view. It is opened with [`open_stream_n`](Capture::open_stream_n) at the next index, and counted by
+    /// [`stream_count`](Setting::stream_count).
+    /// # Errors
+    /// Errors if `frames` is empty.
+    pub fn with_stream(mut self, frames: Vec<Vec<u8>>) -> NokhwaResult<Self> {
+        if frames.is_empty() {
+            return Err(NokhwaError::OpenDeviceError(
+                "File".to_string(),
+                format!("Stream {} holds no frames", self.streams.len()),
+            ));
+        }
+
+        self.streams.push(Arc::new(frames));
+        Ok(self)
+    }
+
+    /// Lists `formats` alongside the one played, e.g. to mimic a real camera's format list. They are only listed:
+    /// [`set_format`](Setting::set_format) still accepts just the played format.
+    #[must_use]
+    pub fn with_listed_formats(mut self, formats: impl IntoIterator<Item = CameraFormat>) -> Self {
+        self.listed_formats.extend(formats);
+        self
+    }
+
+    /// Gives the camera `frame` as a still of `format`, e.g. a photo at a higher resolution than the clip, for
+    /// [`capture_still`](Capture::capture_still) to pick from alongside the first frame of the clip.
+    #[must_use]
+    pub fn with_still(mut self, frame: Vec<u8>, format: CameraFormat) -> Self {
+        self.stills.push((format, frame));
+        self
+    }
+
+    fn current_format(&self) -> CameraFormat {
+        *self.format.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    // Starts playing the logical stream at `index` on a thread configured by `config`.
+    fn open_logical_stream(&mut self, index: usize, config: StreamConfig) -> Result<Stream, NokhwaError> {
+        self.ensure_streamable()?;
+        if config.io_method != IoMethod::Mmap {
+            return Err(NokhwaError::NotImplementedError(format!(
+                "{:?} I/O is not supported by file playback",
+                config.io_method
+            )));
+        }
+        if self.released.is_some() {
+            return Err(NokhwaError::OpenStreamError(
+                "The camera is released, call `reopen` first".to_string(),
+            ));
+        }
+        let frames = self.streams.get(index).cloned().ok_or_else(|| {
+            NokhwaError::OpenStreamError(format!(
+                "stream {index} does not exist, this file has {} streams",
+                self.streams.len()
+            ))
+        })?;
+        if self.running.get(&index).is_some_and(|running| running.load(Ordering::Acquire)) {
+            return Err(NokhwaError::OpenStreamError(format!("Stream {index} is already open")));
+        }
+
+        let format = self.current_format();
+        let (sender, receiver) = flume::bounded(CHANNEL_CAPACITY);
+        let running = Arc::new(AtomicBool::new(true));
+        let stats = Arc::new(StreamStatsRecorder::new());
+
+        let (started_sender, started) = flume::bounded(1);
+
+        let thread = {
+            let running = running.clone();
+            let stats = stats.clone();
+            std::thread::Builder::new()
+                .name("nokhwa-file-playback".to_string())
+                .spawn(move || {
+                    let applied = config.apply_to_current_thread();
+                    let play_frames = applied.is_ok();
+                    let _ = started_sender.send(applied);
+                    if play_frames {
+                        play(&frames, format, &sender, &running, &stats);
+                    }
+                })
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?
+        };
+
+        match started.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(why)) => {
+                let _ = thread.join();
+                return Err(why);
+            }
+            Err(_) => {
+                return Err(NokhwaError::OpenStreamError(
+                    "The playback thread panicked while starting".to_string(),
+                ))
+            }
+        }
+
+        self.running.insert(index, running.clone());
+        let stream = Stream::new(Box::new(FileStream {
+            receiver: Arc::new(receiver),
+            running,
+            thread: Some(thread),
+            stats,
+            format,
+        }));
+
+        if let Some(timeout) = config.first_frame_timeout {
+            if let Err(why) = stream.wait_for_first_frame(timeout) {
+                drop(stream);
+                if let Some(running) = self.running.remove(&index) {
+                    running.store(false, Ordering::Release);
+                }
+                return Err(why);
+            }
+        }
+
+        Ok(stream)
+    }
+}
+
+impl Setting for FileCamera {
+    fn enumerate_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        let mut formats = vec![self.current_format()];
+        formats.extend(&self.listed_formats);
+        Ok(formats)
+    }
+
+    fn enumerate_resolution_and_frame_rates(
+        &self,
+        frame_format: FrameFormat,
+    ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError> {
+        let mut resolutions: HashMap<Resolution, Vec<FrameRate>> = HashMap::new();
+        for format in self.enumerate_formats()? {
+            if format.format() == frame_format {
+                resolutions.entry(format.resolution()).or_default().push(format.frame_rate());
+            }
+        }
+
+        Ok(resolutions)
+    }
+
+    fn set_format(&self, camera_format: CameraFormat) -> Result<(), NokhwaError> {
+        let mut format = self.format.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if camera_format.resolution() != format.resolution() || camera_format.format() != format.format() {
+            return Err(NokhwaError::SetPropertyError {
+                property: "format".to_string(),
+                value: camera_format.to_string(),
+                error: format!("A file can only be played as {} {}", format.resolution(), format.format()),
+            });
+        }
+
+        *format = camera_format;
+        Ok(())
+    }
+
+    fn camera_format(&self) -> Option<CameraFormat> {
+        Some(self.current_format())
+    }
+
+    fn stream_count(&self) -> usize {
+        self.streams.len()
+    }
+
+    fn negotiated_fourcc(&self) -> Option<[u8; 4]> {
+        match self.current_format().format() {
+            FrameFormat::Yuyv422 => Some(*b"YUYV"),
+            FrameFormat::Nv12 => Some(*b"NV12"),
+            FrameFormat::MJpeg => Some(*b"MJPG"),
+            _ => None,
+        }
+    }
+
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn set_property(&mut self, property: &ControlId, value: ControlValue) -> Result<(), NokhwaError> {
+        self.switch_to_manual_for(property)?;
+        self.properties.set_control_value(property, value)
+    }
+}
+
+impl Capture for FileCamera {
+    fn open_stream(&mut self) -> Result<Stream, NokhwaError> {
+        self.open_stream_with(StreamConfig::default())
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Frames are played from memory, so only [`IoMethod::Mmap`] is accepted. The thread priority and affinity
+    /// apply to the playback thread.
+    fn open_stream_with(&mut self, config: StreamConfig) -> Result<Stream, NokhwaError> {
+        self.open_logical_stream(0, config)
+    }
+
+    /// Opens the logical stream at `index`, one of those added with [`with_stream`](FileCamera::with_stream). Each
+    /// can be open at the same time as the others.
+    fn open_stream_n(&mut self, index: usize) -> Result<Stream, NokhwaError> {
+        self.open_logical_stream(index, StreamConfig::default())
+    }
+
+    /// Picks among the stills given with [`with_still`](FileCamera::with_still) and the played format, returning the
+    /// still or the first frame of the clip. Playback is left as is.
+    fn capture_still(&mut self, format: RequestedFormat<'_>) -> Result<FrameBuffer, NokhwaError> {
+        self.ensure_streamable()?;
+        if self.released.is_some() {
+            return Err(NokhwaError::OpenStreamError(
+                "The camera is released, call `reopen` first".to_string(),
+            ));
+        }
+
+        let candidates = self
+            .stills
+            .iter()
+            .map(|(format, _)| *format)
+            .chain([self.current_format()])
+            .collect::<Vec<_>>();
+        let chosen = format.fulfill(&candidates).ok_or_else(|| NokhwaError::GetPropertyError {
+            property: "CameraFormat".to_string(),
+            error: "no format fulfills the request".to_string(),
+        })?;
+        let frame = self
+            .stills
+            .iter()
+            .find(|(format, _)| *format == chosen)
+            .map_or(&self.streams[0][0], |(_, frame)| frame);
+
+        Ok(FrameBuffer::new(chosen.resolution(), frame, chosen.format()))
+    }
+
+    fn close_stream(&mut self) -> Result<(), NokhwaError> {
+        for (_, running) in self.running.drain() {
+            running.store(false, Ordering::Release);
+        }
+        Ok(())
+    }
+}
+
+impl Camera for FileCamera {
+    fn release(&mut self) -> Result<(), NokhwaError> {
+        if self.released.is_some() {
+            return Ok(());
+        }
+
+        self.close_stream()?;
+        self.released = Some((self.current_format(), self.properties.snapshot()));
+        Ok(())
+    }
+
+    fn reopen(&mut self) -> Result<(), NokhwaError> {
+        let Some((format, snapshot)) = self.released.take() else {
+            return Ok(());
+        };
+
+        let (initial_format, initial_properties) = self.initial.clone();
+        *self.format.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = initial_format;
+        self.properties = initial_properties;
+
+        self.set_format(format)?;
+        self.restore_properties(&snapshot)
+    }
+
+    fn is_released(&self) -> bool {
+        self.released.is_some()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncSetting for FileCamera {
+    async fn enumerate_formats_async(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        self.enumerate_formats()
+    }
+
+    async fn enumerate_resolution_and_frame_rates_async(
+        &self,
+        frame_format: FrameFormat,
+    ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError> {
+        self.enumerate_resolution_and_frame_rates(frame_format)
+    }
+
+    async fn set_format_async(&self, camera_format: CameraFormat) -> Result<(), NokhwaError> {
+        self.set_format(camera_format)
+    }
+
+    async fn properties_async(&self) -> &Properties {
+        self.properties()
+    }
+
+    async fn set_property_async(&mut self, property: &ControlId, value: ControlValue) -> Result<(), NokhwaError> {
+        self.set_property(property, value)
+    }
+}
+
+/// Frames are played on a thread of their own, exactly like [`Capture::open_stream`], and awaiting them with
+/// [`Stream::await_frame`] never blocks the executor.
+#[cfg(feature = "async")]
+impl AsyncStream for FileCamera {
+    async fn open_stream_async(&mut self) -> Result<Stream, NokhwaError> {
+        self.open_stream()
+    }
+
+    async fn close_stream_async(&mut self) -> Result<(), NokhwaError> {
+        self.close_stream()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncCamera for FileCamera {}
+
+struct FileStream {
+    receiver: Arc<Receiver<FrameBuffer>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    stats: Arc<StreamStatsRecorder>,
+    format: CameraFormat,
+}
+
+impl StreamInnerTrait for FileStream {
+    fn receiver(&self) -> Arc<Receiver<FrameBuffer>> {
+        self.receiver.clone()
+    }
+
+    fn stop(&mut self) -> NokhwaResult<()> {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread
+                .join()
+                .map_err(|_| NokhwaError::StreamShutdownError("The playback thread panicked".to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn format(&self) -> Option<CameraFormat> {
+        Some(self.format)
+    }
+
+    fn stats(&self) -> StreamStats {
+        self.stats.stats()
+    }
+}
+
+// Sends `frames` on loop at `format`'s frame rate until stopped or the stream is dropped.
+fn play(
+    frames: &[Vec<u8>],
+    format: CameraFormat,
+    sender: &Sender<FrameBuffer>,
+    running: &AtomicBool,
+    stats: &StreamStatsRecorder,
+) {
+    let interval = format
+        .frame_rate()
+        .approximate_float()
+        .filter(|fps| *fps > 0.0)
+        .map_or(Duration::from_millis(33), |fps| Duration::from_secs_f32(1.0 / fps));
+    let mut next = Instant::now();
+
+    for frame in frames.iter().cycle() {
+        if !running.load(Ordering::Acquire) {
+            break;
+        }
+        if !stats.send_frame(sender, FrameBuffer::new(format.resolution(), frame, format.format())) {
+            break;
+        }
+
+        next += interval;
+        std::thread::sleep(next.saturating_duration_since(Instant::now()));
+    }
+
+    running.store(false, Ordering::Release);
+}
+
+// Reads the resolution and format out of a name like `clip_640x480.yuyv`.
+fn format_from_name(path: &Path) -> NokhwaResult<(Resolution, FrameFormat)> {
+    let error = || {
+        NokhwaError::OpenDeviceError(
+            path.display().to_string(),
+            "Expected a name like clip_640x480.yuyv (or .nv12, .mjpeg)".to_string(),
+        )
+    };
+
+    let frame_format = match path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).as_deref() {
+        Some("yuyv" | "yuy2") => FrameFormat::Yuyv422,
+        Some("nv12") => FrameFormat::Nv12,
+        Some("mjpeg" | "mjpg") => FrameFormat::MJpeg,
+        _ => return Err(error()),
+    };
+
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).ok_or_else(error)?;
+    let (width, height) = stem.rsplit('_').next().and_then(|size| size.split_once('x')).ok_or_else(error)?;
+    let resolution = Resolution::new(
+        width.parse().map_err(|_| error())?,
+        height.parse().map_err(|_| error())?,
+    );
+
+    Ok((resolution, frame_format))
+}
+
+// Splits a raw file into frames: fixed size chunks for uncompressed formats, or one whole JPEG after another for
+// MJPEG.
+fn split_frames(data: &[u8], format: CameraFormat) -> NokhwaResult<Vec<Vec<u8>>> {
+    if format.format() == FrameFormat::MJpeg {
+        let mut frames = Vec::new();
+        let mut rest = data;
+        while !rest.is_empty() {
+            let length = jpeg_length(rest).ok_or_else(|| {
+                NokhwaError::OpenDeviceError(
+                    format.to_string(),
+                    format!("No complete JPEG at byte {} of the file", data.len() - rest.len()),
+                )
+            })?;
+            frames.push(rest[..length].to_vec());
+            rest = &rest[length..];
+        }
+        return Ok(frames);
+    }
+
+    let frame_size = format
+        .format()
+        .byte_size(format.resolution())
+        .filter(|size| *size != 0)
+        .ok_or_else(|| {
+            NokhwaError::OpenDeviceError(
+                format.to_string(),
+                "Only uncompressed formats and MJPEG can be read from a file".to_string(),
+            )
+        })?;
+    if !data.len().is_multiple_of(frame_size) {
+        return Err(NokhwaError::OpenDeviceError(
+            format.to_string(),
+            format!("The file is {} bytes, not a whole number of {frame_size} byte frames", data.len()),
+        ));
+    }
+
+    Ok(data.chunks_exact(frame_size).map(<[u8]>::to_vec).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nokhwa_core::capture_thread::ThreadPriority;
+    use nokhwa_core::format_request::RequestedFormatType;
+    use nokhwa_core::properties::{ControlBody, ControlType, ControlValueDescriptor};
+    use nokhwa_core::ranges::Range;
+    use std::collections::HashSet;
+
+    // Two 2x2 luma frames, played fast enough that tests do not wait on the frame rate.
+    fn camera() -> FileCamera {
+        let format = CameraFormat::new(Resolution::new(2, 2), FrameFormat::Luma8, FrameRate::frame_rate(200));
+        FileCamera::from_frames(vec![vec![0; 4], vec![255; 4]], format).unwrap()
+    }
+
+    fn integer_control(value: i64, minimum: i64, maximum: i64, step: i64) -> ControlBody {
+        ControlBody::new(
+            ControlType::Integer,
+            HashSet::new(),
+            ControlValueDescriptor::Integer(Range::new(value, Some(minimum), Some(maximum), Some(step))),
+            Some(ControlValue::Integer(value)),
+            Some(ControlValue::Integer(value)),
+        )
+    }
+
+    #[test]
+    fn open_stream_with_applies_thread_config() {
+        let mut camera = camera();
+        let config = StreamConfig::new()
+            .with_thread_priority(ThreadPriority::Low)
+            .with_first_frame_timeout(Duration::from_secs(1));
+        let stream = camera.open_stream_with(config).unwrap();
+        assert_eq!(stream.poll_frame().unwrap().buffer().len(), 4);
+    }
+
+    #[test]
+    fn open_stream_with_reports_thread_config_errors() {
+        let mut camera = camera();
+        // No CPU is in an empty mask, so pinning the playback thread fails before any frame is played.
+        assert!(camera.open_stream_with(StreamConfig::new().with_cpu_affinity(0)).is_err());
+        assert!(camera.open_stream().is_ok());
+    }
+
+    #[test]
+    fn open_stream_with_rejects_other_io_methods() {
+        let mut camera = camera();
+        let config = StreamConfig::new().with_io_method(IoMethod::UserPtr);
+        assert!(matches!(camera.open_stream_with(config), Err(NokhwaError::NotImplementedError(_))));
+    }
+
+    #[test]
+    fn read_only_cameras_refuse_every_way_of_streaming() {
+        let mut camera = camera().read_only();
+        let refused = |result: Result<Stream, NokhwaError>| {
+            result.err().map(|why| why.to_string()) == Some(NokhwaError::read_only_stream().to_string())
+        };
+
+        assert!(camera.is_read_only());
+        assert!(refused(camera.open_stream()));
+        assert!(refused(camera.open_stream_n(0)));
+        assert!(refused(camera.open_stream_with(StreamConfig::default())));
+        assert_eq!(camera.camera_format().unwrap().resolution(), Resolution::new(2, 2));
+    }
+
+    #[test]
+    fn logical_streams_play_their_own_frames_side_by_side() {
+        let mut camera = camera().with_stream(vec![vec![100; 4]]).unwrap();
+        assert_eq!(camera.stream_count(), 2);
+
+        let first = camera.open_stream_n(0).unwrap();
+        let second = camera.open_stream_n(1).unwrap();
+        assert!(camera.open_stream_n(1).is_err());
+        assert!(camera.open_stream_n(2).is_err());
+
+        let timeout = Duration::from_secs(1);
+        let first = first.collect_frames(2, timeout).unwrap();
+        let second = second.collect_frames(2, timeout).unwrap();
+        assert!(first.iter().all(|frame| frame.buffer() == [0; 4] || frame.buffer() == [255; 4]));
+        assert!(second.iter().all(|frame| frame.buffer() == [100; 4]));
+
+        assert!(camera.with_stream(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn stills_are_captured_at_the_highest_resolution_asked_for() {
+        let still = CameraFormat::new(Resolution::new(4, 4), FrameFormat::Luma8, DEFAULT_FRAME_RATE);
+        let mut camera = camera().with_still(vec![50; 16], still);
+        let stream = camera.open_stream().unwrap();
+
+        let highest =
+            RequestedFormat::with_formats(RequestedFormatType::AbsoluteHighestResolution, &[FrameFormat::Luma8]);
+        let frame = camera.capture_still(highest).unwrap();
+        assert_eq!(frame.resolution(), Resolution::new(4, 4));
+        assert_eq!(frame.buffer(), [50; 16]);
+
+        // The stream keeps playing at the clip's resolution.
+        assert_eq!(stream.poll_frame().unwrap().resolution(), Resolution::new(2, 2));
+        assert_eq!(camera.camera_format().unwrap().resolution(), Resolution::new(2, 2));
+
+        let clip = RequestedFormatType::Exact(camera.camera_format().unwrap());
+        let exact = RequestedFormat::with_formats(clip, &[FrameFormat::Luma8]);
+        assert_eq!(camera.capture_still(exact).unwrap().buffer(), [0; 4]);
+        let nv12 = RequestedFormat::with_formats(RequestedFormatType::AbsoluteHighestResolution, &[FrameFormat::Nv12]);
+        assert!(camera.capture_still(nv12).is_err());
+    }
+
+    #[test]
+    fn the_fourcc_of_the_set_format_is_reported() {
+        let nv12 = CameraFormat::new(Resolution::new(2, 2), FrameFormat::Nv12, DEFAULT_FRAME_RATE);
+        let nv12_camera = FileCamera::from_frames(vec![vec![0; 6]], nv12).unwrap();
+        let mut slower = nv12;
+        slower.set_frame_rate(FrameRate::frame_rate(15));
+        nv12_camera.set_format(slower).unwrap();
+        assert_eq!(nv12_camera.negotiated_fourcc(), Some(*b"NV12"));
+
+        let yuyv = CameraFormat::new(Resolution::new(2, 2), FrameFormat::Yuyv422, DEFAULT_FRAME_RATE);
+        assert_eq!(FileCamera::from_frames(vec![vec![0; 8]], yuyv).unwrap().negotiated_fourcc(), Some(*b"YUYV"));
+        // Only YUYV, NV12 and MJPEG files are named by FourCC.
+        assert_eq!(camera().negotiated_fourcc(), None);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_streams_yield_the_clip_in_the_requested_format() {
+        use futures::StreamExt;
+
+        let clip = [[235, 128, 235, 128, 235, 128, 235, 128], [16, 128, 16, 128, 16, 128, 16, 128]].concat();
+        let path = std::env::temp_dir().join(format!("nokhwa_async_{}_2x2.yuyv", std::process::id()));
+        std::fs::write(&path, &clip).unwrap();
+        let mut platform = FilePlatform::new().with_path(&path);
+
+        let request = |frame_format: &'static [FrameFormat]| {
+            RequestedFormat::with_formats(RequestedFormatType::AbsoluteHighestResolution, frame_format)
+        };
+        let frames = platform.open_async_stream(&CameraIndex::Index(0), request(&[FrameFormat::Yuyv422])).await;
+        let unfulfilled = platform.open_async_stream(&CameraIndex::Index(0), request(&[FrameFormat::Nv12])).await;
+        std::fs::remove_file(&path).unwrap();
+
+        let mut frames = frames.unwrap();
+        assert_eq!(frames.camera().camera_format().unwrap().format(), FrameFormat::Yuyv422);
+        let first = frames.next().await.unwrap().unwrap();
+        let second = frames.next().await.unwrap().unwrap();
+        assert_eq!(first.to_rgb().unwrap(), vec![255; 12]);
+        assert_eq!(second.to_rgb().unwrap(), vec![0; 12]);
+        assert!(unfulfilled.is_err());
+    }
+
+    #[test]
+    fn frames_are_timestamped_in_capture_order() {
+        let mut camera = camera();
+        let stream = camera.open_stream().unwrap();
+        let frames = stream.collect_frames(3, Duration::from_secs(1)).unwrap();
+
+        let timestamps = frames.iter().map(FrameBuffer::timestamp_monotonic).collect::<Vec<_>>();
+        assert!(frames.iter().all(|frame| frame.timestamp().is_some()));
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn paused_streams_deliver_nothing_until_resumed() {
+        let mut camera = camera();
+        let mut stream = camera.open_stream().unwrap();
+        stream.wait_for_first_frame(Duration::from_secs(1)).unwrap();
+
+        stream.pause().unwrap();
+        assert!(stream.is_paused());
+        assert!(stream.try_poll_frame().unwrap().is_none());
+        // Playback carries on, but nothing it sends while paused is handed out.
+        assert!(stream.poll_frame_timeout(Duration::from_millis(50)).is_err());
+        assert_eq!(stream.stats().delivered, 0);
+
+        stream.resume().unwrap();
+        assert!(stream.poll_frame_timeout(Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn frames_are_dropped_once_the_channel_is_full() {
+        let mut camera = camera();
+        let stream = camera.open_stream().unwrap();
+        // At 200 frames per second this plays several times the channel's capacity.
+        std::thread::sleep(Duration::from_millis(100));
+
+        assert!(stream.stats().dropped > 0);
+        for _ in 0..CHANNEL_CAPACITY {
+            assert!(stream.try_poll_frame().unwrap().is_some());
+        }
+        assert_eq!(stream.stats().delivered, CHANNEL_CAPACITY as u64);
+    }
+
+    #[test]
+    fn thumbnails_are_grabbed_from_each_file_that_opens() {
+        let path = std::env::temp_dir().join(format!("nokhwa_thumbnail_{}_2x2.yuyv", std::process::id()));
+        std::fs::write(&path, [16, 128, 235, 128, 16, 128, 235, 128]).unwrap();
+        let mut platform = FilePlatform::new()
+            .with_path(&path)
+            .with_path(std::env::temp_dir().join("nokhwa_missing_2x2.yuyv"));
+
+        let request =
+            RequestedFormat::with_formats(RequestedFormatType::AbsoluteHighestResolution, &[FrameFormat::Yuyv422]);
+        let thumbnails = platform.query_with_thumbnails(request);
+        std::fs::remove_file(&path).unwrap();
+
+        let thumbnails = thumbnails.unwrap();
+        assert_eq!(thumbnails.len(), 2);
+        let thumbnail = thumbnails[0].1.as_ref().unwrap();
+        assert_eq!(thumbnail.resolution(), Resolution::new(2, 2));
+        assert_eq!(thumbnail.buffer(), &[16, 128, 235, 128, 16, 128, 235, 128]);
+        assert!(thumbnails[1].1.is_none());
+    }
+
+    // A JPEG with the structure the splitter walks, though not decodable. Its EXIF segment can carry a thumbnail,
+    // itself a whole JPEG.
+    fn jpeg(thumbnail: Option<&[u8]>) -> Vec<u8> {
+        fn segment(jpeg: &mut Vec<u8>, marker: u8, payload: &[u8]) {
+            jpeg.extend_from_slice(&[0xFF, marker]);
+            jpeg.extend_from_slice(&u16::try_from(payload.len() + 2).unwrap().to_be_bytes());
+            jpeg.extend_from_slice(payload);
+        }
+
+        let mut exif = b"Exif\0\0".to_vec();
+        exif.extend_from_slice(thumbnail.unwrap_or_default());
+        let mut jpeg = vec![0xFF, 0xD8];
+        segment(&mut jpeg, 0xE1, &exif);
+        segment(&mut jpeg, 0xDB, &[0; 65]);
+        segment(&mut jpeg, 0xDA, &[1, 1, 0, 0, 63, 0]);
+        // Entropy coded data with a stuffed 0xFF and a restart marker.
+        jpeg.extend_from_slice(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    #[test]
+    fn mjpeg_files_split_into_whole_jpegs() {
+        let format = CameraFormat::new(Resolution::new(2, 2), FrameFormat::MJpeg, DEFAULT_FRAME_RATE);
+        let with_thumbnail = jpeg(Some(&jpeg(None)));
+        let plain = jpeg(None);
+        let file = [with_thumbnail.as_slice(), &plain].concat();
+
+        assert_eq!(split_frames(&file, format).unwrap(), vec![with_thumbnail.clone(), plain.clone()]);
+
+        let leading_garbage = [&[0x00, 0x01][..], &plain].concat();
+        assert!(split_frames(&leading_garbage, format).is_err());
+        assert!(split_frames(&with_thumbnail[..with_thumbnail.len() - 2], format).is_err());
+    }
+
+    #[test]
+    fn raw_clips_play_and_decode_through_the_camera_api() {
+        // A white frame, then a black one.
+        let clip = [[235, 128, 235, 128, 235, 128, 235, 128], [16, 128, 16, 128, 16, 128, 16, 128]].concat();
+        let path = std::env::temp_dir().join(format!("nokhwa_clip_{}_2x2.yuyv", std::process::id()));
+        std::fs::write(&path, &clip).unwrap();
+        let camera = FileCamera::open(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut camera = camera.unwrap();
+        let format = camera.camera_format().unwrap();
+        assert_eq!((format.resolution(), format.format()), (Resolution::new(2, 2), FrameFormat::Yuyv422));
+        let frames = camera.open_stream().unwrap().collect_frames(2, Duration::from_secs(1)).unwrap();
+        assert_eq!(frames[0].to_rgb().unwrap(), vec![255; 12]);
+        assert_eq!(frames[1].to_rgb().unwrap(), vec![0; 12]);
+
+        let nv12 = CameraFormat::new(Resolution::new(2, 2), FrameFormat::Nv12, DEFAULT_FRAME_RATE);
+        let mut camera = FileCamera::from_frames(vec![vec![235, 235, 235, 235, 128, 128]], nv12).unwrap();
+        let frame = camera.open_stream().unwrap().poll_frame().unwrap();
+        assert_eq!(frame.to_rgb().unwrap(), vec![255; 12]);
+    }
+
+    #[test]
+    fn uncompressed_formats_are_picked_over_larger_mjpeg_ones() {
+        let yuyv = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::Yuyv422, FrameRate::frame_rate(10));
+        let mjpeg = CameraFormat::new(Resolution::new(3840, 2160), FrameFormat::MJpeg, FrameRate::frame_rate(30));
+        let camera = FileCamera::from_frames(vec![jpeg(None)], mjpeg)
+            .unwrap()
+            .with_listed_formats([yuyv]);
+
+        assert_eq!(camera.enumerate_formats().unwrap(), vec![mjpeg, yuyv]);
+        assert_eq!(camera.max_uncompressed_format(), Some(yuyv));
+        assert!(camera.has_uncompressed_at(Resolution::new(1280, 720)));
+        assert!(!camera.has_uncompressed_at(Resolution::new(3840, 2160)));
+        // Listed formats cannot be played.
+        assert!(camera.set_format(yuyv).is_err());
+
+        let mjpeg_only = FileCamera::from_frames(vec![jpeg(None)], mjpeg).unwrap();
+        assert_eq!(mjpeg_only.max_uncompressed_format(), None);
+    }
+
+    #[test]
+    fn typed_in_resolutions_snap_to_the_nearest_supported_one() {
+        let yuyv = |width, height| {
+            CameraFormat::new(Resolution::new(width, height), FrameFormat::Yuyv422, DEFAULT_FRAME_RATE)
+        };
+        let camera = FileCamera::from_frames(vec![vec![0; 640 * 480 * 2]], yuyv(640, 480))
+            .unwrap()
+            .with_listed_formats([yuyv(1280, 720), yuyv(1024, 576)]);
+        let requested = Resolution::new(1000, 600);
+
+        assert_eq!(camera.clamp_resolution(requested, FrameFormat::Yuyv422).unwrap(), Resolution::new(1024, 576));
+        assert!(camera.clamp_resolution(requested, FrameFormat::Nv12).is_err());
+
+        // 976x624 is as far from 1000x600 as 1024x576 is, but closer to its 5:3 aspect ratio.
+        let camera = camera.with_listed_formats([yuyv(976, 624)]);
+        assert_eq!(camera.clamp_resolution(requested, FrameFormat::Yuyv422).unwrap(), Resolution::new(976, 624));
+    }
+
+    #[test]
+    fn reopen_restores_the_format_and_controls_from_before_release() {
+        let properties = Properties::new(HashMap::from([(ControlId::Gain, integer_control(0, 0, 100, 1))]));
+        let mut camera = camera().with_properties(properties);
+        let format = CameraFormat::new(Resolution::new(2, 2), FrameFormat::Luma8, FrameRate::frame_rate(15));
+        camera.set_format(format).unwrap();
+        camera.set_property(&ControlId::Gain, ControlValue::Integer(40)).unwrap();
+
+        camera.release().unwrap();
+        assert!(camera.is_released());
+        assert!(camera.open_stream().is_err());
+
+        camera.reopen().unwrap();
+        assert!(!camera.is_released());
+        assert_eq!(camera.camera_format(), Some(format));
+        assert_eq!(camera.read_property(&ControlId::Gain).unwrap(), ControlValue::Integer(40));
+        assert!(camera.open_stream().is_ok());
+    }
+}
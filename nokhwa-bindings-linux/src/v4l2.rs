@@ -1,20 +1,44 @@
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::mem::ManuallyDrop;
+use std::num::NonZeroI32;
+#[cfg(feature = "async")]
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use v4l::{Device, Format, FourCC, Fraction};
-use v4l2_sys_mit::{V4L2_CID_AUTO_WHITE_BALANCE, V4L2_CID_BACKLIGHT_COMPENSATION, V4L2_CID_BRIGHTNESS, V4L2_CID_CONTRAST, V4L2_CID_DO_WHITE_BALANCE, V4L2_CID_EXPOSURE, V4L2_CID_FOCUS_ABSOLUTE, V4L2_CID_FOCUS_RELATIVE, V4L2_CID_GAIN, V4L2_CID_GAMMA, V4L2_CID_HUE, V4L2_CID_HUE_AUTO, V4L2_CID_IRIS_ABSOLUTE, V4L2_CID_IRIS_RELATIVE, V4L2_CID_PAN_ABSOLUTE, V4L2_CID_PAN_RELATIVE, V4L2_CID_SATURATION, V4L2_CID_SHARPNESS, V4L2_CID_TILT_ABSOLUTE, V4L2_CID_TILT_RELATIVE, V4L2_CID_WHITE_BALANCE_TEMPERATURE, V4L2_CID_ZOOM_ABSOLUTE, V4L2_CID_ZOOM_CONTINUOUS, V4L2_CID_ZOOM_RELATIVE};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use flume::{Receiver, Sender};
+use num_rational::Rational32;
+use v4l::format::FieldOrder;
+use v4l::{capability, control, Control, Device, Format, FourCC, Fraction};
+use v4l2_sys_mit::{v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE, v4l2_buffer, v4l2_cropcap, v4l2_exportbuffer, v4l2_format, v4l2_format__bindgen_ty_1};
+use v4l2_sys_mit::{V4L2_CID_ANALOGUE_GAIN, V4L2_CID_AUTO_EXPOSURE_BIAS, V4L2_CID_AUTO_WHITE_BALANCE, V4L2_CID_DIGITAL_GAIN, V4L2_CID_EXPOSURE_ABSOLUTE, V4L2_CID_EXPOSURE_AUTO, V4L2_CID_EXPOSURE_AUTO_PRIORITY, V4L2_CID_FOCUS_ABSOLUTE, V4L2_CID_FOCUS_AUTO, V4L2_CID_FOCUS_RELATIVE, V4L2_CID_GAIN, V4L2_CID_IRIS_ABSOLUTE, V4L2_CID_IRIS_RELATIVE, V4L2_CID_ISO_SENSITIVITY, V4L2_CID_ISO_SENSITIVITY_AUTO, V4L2_CID_WHITE_BALANCE_TEMPERATURE, V4L2_CID_WIDE_DYNAMIC_RANGE, V4L2_CID_ZOOM_ABSOLUTE};
+#[cfg(feature = "async")]
+use async_io::Async;
 use v4l::device::Handle;
 use v4l::frameinterval::FrameIntervalEnum;
+use v4l::buffer::{Metadata, Type};
+use v4l::io::traits::{CaptureStream, Stream as V4lStream};
 use v4l::prelude::MmapStream;
-use v4l::video::{Capture as V4lCapture, Output};
-use v4l::video::output::Parameters;
-use nokhwa_core::frame_buffer::FrameBuffer;
-use nokhwa_core::camera::{Camera, Open, Setting, Capture};
-use nokhwa_core::properties::{CameraProperties, CameraPropertyFlag, CameraPropertyId, CameraPropertyValue};
+use v4l::video::capture::Parameters;
+use v4l::video::Capture as V4lCapture;
+use nokhwa_core::frame_buffer::{FrameBuffer, RawTimestamp, TimestampClock};
+use nokhwa_core::camera::{Camera, Setting, Capture};
+#[cfg(feature = "async")]
+use nokhwa_core::camera::AsyncStream;
+use nokhwa_core::properties::{ControlBody, ControlFlags, ControlId, ControlType, ControlValue, ControlValueDescriptor, ControlValuePrimitive, PlatformSpecificControlId, Properties};
 use nokhwa_core::{define_back_and_fourth_control, define_back_and_fourth_frame_format};
 use nokhwa_core::error::{NokhwaError, NokhwaResult};
-use nokhwa_core::frame_format::FrameFormat;
-use nokhwa_core::types::{CameraFormat, CameraIndex, CameraInformation, FrameRate, Resolution};
+use nokhwa_core::format_request::RequestedFormat;
+use nokhwa_core::frame_format::{FrameFormat, ScanType};
+use nokhwa_core::platform::{Backends, OpenOptions, PlatformTrait};
+use nokhwa_core::stream::{IoMethod, Stream, StreamConfig, StreamInnerTrait, StreamStats, StreamStatsRecorder};
+use nokhwa_core::ranges::Range;
+use nokhwa_core::types::{BusSpeed, CameraFormat, CameraIndex, CameraInformation, DeviceCapabilities, FrameRate, FrameRateSupport, Resolution};
 
 const NULL_FCC: &'static [u8; 4] = &[0x00, 0x00, 0x00, 0x00];
 
@@ -67,54 +91,253 @@ define_back_and_fourth_frame_format!([u8;4], {
     FrameFormat::Bayer16 => b"BYR2",
 }, func_u8_8_to_fcc, func_fcc_to_u8_8, value_to_fcc_type);
 
-fn linux_id_to_str(id: u32) -> String {
-    id.to_string()
+// V4L2 frame intervals are seconds per frame, the inverse of a frame rate.
+fn interval_to_frame_rate(interval: Fraction) -> Option<FrameRate> {
+    let numerator = i32::try_from(interval.denominator).ok()?;
+    let denominator = NonZeroI32::new(i32::try_from(interval.numerator).ok()?)?;
+    Some(FrameRate::new(numerator, denominator))
 }
 
-fn str_to_linux_id(id: &str) -> Option<u32> {
-    u32::from_str(id).ok()
+fn linux_id_to_platform_specific(id: u32) -> PlatformSpecificControlId {
+    PlatformSpecificControlId::from(id)
 }
 
+fn platform_specific_to_linux_id(id: PlatformSpecificControlId) -> Option<u32> {
+    u32::try_from(id).ok()
+}
+
+// Sensor drivers often expose analog and digital gain separately, while UVC cameras only have `V4L2_CID_GAIN`.
+// Only cameras with a WDR mode have `V4L2_CID_WIDE_DYNAMIC_RANGE`. Controls without a `ControlId` of their own, like
+// brightness, are reached as `ControlId::PlatformSpecific` with their `V4L2_CID_*` value.
 define_back_and_fourth_control!(u32, {
-    CameraPropertyId::BacklightCompensation, None => V4L2_CID_BACKLIGHT_COMPENSATION,
-    CameraPropertyId::Brightness, None => V4L2_CID_BRIGHTNESS,
-    CameraPropertyId::Contrast, None => V4L2_CID_CONTRAST,
-    CameraPropertyId::Exposure, None => V4L2_CID_EXPOSURE,
-    CameraPropertyId::Focus, Some(CameraPropertyFlag::Relative) => V4L2_CID_FOCUS_RELATIVE,
-    CameraPropertyId::Focus, Some(CameraPropertyFlag::Absolute) => V4L2_CID_FOCUS_ABSOLUTE,
-    CameraPropertyId::Gamma, None => V4L2_CID_GAMMA,
-    CameraPropertyId::Gain, None => V4L2_CID_GAIN,
-    CameraPropertyId::Hue, None => V4L2_CID_HUE,
-    CameraPropertyId::Hue, Some(CameraPropertyFlag::Automatic) => V4L2_CID_HUE_AUTO,
-    CameraPropertyId::Iris, Some(CameraPropertyFlag::Relative) => V4L2_CID_IRIS_RELATIVE,
-    CameraPropertyId::Iris, Some(CameraPropertyFlag::Absolute) => V4L2_CID_IRIS_ABSOLUTE,
-    CameraPropertyId::Saturation, None => V4L2_CID_SATURATION,
-    CameraPropertyId::Sharpness, None => V4L2_CID_SHARPNESS,
-    CameraPropertyId::Pan, Some(CameraPropertyFlag::Absolute) => V4L2_CID_PAN_ABSOLUTE,
-    CameraPropertyId::Pan, Some(CameraPropertyFlag::Relative) => V4L2_CID_PAN_RELATIVE,
-    // CameraPropertyId::Pan, None => V4L2_CID_PAN_ABSOLUTE,
-    // CameraPropertyId::Tilt, None => V4L2_CID_TILT_ABSOLUTE,
-    CameraPropertyId::Tilt, Some(CameraPropertyFlag::Absolute) => V4L2_CID_TILT_ABSOLUTE,
-    CameraPropertyId::Tilt, Some(CameraPropertyFlag::Relative) => V4L2_CID_TILT_RELATIVE,
-    // CameraPropertyId::Zoom, None => V4L2_CID_ZOOM_ABSOLUTE,
-    CameraPropertyId::WhiteBalance, None => V4L2_CID_WHITE_BALANCE_TEMPERATURE,
-    CameraPropertyId::WhiteBalance, Some(CameraPropertyFlag::Automatic) => V4L2_CID_AUTO_WHITE_BALANCE,
-    CameraPropertyId::WhiteBalance, Some(CameraPropertyFlag::Enable) => V4L2_CID_DO_WHITE_BALANCE,
-    CameraPropertyId::Zoom, Some(CameraPropertyFlag::Absolute) => V4L2_CID_ZOOM_ABSOLUTE,
-    CameraPropertyId::Zoom, Some(CameraPropertyFlag::Relative) => V4L2_CID_ZOOM_RELATIVE,
-    CameraPropertyId::Zoom, Some(CameraPropertyFlag::Continuous) => V4L2_CID_ZOOM_CONTINUOUS,
-    // CameraPropertyId::Iris, None => V4L2_CID_IRIS_ABSOLUTE,
-
-}, linux_id_to_str, str_to_linux_id);
+    ControlId::FocusMode => V4L2_CID_FOCUS_AUTO,
+    ControlId::FocusAbsolute => V4L2_CID_FOCUS_ABSOLUTE,
+    ControlId::FocusRelative => V4L2_CID_FOCUS_RELATIVE,
+    ControlId::ExposureMode => V4L2_CID_EXPOSURE_AUTO,
+    ControlId::ExposureBias => V4L2_CID_AUTO_EXPOSURE_BIAS,
+    ControlId::ExposureTime => V4L2_CID_EXPOSURE_ABSOLUTE,
+    ControlId::ExposureAutoPriority => V4L2_CID_EXPOSURE_AUTO_PRIORITY,
+    ControlId::ExposureIsoMode => V4L2_CID_ISO_SENSITIVITY_AUTO,
+    ControlId::ExposureIsoSensitivity => V4L2_CID_ISO_SENSITIVITY,
+    ControlId::ExposureApertureAbsolute => V4L2_CID_IRIS_ABSOLUTE,
+    ControlId::ExposureApertureRelative => V4L2_CID_IRIS_RELATIVE,
+    ControlId::Gain => V4L2_CID_GAIN,
+    ControlId::AnalogGain => V4L2_CID_ANALOGUE_GAIN,
+    ControlId::DigitalGain => V4L2_CID_DIGITAL_GAIN,
+    ControlId::WhiteBalanceMode => V4L2_CID_AUTO_WHITE_BALANCE,
+    ControlId::WhiteBalanceTemperature => V4L2_CID_WHITE_BALANCE_TEMPERATURE,
+    ControlId::ZoomAbsolute => V4L2_CID_ZOOM_ABSOLUTE,
+    ControlId::HdrMode => V4L2_CID_WIDE_DYNAMIC_RANGE,
+}, linux_id_to_platform_specific, platform_specific_to_linux_id);
+
+/// Reads the USB speed of `/dev/video{index}` from sysfs: the `speed` attribute of the USB device owning the
+/// video interface, in Mbit/s. Returns `None` for devices that are not on USB.
+pub fn bus_speed(index: usize) -> Option<BusSpeed> {
+    let speed = std::fs::read_to_string(format!("/sys/class/video4linux/video{index}/device/../speed")).ok()?;
+    Some(match speed.trim() {
+        "12" => BusSpeed::Full,
+        "480" => BusSpeed::High,
+        "5000" => BusSpeed::Super,
+        "10000" | "20000" => BusSpeed::SuperPlus,
+        _ => BusSpeed::Unknown,
+    })
+}
+
+/// Reads the firmware revision of `/dev/video{index}` from sysfs: the `bcdDevice` attribute of the USB device
+/// owning the video interface, formatted like `"1.00"`. Returns `None` for devices that are not on USB.
+pub fn firmware_version(index: usize) -> Option<String> {
+    let bcd = std::fs::read_to_string(format!("/sys/class/video4linux/video{index}/device/../bcdDevice")).ok()?;
+    let bcd = u16::from_str_radix(bcd.trim(), 16).ok()?;
+    // Binary coded decimal, `0x0123` is 1.23.
+    let major = (bcd >> 12) * 10 + ((bcd >> 8) & 0xF);
+    Some(format!("{major}.{:02x}", bcd & 0xFF))
+}
+
+/// Formats the driver `version` `VIDIOC_QUERYCAP` reported (the kernel's, for in-tree drivers) like `"6.8.12"`.
+#[must_use]
+pub fn driver_version((major, minor, patch): (u8, u8, u8)) -> String {
+    format!("{major}.{minor}.{patch}")
+}
+
+/// Builds the [`CameraInformation`] for `/dev/video{index}` from what `VIDIOC_QUERYCAP` reported: the card as the
+/// name, the bus as the description and the driver as misc, along with the driver version and
+/// [`DeviceCapabilities`]. The [`bus_speed`] and [`firmware_version`] are read from sysfs, and
+/// [`is_virtual_device`] sets whether it is [reported virtual](CameraInformation::reported_virtual).
+pub fn camera_information(index: usize, caps: &capability::Capabilities) -> CameraInformation {
+    let mut info = CameraInformation::new(
+        caps.card.clone(),
+        caps.bus.clone(),
+        caps.driver.clone(),
+        CameraIndex::Index(index as u32),
+    );
+    info.set_driver_version(Some(driver_version(caps.version)));
+    info.set_capabilities(Some(capabilities_from_flags(caps.capabilities)));
+    info.set_bus_speed(bus_speed(index));
+    info.set_firmware_version(firmware_version(index));
+    info.set_reported_virtual(Some(is_virtual_device(&caps.driver, caps.capabilities)));
+    info
+}
+
+/// Whether a device with the `driver` and `device_caps` `VIDIOC_QUERYCAP` reported is a virtual camera: either the
+/// `v4l2loopback` driver (used by OBS and others), or a node that also accepts output, which a physical camera does
+/// not.
+///
+/// `device_caps` must describe this node alone, as v4l's [`Capabilities::capabilities`](capability::Capabilities)
+/// does, not the whole physical device: capture cards and codecs often have a separate output node. Memory to
+/// memory nodes, such as hardware codecs, are not virtual cameras either.
+pub fn is_virtual_device(driver: &str, device_caps: capability::Flags) -> bool {
+    let memory_to_memory = device_caps.intersects(capability::Flags::VIDEO_M2M | capability::Flags::VIDEO_M2M_MPLANE);
+    driver == "v4l2 loopback" || (device_caps.contains(capability::Flags::VIDEO_OUTPUT) && !memory_to_memory)
+}
+
+/// The pixel aspect ratio `VIDIOC_CROPCAP` reported, as width over height, with the resolution of the default crop
+/// rectangle, which is captured without scaling.
+pub fn cropcap_pixel_aspect_ratio(cropcap: &v4l2_cropcap) -> Option<(Resolution, Rational32)> {
+    // V4L2 reports the ratio as height over width.
+    let (height, width) = (cropcap.pixelaspect.numerator, cropcap.pixelaspect.denominator);
+    if height == 0 || width == 0 {
+        return None;
+    }
+    let ratio = Rational32::new(i32::try_from(width).ok()?, i32::try_from(height).ok()?);
+    Some((Resolution::new(cropcap.defrect.width, cropcap.defrect.height), ratio))
+}
+
+/// Maps a V4L2 field order to a [`ScanType`]. `V4L2_FIELD_INTERLACED` leaves the order to the video standard;
+/// it is taken as top field first, as for everything but NTSC. Field orders that do not interleave the fields line
+/// by line (single or sequential fields) are progressive as far as `nokhwa` is concerned.
+pub fn scan_type_from_field(field: FieldOrder) -> ScanType {
+    match field {
+        FieldOrder::Interlaced | FieldOrder::InterlacedTB => ScanType::InterlacedTff,
+        FieldOrder::InterlacedBT => ScanType::InterlacedBff,
+        _ => ScanType::Progressive,
+    }
+}
+
+/// Maps the `capabilities` flags `VIDIOC_QUERYCAP` reports to [`DeviceCapabilities`].
+pub fn capabilities_from_flags(flags: capability::Flags) -> DeviceCapabilities {
+    DeviceCapabilities {
+        video_capture: flags.contains(capability::Flags::VIDEO_CAPTURE),
+        streaming: flags.contains(capability::Flags::STREAMING),
+        read_write: flags.contains(capability::Flags::READ_WRITE),
+    }
+}
+
+/// Picks the [`IoMethod`] to capture with on a device with `capabilities`, when `requested` was asked for.
+///
+/// [`IoMethod::Mmap`] falls back to [`IoMethod::Read`] on devices that cannot stream but can be read from.
+/// # Errors
+/// Errors if the device supports neither `requested` nor its fallback.
+pub fn select_io_method(requested: IoMethod, capabilities: DeviceCapabilities) -> NokhwaResult<IoMethod> {
+    match (requested, capabilities.preferred_io_method()) {
+        (IoMethod::Mmap, Some(IoMethod::Read)) => Ok(IoMethod::Read),
+        (IoMethod::Read, _) if capabilities.read_write => Ok(IoMethod::Read),
+        (IoMethod::Read, _) => Err(NokhwaError::OpenStreamError("device does not support read() I/O".to_string())),
+        (_, Some(IoMethod::Mmap)) => Ok(requested),
+        _ => Err(NokhwaError::OpenStreamError(format!(
+            "device does not support streaming I/O ({capabilities:?})"
+        ))),
+    }
+}
+
+// Picks the `ControlType` and `ControlValueDescriptor` for a control from its `VIDIOC_QUERYCTRL` description, or
+// `None` for control class headers and compound controls, which hold no single value.
+fn describe_control(description: &control::Description) -> Option<(ControlType, ControlValueDescriptor)> {
+    match description.typ {
+        control::Type::Integer | control::Type::Integer64 => {
+            Some((ControlType::Integer, describe_integer(description)?))
+        }
+        control::Type::Menu => Some((ControlType::Menu, describe_menu(description)?)),
+        control::Type::IntegerMenu => Some((ControlType::IntegerMenu, describe_menu(description)?)),
+        control::Type::Boolean => Some((ControlType::BinaryMenu, ControlValueDescriptor::Boolean)),
+        control::Type::Button => Some((ControlType::Button, ControlValueDescriptor::Null)),
+        control::Type::String => Some((ControlType::String, ControlValueDescriptor::String)),
+        control::Type::Bitmask => Some((ControlType::Bitmask, ControlValueDescriptor::BitMask)),
+        _ => None,
+    }
+}
+
+// Describes an integer control by the minimum, maximum, step and default it reports.
+fn describe_integer(description: &control::Description) -> Option<ControlValueDescriptor> {
+    if !matches!(description.typ, control::Type::Integer | control::Type::Integer64) {
+        return None;
+    }
+
+    // a step of 0 is reported by controls with a single value
+    let step = i64::try_from(description.step).ok().filter(|step| *step > 0);
+    Some(ControlValueDescriptor::Integer(Range::new(
+        description.default,
+        Some(description.minimum),
+        Some(description.maximum),
+        step,
+    )))
+}
+
+// Names each item of a menu control, by its label or, for integer menus, its value.
+fn describe_menu(description: &control::Description) -> Option<ControlValueDescriptor> {
+    let items = description.items.as_ref()?;
+    Some(ControlValueDescriptor::menu_from_items(items.iter().map(|(index, item)| {
+        let label = match item {
+            control::MenuItem::Name(name) => name.clone(),
+            control::MenuItem::Value(value) => value.to_string(),
+        };
+        (i64::from(*index), label)
+    })))
+}
+
+// Converts a value read from the driver to the form `descriptor` accepts: menu indices become their labelled item,
+// and the 0 or 1 of a boolean control a `ControlValue::Boolean`.
+fn typed_value(descriptor: &ControlValueDescriptor, value: ControlValue) -> ControlValue {
+    match (descriptor, value) {
+        (ControlValueDescriptor::Menu(_), ControlValue::Integer(index)) => match descriptor.menu_label(index) {
+            Some(label) => ControlValue::KeyValue(label.to_string(), ControlValuePrimitive::Integer(index)),
+            None => ControlValue::Integer(index),
+        },
+        (ControlValueDescriptor::Boolean, ControlValue::Integer(value)) => ControlValue::Boolean(value != 0),
+        (_, value) => value,
+    }
+}
+
+fn control_flags(flags: control::Flags) -> HashSet<ControlFlags> {
+    [
+        (control::Flags::DISABLED, ControlFlags::Disabled),
+        (control::Flags::GRABBED, ControlFlags::Busy),
+        (control::Flags::READ_ONLY, ControlFlags::ReadOnly),
+        (control::Flags::UPDATE, ControlFlags::CascadingUpdates),
+        (control::Flags::INACTIVE, ControlFlags::Inactive),
+        (control::Flags::SLIDER, ControlFlags::Slider),
+        (control::Flags::WRITE_ONLY, ControlFlags::WriteOnly),
+        (control::Flags::VOLATILE, ControlFlags::ContinuousChange),
+        (control::Flags::EXECUTE_ON_WRITE, ControlFlags::ExecuteOnWrite),
+    ]
+    .into_iter()
+    .filter(|(v4l2_flag, _)| flags.contains(*v4l2_flag))
+    .map(|(_, flag)| flag)
+    .collect()
+}
 
 pub struct DeviceInner {
     device: Device,
+    read_only: bool,
 }
 
 impl DeviceInner {
     pub fn new(index: usize) -> Result<Self, NokhwaError> {
         let device = Device::new(index).map_err(|why| NokhwaError::OpenDeviceError(index.to_string(), why.to_string()))?;
-        Ok(DeviceInner { device })
+        Ok(DeviceInner { device, read_only: false })
+    }
+
+    /// Opens the device for querying formats and controls only.
+    ///
+    /// V4L2 only takes exclusive ownership of a device once buffers are requested, so this opens the device as
+    /// normal and [`V4L2Camera`] refuses to stream from it, leaving it free for other applications.
+    pub fn new_read_only(index: usize) -> Result<Self, NokhwaError> {
+        let mut device = Self::new(index)?;
+        device.read_only = true;
+        Ok(device)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
 
@@ -123,34 +346,1509 @@ impl DeviceInner {
         Ok(resolutions)
     }
 
-    pub fn frame_rates(&self, fourcc: FourCC, resolution: Resolution) -> Result<Vec<FrameRate>, NokhwaError> {
-        let frame_rates = match self.device.enum_frameintervals(fourcc, resolution.width(), resolution.height()) {
-            Ok(intervals) => intervals.into_iter().map(|x| match x.interval {
-                FrameIntervalEnum::Discrete(d) => vec![d],
+    /// Lists the frame rates supported at `resolution`. Stepwise and continuous interval specs are reported
+    /// as a [`Range`] rather than sampled, so callers can pick any rate inside them.
+    pub fn frame_rates(&self, fourcc: FourCC, resolution: Resolution) -> Result<FrameRateSupport, NokhwaError> {
+        let intervals = self.device.enum_frameintervals(fourcc, resolution.width(), resolution.height()).map_err(|why| NokhwaError::GetPropertyError { property: "enum_frameintervals".to_string(), error: why.to_string() })?;
+
+        let mut support = FrameRateSupport::default();
+        for interval in intervals {
+            match interval.interval {
+                FrameIntervalEnum::Discrete(d) => support.discrete.extend(interval_to_frame_rate(d)),
                 FrameIntervalEnum::Stepwise(step) => {
-                    let mut temp_vec = vec![];
-                    for rate in (step.min.numerator..step.max.numerator).step_by(step.step.numerator as usize) {
-                        temp_vec.push(Fraction::new(rate, step.min.denominator))
+                    // Intervals are seconds per frame, so the shortest interval is the highest frame rate.
+                    // Evenly spaced intervals are not evenly spaced frame rates, so the range has no step.
+                    if let (Some(slowest), Some(fastest)) = (interval_to_frame_rate(step.max), interval_to_frame_rate(step.min)) {
+                        support.add_stepwise(Range::new(fastest, Some(slowest), Some(fastest), None));
                     }
-                    temp_vec
                 }
-            }),
-            Err(why) => {
-                return Err(NokhwaError::GetPropertyError { property: "enum_frameintervals".to_string(), error: why.to_string() })
             }
-        }.into_iter().flatten().map(|x| FrameRate::new(x.numerator, x.denominator)).collect::<Vec<FrameRate>>();
-        Ok(frame_rates)
+        }
+
+        Ok(support)
+    }
+
+    /// Describes every control the device has, with its current value. Controls in the [`ControlIdIntermediate`]
+    /// table are keyed by their [`ControlId`] (see [`supported_control_ids`](DeviceInner::supported_control_ids)),
+    /// the rest by [`ControlId::PlatformSpecific`].
+    ///
+    /// Menu controls are described with their item labels (see [`menu_descriptor`](DeviceInner::menu_descriptor)),
+    /// and hold the selected item as a [`ControlValue::KeyValue`] of its label and index.
+    pub fn properties(&self) -> NokhwaResult<Properties> {
+        let descriptions = self.device.query_controls().map_err(|why| NokhwaError::GetPropertyError {
+            property: "controls".to_string(),
+            error: why.to_string(),
+        })?;
+
+        let controls = descriptions
+            .iter()
+            .filter_map(|description| {
+                let (control_type, descriptor) = describe_control(description)?;
+                // Write only controls, such as buttons, have no value to read back.
+                let value = (!description.flags.contains(control::Flags::WRITE_ONLY))
+                    .then(|| self.platform_control(description.id).ok())
+                    .flatten()
+                    .map(|value| typed_value(&descriptor, value));
+                let default_value = match description.typ {
+                    control::Type::Button | control::Type::String => None,
+                    control::Type::Bitmask => Some(ControlValue::BitMask(description.default)),
+                    _ => Some(typed_value(&descriptor, ControlValue::Integer(description.default))),
+                };
+
+                let body = ControlBody::new(
+                    control_type,
+                    control_flags(description.flags),
+                    descriptor,
+                    value,
+                    default_value,
+                );
+                Some((ControlIdIntermediate::into_control_id(description.id), body))
+            })
+            .collect();
+
+        Ok(Properties::new(controls))
+    }
+
+    /// The format the device is set to, from `VIDIOC_G_FMT` and the frame interval of `VIDIOC_G_PARM`.
+    pub fn camera_format(&self) -> Option<CameraFormat> {
+        let format = self.device.format().ok()?;
+        let frame_rate = interval_to_frame_rate(self.device.params().ok()?.interval)?;
+        let mut camera_format = CameraFormat::new(
+            Resolution::new(format.width, format.height),
+            FrameFormatIntermediate::into_frame_format(format.fourcc.repr),
+            frame_rate,
+        );
+        camera_format.set_scan_type(scan_type_from_field(format.field_order));
+        set_pixel_aspect_ratio(&mut camera_format, self.pixel_aspect_ratio());
+        Some(camera_format)
+    }
+
+    /// Sets the resolution and `FourCC` with `VIDIOC_S_FMT`, then the frame interval with `VIDIOC_S_PARM`.
+    /// The driver refuses this while a stream has its buffers allocated.
+    pub fn set_camera_format(&self, camera_format: CameraFormat) -> NokhwaResult<()> {
+        let error = |error: String| NokhwaError::SetPropertyError {
+            property: "format".to_string(),
+            value: camera_format.to_string(),
+            error,
+        };
+
+        let fourcc = FrameFormatIntermediate::from_frame_format(camera_format.format())
+            .ok_or_else(|| error("Unsupported FourCC".to_string()))?;
+        self.device
+            .set_format(&Format::new(
+                camera_format.width(),
+                camera_format.height(),
+                FourCC::new(&fourcc.0),
+            ))
+            .map_err(|why| error(why.to_string()))?;
+
+        // The interval is seconds per frame, the inverse of the frame rate.
+        let frame_rate = camera_format.frame_rate();
+        let interval = match (u32::try_from(*frame_rate.denominator()), u32::try_from(*frame_rate.numerator())) {
+            (Ok(numerator), Ok(denominator)) if denominator > 0 => Fraction::new(numerator, denominator),
+            _ => return Err(error("Invalid frame rate".to_string())),
+        };
+        self.device
+            .set_params(&Parameters::new(interval))
+            .map_err(|why| error(why.to_string()))?;
+        Ok(())
+    }
+
+    /// The `FourCC` the driver currently delivers, as reported by `VIDIOC_G_FMT`. Drivers may settle on a different
+    /// format than the one requested, including ones `nokhwa` only knows as [`FrameFormat::Custom`].
+    pub fn negotiated_fourcc(&self) -> Option<[u8; 4]> {
+        self.device.format().ok().map(|format| format.fourcc.repr)
     }
 
-    pub fn properties(&self) -> CameraProperties {
+    /// The kinds of I/O the device supports, from `VIDIOC_QUERYCAP`.
+    pub fn capabilities(&self) -> NokhwaResult<DeviceCapabilities> {
+        let caps = self.device.query_caps().map_err(|why| NokhwaError::GetPropertyError {
+            property: "capabilities".to_string(),
+            error: why.to_string(),
+        })?;
+        Ok(capabilities_from_flags(caps.capabilities))
+    }
+
+    /// Whether the device delivers `fourcc` at `resolution` progressive or interlaced, from the field order
+    /// `VIDIOC_TRY_FMT` settles on, without changing the device's format. Progressive if the driver refuses it.
+    pub fn scan_type(&self, fourcc: FourCC, resolution: Resolution) -> ScanType {
+        let mut format = v4l2_format {
+            type_: v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE,
+            fmt: v4l2_format__bindgen_ty_1 {
+                pix: Format::new(resolution.width(), resolution.height(), fourcc).into(),
+            },
+        };
 
+        // SAFETY: the fd belongs to our open device and `format` outlives the call.
+        let tried = unsafe {
+            v4l::v4l2::ioctl(
+                self.device.handle().fd(),
+                v4l::v4l2::vidioc::VIDIOC_TRY_FMT,
+                &mut format as *mut v4l2_format as *mut c_void,
+            )
+        };
+        match tried {
+            // SAFETY: `pix` is the member VIDIOC_TRY_FMT fills in for capture formats.
+            Ok(()) => scan_type_from_field(Format::from(unsafe { format.fmt.pix }).field_order),
+            Err(_) => ScanType::Progressive,
+        }
+    }
+
+    /// The width to height ratio of the device's pixels from `VIDIOC_CROPCAP`, and the unscaled resolution it
+    /// applies to. [`enumerate_formats`](Setting::enumerate_formats) and [`camera_format`](DeviceInner::camera_format)
+    /// set it as the [`CameraFormat::pixel_aspect_ratio`] of formats at that resolution; scaled ones are left
+    /// unknown. `None` if the driver does not support the query.
+    pub fn pixel_aspect_ratio(&self) -> Option<(Resolution, Rational32)> {
+        // SAFETY: v4l2_cropcap is a plain C struct, all zeroes is a valid (empty) request.
+        let mut cropcap: v4l2_cropcap = unsafe { std::mem::zeroed() };
+        cropcap.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+
+        // SAFETY: the fd belongs to our open device and `cropcap` outlives the call.
+        unsafe {
+            v4l::v4l2::ioctl(
+                self.device.handle().fd(),
+                v4l::v4l2::vidioc::VIDIOC_CROPCAP,
+                &mut cropcap as *mut v4l2_cropcap as *mut c_void,
+            )
+        }
+        .ok()?;
+
+        cropcap_pixel_aspect_ratio(&cropcap)
     }
 
     pub fn inner(&self) -> &Device {
         &self.device
     }
+
+    /// Reads the raw V4L2 control `id` (a `V4L2_CID_*` value, including vendor specific ones) with `VIDIOC_G_EXT_CTRLS`.
+    pub fn platform_control(&self, id: u32) -> NokhwaResult<ControlValue> {
+        let control = self.device.control(id).map_err(|why| NokhwaError::GetPropertyError {
+            property: format!("V4L2 control {id:#x}"),
+            error: why.to_string(),
+        })?;
+
+        match control.value {
+            control::Value::None => Ok(ControlValue::Null),
+            control::Value::Integer(value) => Ok(ControlValue::Integer(value)),
+            control::Value::Boolean(value) => Ok(ControlValue::Boolean(value)),
+            control::Value::String(value) => Ok(ControlValue::String(value)),
+            _ => Err(NokhwaError::GetPropertyError {
+                property: format!("V4L2 control {id:#x}"),
+                error: "Compound controls are not supported".to_string(),
+            }),
+        }
+    }
+
+    /// The [`ControlId`]s with a V4L2 control of their own (see [`ControlIdIntermediate`]) that this device has,
+    /// e.g. both [`ControlId::AnalogGain`] and [`ControlId::DigitalGain`] on a sensor that reports them separately.
+    pub fn supported_control_ids(&self) -> NokhwaResult<Vec<ControlId>> {
+        let controls = self.device.query_controls().map_err(|why| NokhwaError::GetPropertyError {
+            property: "controls".to_string(),
+            error: why.to_string(),
+        })?;
+
+        Ok(controls
+            .iter()
+            .map(|description| ControlIdIntermediate::into_control_id(description.id))
+            .filter(|control_id| !matches!(control_id, ControlId::PlatformSpecific(_)))
+            .collect())
+    }
+
+    /// Describes the menu control `id` (such as `V4L2_CID_EXPOSURE_AUTO`) as a [`ControlValueDescriptor::Menu`]
+    /// of its items' names, as reported by `VIDIOC_QUERYMENU`. Integer menu items are named by their value.
+    pub fn menu_descriptor(&self, id: u32) -> NokhwaResult<ControlValueDescriptor> {
+        describe_menu(&self.query_control(id)?).ok_or_else(|| NokhwaError::GetPropertyError {
+            property: format!("V4L2 control {id:#x}"),
+            error: "Not a menu control".to_string(),
+        })
+    }
+
+    /// Describes the integer control `id` (such as `V4L2_CID_BRIGHTNESS`) as a [`ControlValueDescriptor::Integer`]
+    /// with the minimum, maximum, step and default `VIDIOC_QUERYCTRL` reports. Many UVC cameras only move some
+    /// controls in steps larger than 1, so values between steps are rejected by
+    /// [`set_property`](nokhwa_core::camera::Setting::set_property).
+    pub fn integer_descriptor(&self, id: u32) -> NokhwaResult<ControlValueDescriptor> {
+        describe_integer(&self.query_control(id)?).ok_or_else(|| NokhwaError::GetPropertyError {
+            property: format!("V4L2 control {id:#x}"),
+            error: "Not an integer control".to_string(),
+        })
+    }
+
+    fn query_control(&self, id: u32) -> NokhwaResult<control::Description> {
+        let error = |error: String| NokhwaError::GetPropertyError {
+            property: format!("V4L2 control {id:#x}"),
+            error,
+        };
+
+        self.device
+            .query_controls()
+            .map_err(|why| error(why.to_string()))?
+            .into_iter()
+            .find(|description| description.id == id)
+            .ok_or_else(|| error("Not Found/Not Supported".to_string()))
+    }
+
+    /// Writes the raw V4L2 control `id` with `VIDIOC_S_EXT_CTRLS`. Only [`ControlValue::Null`] (for button controls),
+    /// [`ControlValue::Integer`], [`ControlValue::BitMask`], [`ControlValue::Boolean`], [`ControlValue::String`] and
+    /// menu items (a [`ControlValue::KeyValue`] holding the item's index, see [`DeviceInner::properties`]) can be
+    /// written.
+    pub fn set_platform_control(&self, id: u32, value: ControlValue) -> NokhwaResult<()> {
+        let error = |error: String| NokhwaError::SetPropertyError {
+            property: format!("V4L2 control {id:#x}"),
+            value: value.to_string(),
+            error,
+        };
+
+        let raw = match &value {
+            ControlValue::Null => control::Value::None,
+            ControlValue::Integer(value)
+            | ControlValue::BitMask(value)
+            | ControlValue::KeyValue(_, ControlValuePrimitive::Integer(value)) => control::Value::Integer(*value),
+            ControlValue::Boolean(value) => control::Value::Boolean(*value),
+            ControlValue::String(value) => control::Value::String(value.clone()),
+            _ => return Err(error("Unsupported value type".to_string())),
+        };
+
+        self.device
+            .set_control(Control { id, value: raw })
+            .map_err(|why| error(why.to_string()))
+    }
+
+    /// Exports the capture buffer at `index` as a dma-buf file descriptor with `VIDIOC_EXPBUF`.
+    /// The buffers must already be allocated as `V4L2_MEMORY_MMAP`, e.g. by an open [`MmapStream`].
+    pub fn export_dmabuf(&self, index: u32) -> NokhwaResult<OwnedFd> {
+        // SAFETY: v4l2_exportbuffer is a plain C struct, all zeroes is a valid (empty) request.
+        let mut export: v4l2_exportbuffer = unsafe { std::mem::zeroed() };
+        export.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        export.index = index;
+
+        // SAFETY: the fd belongs to our open device and `export` outlives the call.
+        unsafe {
+            v4l::v4l2::ioctl(
+                self.device.handle().fd(),
+                v4l::v4l2::vidioc::VIDIOC_EXPBUF,
+                &mut export as *mut v4l2_exportbuffer as *mut c_void,
+            )
+        }
+        .map_err(|why| NokhwaError::OpenStreamError(format!("VIDIOC_EXPBUF failed: {why}")))?;
+
+        // SAFETY: VIDIOC_EXPBUF succeeded, so `export.fd` is a new descriptor that we now own.
+        Ok(unsafe { OwnedFd::from_raw_fd(export.fd) })
+    }
+
+    /// Grabs a single frame at `format`, usually a higher resolution than the video stream, then switches the
+    /// device back to the format it had before.
+    ///
+    /// V4L2 has no separate still pin, so this renegotiates the capture format, which the driver refuses while
+    /// buffers are allocated: close any open [`StreamInner`] first.
+    pub fn capture_still(&self, format: CameraFormat) -> NokhwaResult<FrameBuffer> {
+        let fourcc = FrameFormatIntermediate::from_frame_format(format.format()).ok_or_else(|| {
+            NokhwaError::SetPropertyError {
+                property: "capture_still".to_string(),
+                value: format.to_string(),
+                error: "Unsupported FourCC".to_string(),
+            }
+        })?;
+
+        let previous = self.device.format().map_err(|why| NokhwaError::GetPropertyError {
+            property: "format".to_string(),
+            error: why.to_string(),
+        })?;
+
+        let still = self
+            .device
+            .set_format(&Format::new(format.width(), format.height(), FourCC::new(&fourcc.0)))
+            .map_err(|why| NokhwaError::SetPropertyError {
+                property: "capture_still".to_string(),
+                value: format.to_string(),
+                error: why.to_string(),
+            });
+
+        // Restore the previous format whether or not the grab worked.
+        let frame = still.and_then(|still| {
+            let mut stream = MmapStream::with_buffers(&self.device, Type::VideoCapture, 1)
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?;
+            let (buffer, metadata) = stream
+                .next()
+                .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+
+            let frame = FrameBuffer::new(
+                Resolution::new(still.width, still.height),
+                &buffer[..metadata.bytesused as usize],
+                format.format(),
+            )
+            .with_scan_type(scan_type_from_field(still.field_order));
+            Ok(match buffer_timestamp(metadata) {
+                Some(timestamp) => frame.with_raw_timestamp(timestamp, monotonic_now()),
+                None => frame,
+            })
+        });
+
+        self.device.set_format(&previous).map_err(|why| NokhwaError::SetPropertyError {
+            property: "format".to_string(),
+            value: previous.to_string(),
+            error: why.to_string(),
+        })?;
+
+        frame
+    }
+}
+
+// The field order the driver tagged a dequeued buffer with, or `None` if it left it unset.
+fn buffer_scan_type(metadata: &Metadata) -> Option<ScanType> {
+    match FieldOrder::try_from(metadata.field) {
+        Ok(FieldOrder::Any) | Err(()) => None,
+        Ok(field) => Some(scan_type_from_field(field)),
+    }
+}
+
+// The time the driver stamped a dequeued buffer with, or `None` if it left it unset.
+fn buffer_timestamp(metadata: &Metadata) -> Option<RawTimestamp> {
+    let (Ok(sec), Ok(usec)) = (u64::try_from(metadata.timestamp.sec), u32::try_from(metadata.timestamp.usec)) else {
+        return None;
+    };
+    let time = Duration::new(sec, usec * 1000);
+    (!time.is_zero()).then(|| RawTimestamp::new(TimestampClock::Monotonic, time))
+}
+
+// Reads `CLOCK_MONOTONIC`, the clock V4L2 stamps buffers with.
+fn monotonic_now() -> Duration {
+    let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `now` is a valid timespec for the call to write into.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    Duration::new(now.tv_sec as u64, now.tv_nsec as u32)
+}
+
+// How a `StreamInner` gets frames out of the device.
+enum CaptureIo {
+    // v4l shares the device handle with the stream, so it does not need to borrow `DeviceInner`.
+    Mmap(MmapStream<'static>),
+    // Buffers exported as dma-bufs, queued and dequeued by `DmaBufQueue` so each frame knows its buffer.
+    DmaBuf(DmaBufQueue),
+    // `read()`s whole frames of up to `frame_size` bytes straight from the device.
+    Read { frame_size: usize },
+}
+
+// A frame copied out of the device.
+struct RawFrame {
+    bytes: Vec<u8>,
+    // The driver's metadata for dequeued buffers, `None` for frames `read()` from the device.
+    metadata: Option<Metadata>,
+    // The buffer the frame was captured into, for `IoMethod::DmaBuf` streams.
+    dmabuf_fd: Option<RawFd>,
+}
+
+impl CaptureIo {
+    // Dequeues or reads the next frame. Fails with `WouldBlock` if the device fd is non-blocking and no frame is
+    // ready yet.
+    fn next(&mut self, handle: &Handle) -> std::io::Result<RawFrame> {
+        match self {
+            CaptureIo::Mmap(stream) => {
+                let (buffer, metadata) = CaptureStream::next(stream)?;
+                Ok(RawFrame {
+                    bytes: buffer[..(metadata.bytesused as usize).min(buffer.len())].to_vec(),
+                    metadata: Some(*metadata),
+                    dmabuf_fd: None,
+                })
+            }
+            CaptureIo::DmaBuf(queue) => queue.next(handle),
+            CaptureIo::Read { frame_size } => {
+                // SAFETY: the fd stays open as long as `handle` does, and `ManuallyDrop` keeps the `File` from
+                // closing it.
+                let mut file = ManuallyDrop::new(unsafe { File::from_raw_fd(handle.fd()) });
+                let mut frame = vec![0; *frame_size];
+                let read = file.read(&mut frame)?;
+                frame.truncate(read);
+                Ok(RawFrame {
+                    bytes: frame,
+                    metadata: None,
+                    dmabuf_fd: None,
+                })
+            }
+        }
+    }
+
+    fn stop(&mut self) -> std::io::Result<()> {
+        match self {
+            CaptureIo::Mmap(stream) => stream.stop(),
+            CaptureIo::DmaBuf(queue) => queue.stop(),
+            CaptureIo::Read { .. } => Ok(()),
+        }
+    }
+}
+
+// A driver buffer exported as a dma-buf, with a read-only mapping of it to copy frames out of.
+struct ExportedBuffer {
+    fd: OwnedFd,
+    data: *mut c_void,
+    length: usize,
+}
+
+// SAFETY: the mapping is owned by this buffer, only read through it, and unmapped when it is dropped.
+unsafe impl Send for ExportedBuffer {}
+
+impl ExportedBuffer {
+    // Maps and exports the `V4L2_MEMORY_MMAP` buffer at `index`, which must already be allocated.
+    fn new(device: &DeviceInner, index: u32) -> NokhwaResult<Self> {
+        let fd = device.export_dmabuf(index)?;
+
+        let mut buffer = capture_buffer();
+        buffer.index = index;
+        // SAFETY: the fd belongs to our open device and `buffer` outlives the call.
+        unsafe {
+            v4l::v4l2::ioctl(
+                device.inner().handle().fd(),
+                v4l::v4l2::vidioc::VIDIOC_QUERYBUF,
+                &mut buffer as *mut v4l2_buffer as *mut c_void,
+            )
+        }
+        .map_err(|why| NokhwaError::OpenStreamError(format!("VIDIOC_QUERYBUF failed: {why}")))?;
+
+        let length = buffer.length as usize;
+        // SAFETY: VIDIOC_QUERYBUF reported the offset and length to map this buffer with, and for MMAP buffers the
+        // offset is the active field of `m`.
+        let data = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                length,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                device.inner().handle().fd(),
+                buffer.m.offset as libc::off_t,
+            )
+        };
+        if data == libc::MAP_FAILED {
+            return Err(NokhwaError::os_error(
+                "could not map the V4L2 buffer",
+                std::io::Error::last_os_error(),
+            ));
+        }
+
+        Ok(ExportedBuffer { fd, data, length })
+    }
+
+    fn bytes(&self, len: usize) -> Vec<u8> {
+        // SAFETY: `data` is a mapping of `length` bytes that lives as long as `self`.
+        unsafe { std::slice::from_raw_parts(self.data.cast::<u8>(), len.min(self.length)) }.to_vec()
+    }
+}
+
+impl Drop for ExportedBuffer {
+    fn drop(&mut self) {
+        // SAFETY: `data` and `length` are the mapping made in `new`, and nothing borrows it past `bytes`.
+        unsafe { libc::munmap(self.data, self.length) };
+    }
+}
+
+// A zeroed `v4l2_buffer` describing a capture buffer allocated as `V4L2_MEMORY_MMAP`.
+fn capture_buffer() -> v4l2_buffer {
+    // SAFETY: v4l2_buffer is a plain C struct, all zeroes is a valid (empty) request.
+    let mut buffer: v4l2_buffer = unsafe { std::mem::zeroed() };
+    buffer.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+    buffer.memory = v4l::memory::Memory::Mmap as u32;
+    buffer
+}
+
+// Streams into exported buffers. v4l's `MmapStream` does not say which buffer it dequeued, which is needed to hand
+// out the right dma-buf, so it is only used to allocate and free the buffers, while this queues and dequeues them.
+struct DmaBufQueue {
+    // Declared before `stream`, so the mappings are gone before it frees the buffers.
+    buffers: Vec<ExportedBuffer>,
+    stream: MmapStream<'static>,
+    // The buffer handed out last, queued again before the next one is dequeued.
+    dequeued: Option<u32>,
+    streaming: bool,
+}
+
+impl DmaBufQueue {
+    fn new(device: &DeviceInner, stream: MmapStream<'static>, buffer_count: u32) -> NokhwaResult<Self> {
+        let buffers = (0..buffer_count)
+            .map(|index| ExportedBuffer::new(device, index))
+            .collect::<NokhwaResult<Vec<_>>>()?;
+        Ok(DmaBufQueue {
+            buffers,
+            stream,
+            dequeued: None,
+            streaming: false,
+        })
+    }
+
+    fn next(&mut self, handle: &Handle) -> std::io::Result<RawFrame> {
+        if self.streaming {
+            if let Some(index) = self.dequeued.take() {
+                Self::queue(handle, index)?;
+            }
+        } else {
+            // Like `MmapStream`, queue every buffer and start the device on the first frame, or after a pause.
+            for index in 0..self.buffers.len() as u32 {
+                Self::queue(handle, index)?;
+            }
+            self.stream.start()?;
+            self.streaming = true;
+        }
+
+        let mut buffer = capture_buffer();
+        // SAFETY: the fd stays open as long as `handle` does, and `buffer` outlives the call.
+        unsafe {
+            v4l::v4l2::ioctl(
+                handle.fd(),
+                v4l::v4l2::vidioc::VIDIOC_DQBUF,
+                &mut buffer as *mut v4l2_buffer as *mut c_void,
+            )?;
+        }
+        self.dequeued = Some(buffer.index);
+
+        let exported = self
+            .buffers
+            .get(buffer.index as usize)
+            .ok_or_else(|| std::io::Error::other(format!("driver dequeued unknown buffer {}", buffer.index)))?;
+        Ok(RawFrame {
+            bytes: exported.bytes(buffer.bytesused as usize),
+            metadata: Some(Metadata {
+                bytesused: buffer.bytesused,
+                flags: buffer.flags.into(),
+                field: buffer.field,
+                timestamp: buffer.timestamp.into(),
+                sequence: buffer.sequence,
+            }),
+            dmabuf_fd: Some(exported.fd.as_raw_fd()),
+        })
+    }
+
+    fn queue(handle: &Handle, index: u32) -> std::io::Result<()> {
+        let mut buffer = capture_buffer();
+        buffer.index = index;
+        // SAFETY: the fd stays open as long as `handle` does, and `buffer` outlives the call.
+        unsafe {
+            v4l::v4l2::ioctl(
+                handle.fd(),
+                v4l::v4l2::vidioc::VIDIOC_QBUF,
+                &mut buffer as *mut v4l2_buffer as *mut c_void,
+            )
+        }
+    }
+
+    // `VIDIOC_STREAMOFF` hands every buffer back, so they are all queued again on the next frame.
+    fn stop(&mut self) -> std::io::Result<()> {
+        self.stream.stop()?;
+        self.streaming = false;
+        self.dequeued = None;
+        Ok(())
+    }
+}
+
+// Lends the device fd to `async-io` without handing over ownership; the `Handle` closes it.
+#[cfg(feature = "async")]
+struct DeviceFd(Arc<Handle>);
+
+#[cfg(feature = "async")]
+impl AsFd for DeviceFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: the fd stays open as long as the `Handle` does.
+        unsafe { BorrowedFd::borrow_raw(self.0.fd()) }
+    }
+}
+
+pub struct StreamInner {
+    handle: Arc<Handle>,
+    io: CaptureIo,
+    io_method: IoMethod,
+    resolution: Resolution,
+    frame_format: FrameFormat,
+    // The field order negotiated when the stream opened, for frames whose buffer does not say.
+    scan_type: ScanType,
+    // The device fd registered with the `async-io` reactor, from the first `next_frame_async`.
+    #[cfg(feature = "async")]
+    readiness: Option<Async<DeviceFd>>,
+}
+
+impl StreamInner {
+    /// Allocates `buffer_count` capture buffers on `device` for the given [`IoMethod`].
+    ///
+    /// [`IoMethod::DmaBuf`] allocates the buffers as `V4L2_MEMORY_MMAP` and exports each one with `VIDIOC_EXPBUF`,
+    /// so frames can be handed to a GPU or encoder zero-copy: each frame carries the
+    /// [dma-buf fd](FrameBuffer::dmabuf_fd) of the buffer it was captured into. The driver reuses that buffer once
+    /// the next frame is dequeued. [`IoMethod::UserPtr`] is not supported yet.
+    ///
+    /// Devices that cannot stream (no `V4L2_CAP_STREAMING`) but can be read from fall back from [`IoMethod::Mmap`]
+    /// to [`IoMethod::Read`], see [`DeviceCapabilities::preferred_io_method`].
+    pub fn new(device: &DeviceInner, buffer_count: u32, io_method: IoMethod) -> NokhwaResult<Self> {
+        if io_method == IoMethod::UserPtr {
+            return Err(NokhwaError::NotImplementedError("V4L2 UserPtr streaming".to_string()));
+        }
+
+        let io_method = select_io_method(io_method, device.capabilities()?)?;
+
+        let format = device.inner().format().map_err(|why| NokhwaError::GetPropertyError {
+            property: "format".to_string(),
+            error: why.to_string(),
+        })?;
+        let resolution = Resolution::new(format.width, format.height);
+        let frame_format = FrameFormatIntermediate::into_frame_format(format.fourcc.repr);
+        let scan_type = scan_type_from_field(format.field_order);
+
+        if io_method == IoMethod::Read {
+            return Ok(StreamInner {
+                handle: device.inner().handle(),
+                io: CaptureIo::Read {
+                    frame_size: format.size as usize,
+                },
+                io_method,
+                resolution,
+                frame_format,
+                scan_type,
+                #[cfg(feature = "async")]
+                readiness: None,
+            });
+        }
+
+        let stream = MmapStream::with_buffers(device.inner(), Type::VideoCapture, buffer_count)
+            .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?;
+
+        let io = match io_method {
+            IoMethod::DmaBuf => CaptureIo::DmaBuf(DmaBufQueue::new(device, stream, buffer_count)?),
+            _ => CaptureIo::Mmap(stream),
+        };
+
+        Ok(StreamInner {
+            handle: device.inner().handle(),
+            io,
+            io_method,
+            resolution,
+            frame_format,
+            scan_type,
+            #[cfg(feature = "async")]
+            readiness: None,
+        })
+    }
+
+    pub fn io_method(&self) -> IoMethod {
+        self.io_method
+    }
+
+    /// The dma-buf fd of the driver buffer at `index`, if this stream was opened with [`IoMethod::DmaBuf`].
+    pub fn dmabuf_fd(&self, index: usize) -> Option<RawFd> {
+        match &self.io {
+            CaptureIo::DmaBuf(queue) => queue.buffers.get(index).map(|buffer| buffer.fd.as_raw_fd()),
+            _ => None,
+        }
+    }
+
+    /// Waits up to `timeout` for the device to have a frame ready, returning `false` if none arrived in time.
+    ///
+    /// A stream that is not running yet (or was [paused](StreamInner::pause)) reports ready straight away, as the
+    /// next [`StreamInner::next_frame`] starts it.
+    pub fn wait_for_frame(&self, timeout: Duration) -> NokhwaResult<bool> {
+        let timeout = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        self.handle
+            .poll(libc::POLLIN, timeout)
+            .map(|ready| ready > 0)
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))
+    }
+
+    /// Waits for the next frame and copies out its bytes. Dequeued buffers carry the driver's capture time as
+    /// their [raw timestamp](FrameBuffer::raw_timestamp); frames `read()` from the device have none.
+    pub fn next_frame(&mut self) -> NokhwaResult<FrameBuffer> {
+        self.read_frame()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))
+    }
+
+    // Like `next_frame`, keeping the OS error so the capture thread can tell when the device is gone.
+    fn read_frame(&mut self) -> std::io::Result<FrameBuffer> {
+        let raw = self.io.next(&self.handle)?;
+        Ok(self.frame_buffer(raw))
+    }
+
+    fn frame_buffer(&self, raw: RawFrame) -> FrameBuffer {
+        let scan_type = raw.metadata.as_ref().and_then(buffer_scan_type).unwrap_or(self.scan_type);
+        let mut frame = FrameBuffer::new(self.resolution, &raw.bytes, self.frame_format).with_scan_type(scan_type);
+        if let Some(fd) = raw.dmabuf_fd {
+            frame = frame.with_dmabuf_fd(fd);
+        }
+        match raw.metadata.as_ref().and_then(buffer_timestamp) {
+            Some(timestamp) => frame.with_raw_timestamp(timestamp, monotonic_now()),
+            None => frame,
+        }
+    }
+
+    /// Like [`StreamInner::next_frame`], but waits without blocking the executor: the device fd is registered with
+    /// `async-io`'s reactor, and the frame is only dequeued once the driver reports one ready.
+    ///
+    /// The first call switches the device fd to non-blocking mode, so a [`IoMethod::Read`] stream should not go
+    /// back to [`StreamInner::next_frame`] afterwards, as it would fail instead of waiting.
+    #[cfg(feature = "async")]
+    pub async fn next_frame_async(&mut self) -> NokhwaResult<FrameBuffer> {
+        let StreamInner {
+            handle,
+            io,
+            readiness,
+            ..
+        } = self;
+
+        let readiness = match readiness {
+            Some(readiness) => readiness,
+            None => readiness.insert(
+                Async::new(DeviceFd(handle.clone()))
+                    .map_err(|why| NokhwaError::os_error("could not register the V4L2 device for polling", why))?,
+            ),
+        };
+
+        let raw = readiness
+            .read_with(|_| io.next(handle))
+            .await
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+        Ok(self.frame_buffer(raw))
+    }
+
+    /// Stops the device with `VIDIOC_STREAMOFF`. The mapped buffers stay allocated, so [`StreamInner::resume`] is instant.
+    /// Streams using [`IoMethod::Read`] have nothing to stop.
+    pub fn pause(&mut self) -> NokhwaResult<()> {
+        self.io
+            .stop()
+            .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))
+    }
+
+    /// Undoes [`StreamInner::pause`]. The buffers from before are queued again and the device restarted with
+    /// `VIDIOC_STREAMON` by the next [`StreamInner::next_frame`], so there is nothing to do here.
+    pub fn resume(&mut self) -> NokhwaResult<()> {
+        Ok(())
+    }
+}
+
+/// Lists and opens V4L2 capture devices. [`CameraIndex::Index`] opens `/dev/video{index}`.
+#[derive(Clone, Debug, Default)]
+pub struct V4L2Platform;
+
+impl V4L2Platform {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PlatformTrait for V4L2Platform {
+    const PLATFORM: Backends = Backends::Video4Linux2;
+    type Camera = V4L2Camera;
+
+    fn block_on_permission(&mut self) -> NokhwaResult<()> {
+        Ok(())
+    }
+
+    fn check_permission_given(&mut self) -> bool {
+        true
+    }
+
+    fn query(&mut self) -> NokhwaResult<Vec<CameraInformation>> {
+        Ok(v4l::context::enum_devices()
+            .into_iter()
+            .filter_map(|node| {
+                let index = node.index();
+                let caps = Device::new(index).ok()?.query_caps().ok()?;
+                caps.capabilities
+                    .contains(capability::Flags::VIDEO_CAPTURE)
+                    .then(|| camera_information(index, &caps))
+            })
+            .collect())
+    }
+
+    fn open(&mut self, index: &CameraIndex) -> NokhwaResult<Self::Camera> {
+        self.open_with(index, OpenOptions::new())
+    }
+
+    fn open_with(&mut self, index: &CameraIndex, options: OpenOptions) -> NokhwaResult<Self::Camera> {
+        let open = |device_index: u32| {
+            let device = if options.read_only {
+                DeviceInner::new_read_only(device_index as usize)?
+            } else {
+                DeviceInner::new(device_index as usize)?
+            };
+            V4L2Camera::new(device)
+        };
+
+        index
+            .as_index()
+            .and_then(open)
+            .map_err(|why| why.with_context(Self::PLATFORM, index.clone()))
+    }
+}
+
+/// A V4L2 capture device, see [`V4L2Platform`].
+///
+/// Its controls are read once when opened, and kept up to date as they are set through
+/// [`set_property`](Setting::set_property).
+pub struct V4L2Camera {
+    device: DeviceInner,
+    properties: Properties,
+    running: Option<Arc<AtomicBool>>,
+}
+
+impl V4L2Camera {
+    /// Wraps an open device, reading its controls.
+    /// # Errors
+    /// Errors if the controls cannot be queried.
+    pub fn new(device: DeviceInner) -> NokhwaResult<Self> {
+        let properties = device.properties()?;
+        Ok(V4L2Camera {
+            device,
+            properties,
+            running: None,
+        })
+    }
+
+    pub fn device(&self) -> &DeviceInner {
+        &self.device
+    }
+
+    // The frame rates of every resolution in every format the device lists.
+    fn frame_rate_support(&self) -> NokhwaResult<Vec<(Resolution, FrameFormat, FrameRateSupport)>> {
+        let descriptions = self.device.inner().enum_formats().map_err(|why| NokhwaError::GetPropertyError {
+            property: "enum_formats".to_string(),
+            error: why.to_string(),
+        })?;
+
+        let mut supported = vec![];
+        for description in descriptions {
+            let frame_format = FrameFormatIntermediate::into_frame_format(description.fourcc.repr);
+            for resolution in self.device.resolutions(description.fourcc)? {
+                let frame_rates = self.device.frame_rates(description.fourcc, resolution)?;
+                supported.push((resolution, frame_format, frame_rates));
+            }
+        }
+        Ok(supported)
+    }
+}
+
+impl Setting for V4L2Camera {
+    fn enumerate_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        let pixel_aspect_ratio = self.device.pixel_aspect_ratio();
+        Ok(self
+            .frame_rate_support()?
+            .into_iter()
+            .flat_map(|(resolution, frame_format, support)| {
+                let scan_type = FrameFormatIntermediate::from_frame_format(frame_format)
+                    .map_or(ScanType::Progressive, |fourcc| {
+                        self.device.scan_type(FourCC::new(&fourcc.0), resolution)
+                    });
+                listed_frame_rates(&support).into_iter().map(move |frame_rate| {
+                    let mut camera_format = CameraFormat::new(resolution, frame_format, frame_rate);
+                    camera_format.set_scan_type(scan_type);
+                    set_pixel_aspect_ratio(&mut camera_format, pixel_aspect_ratio);
+                    camera_format
+                })
+            })
+            .collect())
+    }
+
+    fn enumerate_resolution_and_frame_rates(
+        &self,
+        frame_format: FrameFormat,
+    ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError> {
+        let Some(fourcc) = FrameFormatIntermediate::from_frame_format(frame_format) else {
+            return Ok(HashMap::new());
+        };
+        let fourcc = FourCC::new(&fourcc.0);
+
+        self.device
+            .resolutions(fourcc)?
+            .into_iter()
+            .map(|resolution| {
+                let frame_rates = self.device.frame_rates(fourcc, resolution)?;
+                Ok((resolution, listed_frame_rates(&frame_rates)))
+            })
+            .collect()
+    }
+
+    fn set_format(&self, camera_format: CameraFormat) -> Result<(), NokhwaError> {
+        self.device.set_camera_format(camera_format)
+    }
+
+    // Resolved against the full `FrameRateSupport`, so `Closest` can pick any rate in a stepwise range.
+    fn set_requested_format(&self, request: RequestedFormat<'_>) -> Result<CameraFormat, NokhwaError> {
+        let camera_format = request
+            .format_request()
+            .resolve_with_support(&self.frame_rate_support()?)
+            .ok_or_else(format_not_fulfilled)?;
+        self.set_format(camera_format)?;
+        Ok(camera_format)
+    }
+
+    fn camera_format(&self) -> Option<CameraFormat> {
+        self.device.camera_format()
+    }
+
+    fn negotiated_fourcc(&self) -> Option<[u8; 4]> {
+        self.device.negotiated_fourcc()
+    }
+
+    fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    fn set_property(&mut self, property: &ControlId, value: ControlValue) -> Result<(), NokhwaError> {
+        let error = |error: &str| NokhwaError::SetPropertyError {
+            property: property.to_string(),
+            value: value.to_string(),
+            error: error.to_string(),
+        };
+
+        let (Some(id), Some(control)) = (
+            ControlIdIntermediate::from_control_id(*property),
+            self.properties.control_value(property),
+        ) else {
+            return Err(error("Not Found/Not Supported"));
+        };
+        if control.flags().contains(&ControlFlags::ReadOnly) {
+            return Err(error("Read only control"));
+        }
+        if control.descriptor().validate(&value).is_break() {
+            return Err(error("Failed to validate control value"));
+        }
+
+        // Drivers ignore a manual white balance temperature or ISO while their automatic mode is on.
+        self.switch_to_manual_for(property)?;
+        self.device.set_platform_control(id.0, value.clone())?;
+        self.properties.set_control_value(property, value)
+    }
+
+    /// Reads the control with `VIDIOC_G_CTRL`, typed like the cached value (a menu item's label, or a boolean), and
+    /// caches it.
+    fn read_property(&mut self, property: &ControlId) -> Result<ControlValue, NokhwaError> {
+        let (Some(id), Some(control)) = (
+            ControlIdIntermediate::from_control_id(*property),
+            self.properties.control_value(property),
+        ) else {
+            return Err(NokhwaError::GetPropertyError {
+                property: property.to_string(),
+                error: "Not Found/Not Supported".to_string(),
+            });
+        };
+        if control.flags().contains(&ControlFlags::WriteOnly) {
+            return Err(NokhwaError::GetPropertyError {
+                property: property.to_string(),
+                error: "Write only control".to_string(),
+            });
+        }
+
+        let value = typed_value(control.descriptor(), self.device.platform_control(id.0)?);
+        let _ = self.properties.set_control_value(property, value.clone());
+        Ok(value)
+    }
+
+    /// Reads any V4L2 control, including vendor specific ones `nokhwa` does not list in its
+    /// [`properties`](Setting::properties), straight from the driver.
+    fn get_platform_control(&self, id: PlatformSpecificControlId) -> Result<ControlValue, NokhwaError> {
+        let id = platform_specific_to_linux_id(id).ok_or_else(|| NokhwaError::GetPropertyError {
+            property: ControlId::PlatformSpecific(id).to_string(),
+            error: "Not a V4L2 control ID".to_string(),
+        })?;
+        self.device.platform_control(id)
+    }
+
+    /// Writes any V4L2 control straight to the driver, see [`DeviceInner::set_platform_control`].
+    fn set_platform_control(&mut self, id: PlatformSpecificControlId, value: ControlValue) -> Result<(), NokhwaError> {
+        let Some(linux_id) = platform_specific_to_linux_id(id) else {
+            return Err(NokhwaError::SetPropertyError {
+                property: ControlId::PlatformSpecific(id).to_string(),
+                value: value.to_string(),
+                error: "Not a V4L2 control ID".to_string(),
+            });
+        };
+        self.device.set_platform_control(linux_id, value.clone())?;
+        // Keep the cache in step for controls it lists; vendor controls it does not are fine to miss.
+        let _ = self.properties.set_control_value(&ControlId::PlatformSpecific(id), value);
+        Ok(())
+    }
+}
+
+impl Capture for V4L2Camera {
+    fn open_stream(&mut self) -> Result<Stream, NokhwaError> {
+        self.open_stream_with(StreamConfig::default())
+    }
+
+    fn close_stream(&mut self) -> Result<(), NokhwaError> {
+        if let Some(running) = self.running.take() {
+            running.store(false, Ordering::Release);
+        }
+        Ok(())
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.device.is_read_only()
+    }
+
+    /// Grabs the frame with [`DeviceInner::capture_still`], which renegotiates the device's format and so cannot
+    /// run while a stream is open.
+    fn capture_still(&mut self, format: RequestedFormat<'_>) -> Result<FrameBuffer, NokhwaError> {
+        if self.running.as_ref().is_some_and(|running| running.load(Ordering::Acquire)) {
+            return Err(NokhwaError::ReadFrameError(
+                "Close the stream before capturing a still".to_string(),
+            ));
+        }
+
+        let still = format
+            .fulfill(&self.enumerate_formats()?)
+            .ok_or_else(format_not_fulfilled)?;
+        self.device.capture_still(still)
+    }
+
+    /// Frames are captured on a thread of their own, with any [`IoMethod`] [`StreamInner::new`] accepts. The
+    /// thread priority and affinity apply to that thread.
+    fn open_stream_with(&mut self, config: StreamConfig) -> Result<Stream, NokhwaError> {
+        self.ensure_streamable()?;
+        if self.running.as_ref().is_some_and(|running| running.load(Ordering::Acquire)) {
+            return Err(NokhwaError::OpenStreamError("A stream is already open".to_string()));
+        }
+
+        let format = self.device.camera_format().ok_or_else(|| {
+            NokhwaError::OpenStreamError("The device has no format set".to_string())
+        })?;
+        let inner = StreamInner::new(&self.device, STREAM_BUFFER_COUNT, config.io_method)?;
+        let io_method = inner.io_method();
+
+        let (sender, receiver) = flume::bounded(CHANNEL_CAPACITY);
+        let (commands, command_receiver) = flume::unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let stats = Arc::new(StreamStatsRecorder::new());
+
+        let (started_sender, started) = flume::bounded(1);
+
+        let thread = {
+            let running = running.clone();
+            let stats = stats.clone();
+            std::thread::Builder::new()
+                .name("nokhwa-v4l2-capture".to_string())
+                .spawn(move || {
+                    let applied = config.apply_to_current_thread();
+                    let capture_frames = applied.is_ok();
+                    let _ = started_sender.send(applied);
+                    if capture_frames {
+                        capture(inner, &sender, &command_receiver, &running, &stats);
+                    }
+                })
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?
+        };
+
+        match started.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(why)) => {
+                let _ = thread.join();
+                return Err(why);
+            }
+            Err(_) => {
+                return Err(NokhwaError::OpenStreamError(
+                    "The capture thread panicked while starting".to_string(),
+                ))
+            }
+        }
+
+        self.running = Some(running.clone());
+        let stream = Stream::new(Box::new(V4L2Stream {
+            receiver: Arc::new(receiver),
+            commands,
+            running,
+            thread: Some(thread),
+            stats,
+            format,
+            io_method,
+        }));
+
+        if let Some(timeout) = config.first_frame_timeout {
+            if let Err(why) = stream.wait_for_first_frame(timeout) {
+                drop(stream);
+                self.close_stream()?;
+                return Err(why);
+            }
+        }
+
+        Ok(stream)
+    }
+}
+
+impl Camera for V4L2Camera {}
+
+/// The stream captures on its own thread, blocking on the device there, exactly like [`Capture::open_stream`], so
+/// the device fd stays in blocking mode and awaiting frames with [`Stream::await_frame`] never blocks the executor.
+#[cfg(feature = "async")]
+impl AsyncStream for V4L2Camera {
+    async fn open_stream_async(&mut self) -> Result<Stream, NokhwaError> {
+        self.open_stream()
+    }
+
+    async fn close_stream_async(&mut self) -> Result<(), NokhwaError> {
+        self.close_stream()
+    }
+}
+
+// Buffers the driver captures into, so it can fill one while the others are being copied out.
+const STREAM_BUFFER_COUNT: u32 = 4;
+// Frames buffered between the capture thread and the `Stream` before new ones are dropped.
+const CHANNEL_CAPACITY: usize = 4;
+// How long the capture thread waits for a frame, or for a paused stream to resume, before checking if it was stopped.
+const CAPTURE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Asks the capture thread to pause or resume the device, replying once it has.
+enum StreamCommand {
+    Pause(Sender<NokhwaResult<()>>),
+    Resume(Sender<NokhwaResult<()>>),
+}
+
+struct V4L2Stream {
+    receiver: Arc<Receiver<FrameBuffer>>,
+    commands: Sender<StreamCommand>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    stats: Arc<StreamStatsRecorder>,
+    format: CameraFormat,
+    io_method: IoMethod,
+}
+
+impl V4L2Stream {
+    fn command(&self, command: impl FnOnce(Sender<NokhwaResult<()>>) -> StreamCommand) -> NokhwaResult<()> {
+        let (reply, result) = flume::bounded(1);
+        self.commands
+            .send(command(reply))
+            .map_err(|_| NokhwaError::ReadFrameError("The capture thread has stopped".to_string()))?;
+        result
+            .recv()
+            .map_err(|_| NokhwaError::ReadFrameError("The capture thread has stopped".to_string()))?
+    }
+}
+
+impl StreamInnerTrait for V4L2Stream {
+    fn receiver(&self) -> Arc<Receiver<FrameBuffer>> {
+        self.receiver.clone()
+    }
+
+    fn stop(&mut self) -> NokhwaResult<()> {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread
+                .join()
+                .map_err(|_| NokhwaError::StreamShutdownError("The capture thread panicked".to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn io_method(&self) -> IoMethod {
+        self.io_method
+    }
+
+    fn format(&self) -> Option<CameraFormat> {
+        Some(self.format)
+    }
+
+    fn stats(&self) -> StreamStats {
+        self.stats.stats()
+    }
+
+    /// Stops the device with `VIDIOC_STREAMOFF`, see [`StreamInner::pause`].
+    fn pause(&mut self) -> NokhwaResult<()> {
+        self.command(StreamCommand::Pause)
+    }
+
+    fn resume(&mut self) -> NokhwaResult<()> {
+        self.command(StreamCommand::Resume)
+    }
+}
+
+// Captures frames until stopped, the stream is dropped or the device is unplugged, pausing and resuming the device
+// as the `V4L2Stream` asks.
+fn capture(
+    mut inner: StreamInner,
+    sender: &Sender<FrameBuffer>,
+    commands: &Receiver<StreamCommand>,
+    running: &AtomicBool,
+    stats: &StreamStatsRecorder,
+) {
+    let mut paused = false;
+
+    while running.load(Ordering::Acquire) {
+        let command = if paused {
+            commands.recv_timeout(CAPTURE_POLL_INTERVAL).ok()
+        } else {
+            commands.try_recv().ok()
+        };
+        match command {
+            Some(StreamCommand::Pause(reply)) => {
+                let result = inner.pause();
+                paused |= result.is_ok();
+                let _ = reply.send(result);
+                continue;
+            }
+            Some(StreamCommand::Resume(reply)) => {
+                let result = inner.resume();
+                paused &= result.is_err();
+                let _ = reply.send(result);
+                continue;
+            }
+            None if paused => continue,
+            None => {}
+        }
+
+        match inner.wait_for_frame(CAPTURE_POLL_INTERVAL) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => {
+                stats.record_error();
+                break;
+            }
+        }
+
+        match inner.read_frame() {
+            Ok(frame) => {
+                if !stats.send_frame(sender, frame) {
+                    break;
+                }
+            }
+            // The device was unplugged, no more frames will come.
+            Err(why) if why.raw_os_error() == Some(libc::ENODEV) => {
+                stats.record_error();
+                break;
+            }
+            Err(_) => stats.record_error(),
+        }
+    }
+
+    running.store(false, Ordering::Release);
+}
+
+// Sets the ratio `DeviceInner::pixel_aspect_ratio` reported on `camera_format` if it is at the unscaled resolution.
+fn set_pixel_aspect_ratio(camera_format: &mut CameraFormat, pixel_aspect_ratio: Option<(Resolution, Rational32)>) {
+    if let Some((unscaled, ratio)) = pixel_aspect_ratio {
+        if camera_format.resolution() == unscaled {
+            camera_format.set_pixel_aspect_ratio(Some(ratio));
+        }
+    }
 }
 
-pub struct StreamInner<'a> {
-    stream: MmapStream<'a>
+// Same error as `Setting::set_requested_format` reports.
+fn format_not_fulfilled() -> NokhwaError {
+    NokhwaError::GetPropertyError {
+        property: "CameraFormat".to_string(),
+        error: "no format fulfills the request".to_string(),
+    }
+}
+
+// The frame rates `FrameRateSupport` lists: the discrete ones, and the fastest and slowest of the stepwise range.
+fn listed_frame_rates(support: &FrameRateSupport) -> Vec<FrameRate> {
+    let mut frame_rates = support.discrete.clone();
+    if let Some(stepwise) = support.stepwise {
+        frame_rates.extend(stepwise.maximum());
+        frame_rates.extend(stepwise.minimum());
+    }
+    frame_rates.dedup();
+    frame_rates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cropcap_pixel_aspect_is_inverted_to_width_over_height() {
+        // SAFETY: v4l2_cropcap is a plain C struct, all zeroes is a valid value.
+        let mut cropcap: v4l2_cropcap = unsafe { std::mem::zeroed() };
+        assert_eq!(cropcap_pixel_aspect_ratio(&cropcap), None);
+
+        // NTSC sampled at 13.5 MHz reports 11/10 (height over width).
+        cropcap.defrect.width = 720;
+        cropcap.defrect.height = 480;
+        cropcap.pixelaspect.numerator = 11;
+        cropcap.pixelaspect.denominator = 10;
+        assert_eq!(
+            cropcap_pixel_aspect_ratio(&cropcap),
+            Some((Resolution::new(720, 480), Rational32::new(10, 11)))
+        );
+    }
+
+    #[test]
+    fn only_loopback_and_output_nodes_are_virtual() {
+        let capture = capability::Flags::VIDEO_CAPTURE | capability::Flags::STREAMING;
+        assert!(!is_virtual_device("uvcvideo", capture));
+        assert!(is_virtual_device("v4l2 loopback", capture));
+        assert!(is_virtual_device("vivid", capture | capability::Flags::VIDEO_OUTPUT));
+        assert!(!is_virtual_device(
+            "hantro-vpu",
+            capture | capability::Flags::VIDEO_OUTPUT | capability::Flags::VIDEO_M2M
+        ));
+    }
+
+    fn menu_control(items: Vec<(u32, control::MenuItem)>) -> control::Description {
+        control::Description {
+            id: V4L2_CID_EXPOSURE_AUTO,
+            typ: control::Type::Menu,
+            name: "Auto Exposure".to_string(),
+            minimum: 0,
+            maximum: 3,
+            step: 1,
+            default: 3,
+            flags: control::Flags::from(0),
+            items: Some(items),
+        }
+    }
+
+    #[test]
+    fn menu_controls_are_described_by_their_labels() {
+        let description = menu_control(vec![
+            (1, control::MenuItem::Name("Manual Mode".to_string())),
+            (3, control::MenuItem::Name("Aperture Priority Mode".to_string())),
+        ]);
+
+        let (control_type, descriptor) = describe_control(&description).unwrap();
+        assert_eq!(control_type, ControlType::Menu);
+        assert_eq!(descriptor.menu_label(1), Some("Manual Mode"));
+        assert_eq!(descriptor.menu_label(3), Some("Aperture Priority Mode"));
+        assert_eq!(descriptor.menu_label(2), None);
+
+        assert_eq!(
+            typed_value(&descriptor, ControlValue::Integer(3)),
+            ControlValue::KeyValue("Aperture Priority Mode".to_string(), ControlValuePrimitive::Integer(3))
+        );
+        assert!(descriptor.validate(&typed_value(&descriptor, ControlValue::Integer(1))).is_continue());
+    }
+
+    #[test]
+    fn integer_controls_are_described_by_their_range() {
+        let description = control::Description {
+            id: V4L2_CID_BRIGHTNESS,
+            typ: control::Type::Integer,
+            name: "Brightness".to_string(),
+            minimum: -64,
+            maximum: 64,
+            step: 4,
+            default: 0,
+            flags: control::Flags::SLIDER,
+            items: None,
+        };
+
+        let (control_type, descriptor) = describe_control(&description).unwrap();
+        assert_eq!(control_type, ControlType::Integer);
+        assert_eq!(
+            descriptor,
+            ControlValueDescriptor::Integer(Range::new(0, Some(-64), Some(64), Some(4)))
+        );
+        assert!(descriptor.validate(&ControlValue::Integer(8)).is_continue());
+        assert!(descriptor.validate(&ControlValue::Integer(6)).is_break());
+        assert_eq!(control_flags(description.flags), HashSet::from([ControlFlags::Slider]));
+    }
+
+    #[test]
+    fn buffers_are_stamped_on_the_monotonic_clock() {
+        let metadata = Metadata {
+            timestamp: v4l::timestamp::Timestamp::new(12, 345_678),
+            ..Metadata::default()
+        };
+        assert_eq!(
+            buffer_timestamp(&metadata),
+            Some(RawTimestamp::new(TimestampClock::Monotonic, Duration::new(12, 345_678_000)))
+        );
+
+        // Drivers that do not timestamp leave it zeroed.
+        assert_eq!(buffer_timestamp(&Metadata::default()), None);
+    }
+
+    #[test]
+    fn buffers_are_tagged_with_their_field_order() {
+        let field = |field: FieldOrder| Metadata {
+            field: field as u32,
+            ..Metadata::default()
+        };
+        assert_eq!(buffer_scan_type(&field(FieldOrder::Progressive)), Some(ScanType::Progressive));
+        assert_eq!(buffer_scan_type(&field(FieldOrder::InterlacedTB)), Some(ScanType::InterlacedTff));
+        assert_eq!(buffer_scan_type(&field(FieldOrder::InterlacedBT)), Some(ScanType::InterlacedBff));
+        assert_eq!(buffer_scan_type(&field(FieldOrder::Interlaced)), Some(ScanType::InterlacedTff));
+        // Single fields are not interleaved, so there is nothing to deinterlace.
+        assert_eq!(buffer_scan_type(&field(FieldOrder::Top)), Some(ScanType::Progressive));
+        assert_eq!(buffer_scan_type(&Metadata::default()), None);
+    }
+
+    #[test]
+    fn integer_menu_items_are_labelled_by_value() {
+        let mut description = menu_control(vec![(0, control::MenuItem::Value(100)), (1, control::MenuItem::Value(200))]);
+        description.typ = control::Type::IntegerMenu;
+
+        let (control_type, descriptor) = describe_control(&description).unwrap();
+        assert_eq!(control_type, ControlType::IntegerMenu);
+        assert_eq!(descriptor.menu_label(1), Some("200"));
+    }
+
+    #[test]
+    fn driver_versions_are_formatted_into_the_camera_information() {
+        assert_eq!(driver_version((6, 8, 12)), "6.8.12");
+        assert_eq!(driver_version((0, 0, 0)), "0.0.0");
+
+        let caps = capability::Capabilities {
+            driver: "uvcvideo".to_string(),
+            card: "Integrated Camera".to_string(),
+            bus: "usb-0000:00:14.0-1".to_string(),
+            version: (6, 8, 12),
+            capabilities: capability::Flags::VIDEO_CAPTURE | capability::Flags::STREAMING,
+        };
+        // No /dev/video{u32::MAX}, so nothing is read from sysfs.
+        let info = camera_information(u32::MAX as usize, &caps);
+        assert_eq!(info.driver_version(), Some("6.8.12"));
+        assert_eq!(info.human_name(), "Integrated Camera");
+    }
+
+    #[test]
+    fn devices_that_cannot_stream_fall_back_to_read() {
+        let read_only = capabilities_from_flags(capability::Flags::VIDEO_CAPTURE | capability::Flags::READ_WRITE);
+        assert!(!read_only.streaming);
+        assert_eq!(select_io_method(IoMethod::Mmap, read_only).unwrap(), IoMethod::Read);
+        assert_eq!(select_io_method(IoMethod::Read, read_only).unwrap(), IoMethod::Read);
+        assert!(select_io_method(IoMethod::DmaBuf, read_only).is_err());
+
+        let streaming = capabilities_from_flags(capability::Flags::VIDEO_CAPTURE | capability::Flags::STREAMING);
+        assert_eq!(select_io_method(IoMethod::Mmap, streaming).unwrap(), IoMethod::Mmap);
+        assert_eq!(select_io_method(IoMethod::DmaBuf, streaming).unwrap(), IoMethod::DmaBuf);
+        assert!(select_io_method(IoMethod::Read, streaming).is_err());
+
+        assert!(select_io_method(IoMethod::Mmap, capabilities_from_flags(capability::Flags::STREAMING)).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[ignore = "needs a V4L2 camera at /dev/video0"]
+    async fn async_streams_deliver_frames_without_blocking_the_executor() {
+        let mut camera = V4L2Camera::new(DeviceInner::new(0).unwrap()).unwrap();
+        let stream = camera.open_stream_async().await.unwrap();
+        let frame = stream.await_frame().await.unwrap();
+        assert_eq!(Some(frame.resolution()), camera.camera_format().map(|format| format.resolution()));
+        drop(stream);
+        camera.close_stream_async().await.unwrap();
+
+        // The readiness path, which waits on the device fd in non-blocking mode.
+        let mut inner = StreamInner::new(&camera.device, STREAM_BUFFER_COUNT, IoMethod::Mmap).unwrap();
+        assert!(!inner.next_frame_async().await.unwrap().buffer().is_empty());
+    }
+
+    #[test]
+    #[ignore = "needs a V4L2 camera at /dev/video0"]
+    fn live_reads_refresh_the_cached_value() {
+        let mut camera = V4L2Camera::new(DeviceInner::new(0).unwrap()).unwrap();
+        let cached = |camera: &V4L2Camera, id| camera.properties().control_value(&id).and_then(|c| c.value().clone());
+
+        // Change a writable integer control behind the cache's back.
+        let (id, raw, previous, minimum) = camera
+            .properties()
+            .as_ui_list()
+            .into_iter()
+            .filter(|control| !control.read_only)
+            .find_map(|control| match (&control.descriptor, &control.value) {
+                (ControlValueDescriptor::Integer(range), Some(ControlValue::Integer(value))) => {
+                    let raw = ControlIdIntermediate::from_control_id(control.id)?.0;
+                    let minimum = range.minimum().filter(|minimum| minimum != value)?;
+                    Some((control.id, raw, *value, minimum))
+                }
+                _ => None,
+            })
+            .expect("the camera has no writable integer control");
+        camera.device.set_platform_control(raw, ControlValue::Integer(minimum)).unwrap();
+        assert_eq!(cached(&camera, id), Some(ControlValue::Integer(previous)));
+
+        let read = camera.read_property(&id);
+        camera.device.set_platform_control(raw, ControlValue::Integer(previous)).unwrap();
+        assert_eq!(read.unwrap(), ControlValue::Integer(minimum));
+        assert_eq!(cached(&camera, id), Some(ControlValue::Integer(minimum)));
+    }
 }
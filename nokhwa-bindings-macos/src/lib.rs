@@ -95,6 +95,12 @@ mod internal {
 
             pub fn CMSampleBufferGetDataBuffer(sbuf: CMSampleBufferRef) -> CMBlockBufferRef;
 
+            pub fn CMSampleBufferGetPresentationTimeStamp(sbuf: CMSampleBufferRef) -> CMTime;
+
+            pub fn CMClockGetHostTimeClock() -> CMClockRef;
+
+            pub fn CMClockGetTime(clock: CMClockRef) -> CMTime;
+
             pub fn dispatch_queue_create(
                 label: *const std::os::raw::c_char,
                 attr: NSObject,
@@ -122,6 +128,27 @@ mod internal {
             ) -> *mut std::os::raw::c_void;
 
             pub fn CVPixelBufferGetPixelFormatType(pixelBuffer: CVPixelBufferRef) -> OSType;
+
+            pub fn CVPixelBufferIsPlanar(pixelBuffer: CVPixelBufferRef) -> Boolean;
+
+            pub fn CVPixelBufferGetBaseAddressOfPlane(
+                pixelBuffer: CVPixelBufferRef,
+                planeIndex: std::os::raw::c_ulong,
+            ) -> *mut std::os::raw::c_void;
+
+            pub fn CVPixelBufferGetBytesPerRowOfPlane(
+                pixelBuffer: CVPixelBufferRef,
+                planeIndex: std::os::raw::c_ulong,
+            ) -> std::os::raw::c_ulong;
+
+            pub fn CVPixelBufferGetHeightOfPlane(
+                pixelBuffer: CVPixelBufferRef,
+                planeIndex: std::os::raw::c_ulong,
+            ) -> std::os::raw::c_ulong;
+
+            pub fn CVPixelBufferGetWidth(pixelBuffer: CVPixelBufferRef) -> std::os::raw::c_ulong;
+
+            pub fn CVPixelBufferGetHeight(pixelBuffer: CVPixelBufferRef) -> std::os::raw::c_ulong;
         }
 
         #[repr(C)]
@@ -137,6 +164,12 @@ mod internal {
             _unused: [u8; 0],
         }
 
+        #[repr(C)]
+        #[derive(Debug, Copy, Clone)]
+        pub struct OpaqueCMClock {
+            _unused: [u8; 0],
+        }
+
         #[allow(non_snake_case)]
         #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
         #[repr(C)]
@@ -147,11 +180,13 @@ mod internal {
         }
 
         pub type CVBufferRef = *mut __CVBuffer;
+        pub type CMClockRef = *mut OpaqueCMClock;
 
         pub type CVImageBufferRef = CVBufferRef;
         pub type CVPixelBufferRef = CVImageBufferRef;
         pub type CVPixelBufferLockFlags = u64;
         pub type CVReturn = i32;
+        pub type Boolean = u8;
 
         pub type OSType = FourCharCode;
         pub type AVVideoCodecType = NSString;
@@ -200,10 +235,13 @@ mod internal {
         AVCaptureExposureTargetBiasCurrent, AVCaptureISOCurrent, AVCaptureWhiteBalanceGains,
         AVMediaTypeAudio, AVMediaTypeClosedCaption, AVMediaTypeDepthData, AVMediaTypeMetadata,
         AVMediaTypeMetadataObject, AVMediaTypeMuxed, AVMediaTypeSubtitle, AVMediaTypeText,
-        AVMediaTypeTimecode, AVMediaTypeVideo, CGPoint, CMSampleBufferGetImageBuffer,
+        AVMediaTypeTimecode, AVMediaTypeVideo, CGPoint, CMClockGetHostTimeClock, CMClockGetTime,
+        CMSampleBufferGetImageBuffer, CMSampleBufferGetPresentationTimeStamp,
         CMVideoFormatDescriptionGetDimensions, CVImageBufferRef, CVPixelBufferGetBaseAddress,
-        CVPixelBufferGetDataSize, CVPixelBufferLockBaseAddress, CVPixelBufferUnlockBaseAddress,
-        NSObject, OSType,
+        CVPixelBufferGetBaseAddressOfPlane, CVPixelBufferGetBytesPerRowOfPlane,
+        CVPixelBufferGetDataSize, CVPixelBufferGetHeight, CVPixelBufferGetHeightOfPlane,
+        CVPixelBufferGetPixelFormatType, CVPixelBufferGetWidth, CVPixelBufferIsPlanar,
+        CVPixelBufferLockBaseAddress, CVPixelBufferUnlockBaseAddress, NSObject, OSType,
     };
 
     use block::ConcreteBlock;
@@ -223,6 +261,7 @@ mod internal {
     };
     use flume::{Receiver, Sender};
     use nokhwa_core::{
+        conversions::{nv12_predicted_size, pack_nv12_strided},
         error::NokhwaError,
         types::{
             ApiBackend, CameraFormat, CameraIndex, CameraInformation,
@@ -245,6 +284,7 @@ mod internal {
         error::Error,
         ffi::{c_float, c_void, CStr},
         sync::Arc,
+        time::Duration,
     };
     use nokhwa_core::properties::{CameraControl, ControlValueDescription, ControlValue, KnownCameraControl};
 
@@ -376,6 +416,51 @@ mod internal {
         }
     }
 
+    // Copies the planes of a bi-planar 4:2:0 pixel buffer into a packed NV12 frame. CoreVideo aligns the rows of
+    // each plane (usually to 16 or 64 bytes) and may not keep the planes adjacent, so the base address and bytes per
+    // row of each plane are used instead of reading the buffer as one `width`-strided block.
+    //
+    // SAFETY: `image_buffer` must be a planar pixel buffer whose base address is locked.
+    unsafe fn pack_planar_nv12(image_buffer: CVImageBufferRef) -> Result<Vec<u8>, NokhwaError> {
+        let resolution = Resolution::new(
+            CVPixelBufferGetWidth(image_buffer) as u32,
+            CVPixelBufferGetHeight(image_buffer) as u32,
+        );
+        let plane = |index| {
+            let base = CVPixelBufferGetBaseAddressOfPlane(image_buffer, index).cast::<u8>();
+            let stride = CVPixelBufferGetBytesPerRowOfPlane(image_buffer, index) as usize;
+            let rows = CVPixelBufferGetHeightOfPlane(image_buffer, index) as usize;
+            if base.is_null() {
+                return Err(NokhwaError::ReadFrameError("Plane Pointer Null".to_string()));
+            }
+            Ok((std::slice::from_raw_parts(base, stride * rows), stride))
+        };
+        let (y_plane, y_stride) = plane(0)?;
+        let (uv_plane, uv_stride) = plane(1)?;
+
+        let mut packed = vec![0; nv12_predicted_size(resolution)];
+        pack_nv12_strided(resolution, y_plane, y_stride, uv_plane, uv_stride, &mut packed)?;
+        Ok(packed)
+    }
+
+    // Converts a `CMTime` to the time since its clock's origin, or `None` if it is invalid or negative.
+    fn cmtime_to_duration(time: CMTime) -> Option<Duration> {
+        // kCMTimeFlags_Valid
+        if time.flags & 1 == 0 {
+            return None;
+        }
+        let value = u64::try_from(time.value).ok()?;
+        let timescale = u64::try_from(time.timescale).ok().filter(|timescale| *timescale > 0)?;
+        let nanos = (value % timescale) * 1_000_000_000 / timescale;
+        Some(Duration::new(value / timescale, nanos as u32))
+    }
+
+    /// Reads the Core Media host time clock, the clock sample buffer presentation times are on.
+    pub fn host_time_now() -> Duration {
+        // SAFETY: the host time clock is a process wide singleton that is never released.
+        cmtime_to_duration(unsafe { CMClockGetTime(CMClockGetHostTimeClock()) }).unwrap_or_default()
+    }
+
     pub type CompressionData<'a> = (Cow<'a, [u8]>, FrameFormat);
     pub type DataPipe<'a> = (Sender<CompressionData<'a>>, Receiver<CompressionData<'a>>);
 
@@ -417,23 +502,43 @@ mod internal {
                     CVPixelBufferLockBaseAddress(image_buffer, 0);
                 };
 
-                let buffer_length = unsafe { CVPixelBufferGetDataSize(image_buffer) };
-                let buffer_ptr = unsafe { CVPixelBufferGetBaseAddress(image_buffer) };
-                let buffer_as_vec = unsafe {
-                    std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_length as usize)
-                        .to_vec()
+                // only the 8-bit formats, 10-bit samples take two bytes and are not NV12
+                let is_planar_nv12 = unsafe { CVPixelBufferIsPlanar(image_buffer) } != 0
+                    && matches!(
+                        unsafe { CVPixelBufferGetPixelFormatType(image_buffer) },
+                        kCVPixelFormatType_420YpCbCr8BiPlanarFullRange | 875704438
+                    );
+                let buffer_as_vec = if is_planar_nv12 {
+                    match unsafe { pack_planar_nv12(image_buffer) } {
+                        Ok(packed) => packed,
+                        Err(_) => {
+                            unsafe { CVPixelBufferUnlockBaseAddress(image_buffer, 0) };
+                            return;
+                        }
+                    }
+                } else {
+                    let buffer_length = unsafe { CVPixelBufferGetDataSize(image_buffer) };
+                    let buffer_ptr = unsafe { CVPixelBufferGetBaseAddress(image_buffer) };
+                    unsafe {
+                        std::slice::from_raw_parts_mut(buffer_ptr as *mut u8, buffer_length as usize)
+                            .to_vec()
+                    }
                 };
 
                 unsafe { CVPixelBufferUnlockBaseAddress(image_buffer, 0) };
+                // on the host time clock, see `host_time_now`
+                let presentation_time = cmtime_to_duration(unsafe {
+                    CMSampleBufferGetPresentationTimeStamp(didOutputSampleBuffer)
+                });
                 // oooooh scarey unsafe
                 // AAAAAAAAAAAAAAAAAAAAAAAAA
                 // https://c.tenor.com/0e_zWtFLOzQAAAAC/needy-streamer-overload-needy-girl-overdose.gif
                 let bufferlck_cv: *const c_void = unsafe { msg_send![this, bufferPtr] };
                 let buffer_sndr = unsafe {
-                    let ptr = bufferlck_cv.cast::<Sender<(Vec<u8>, FrameFormat)>>();
+                    let ptr = bufferlck_cv.cast::<Sender<(Vec<u8>, FrameFormat, Option<Duration>)>>();
                     Arc::from_raw(ptr)
                 };
-                if let Err(_) = buffer_sndr.send((buffer_as_vec, FrameFormat::GRAY)) {
+                if let Err(_) = buffer_sndr.send((buffer_as_vec, FrameFormat::GRAY, presentation_time)) {
                     // FIXME: dont, what the fuck???
                     return;
                 }
@@ -681,7 +786,7 @@ mod internal {
     impl AVCaptureVideoCallback {
         pub fn new(
             device_spec: &CStr,
-            buffer: &Arc<Sender<(Vec<u8>, FrameFormat)>>,
+            buffer: &Arc<Sender<(Vec<u8>, FrameFormat, Option<Duration>)>>,
         ) -> Result<Self, NokhwaError> {
             let cls = &CALLBACK_CLASS as &Class;
             let delegate: *mut Object = unsafe { msg_send![cls, alloc] };
@@ -29,6 +29,7 @@
 
 #[cfg(all(windows, not(feature = "docs-only")))]
 pub mod wmf {
+    use nokhwa_core::conversions::{nv12_predicted_size, pack_nv12_strided};
     use nokhwa_core::error::NokhwaError;
     use nokhwa_core::types::{
         ApiBackend, CameraFormat, CameraIndex, CameraInformation,
@@ -39,7 +40,9 @@ pub mod wmf {
     use std::{
         borrow::Cow,
         cell::Cell,
+        marker::PhantomData,
         mem::MaybeUninit,
+        ops::Deref,
         slice::from_raw_parts,
         sync::{
             atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -65,9 +68,10 @@ pub mod wmf {
                 },
                 KernelStreaming::GUID_NULL,
                 MediaFoundation::{
-                    IMFActivate, IMFAttributes, IMFMediaSource, IMFSample, IMFSourceReader,
+                    IMF2DBuffer, IMF2DBuffer2, IMFActivate, IMFAttributes, IMFMediaBuffer, IMFMediaSource, IMFSample,
+                    IMFSourceReader,
                     MFCreateAttributes, MFCreateMediaType, MFCreateSourceReaderFromMediaSource,
-                    MFEnumDeviceSources, MFMediaType_Video, MFShutdown, MFStartup,
+                    MF2DBuffer_LockFlags_Read, MFEnumDeviceSources, MFMediaType_Video, MFShutdown, MFStartup,
                     MFSTARTUP_NOSOCKET, MF_API_VERSION, MF_DEVSOURCE_ATTRIBUTE_FRIENDLY_NAME,
                     MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE,
                     MF_DEVSOURCE_ATTRIBUTE_SOURCE_TYPE_VIDCAP_GUID,
@@ -1116,7 +1120,8 @@ pub mod wmf {
             Ok(())
         }
 
-        pub fn raw_bytes(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+        // Reads the next sample off the source reader as a single contiguous buffer.
+        fn read_buffer(&mut self) -> Result<IMFMediaBuffer, NokhwaError> {
             let mut imf_sample: Option<IMFSample> = match unsafe { MFCreateSample() } {
                 Ok(sample) => Some(sample),
                 Err(why) => {
@@ -1153,11 +1158,116 @@ pub mod wmf {
                 }
             };
 
-            let buffer = match unsafe { imf_sample.ConvertToContiguousBuffer() } {
-                Ok(buf) => buf,
-                Err(why) => return Err(NokhwaError::ReadFrameError(why.to_string())),
+            match unsafe { imf_sample.ConvertToContiguousBuffer() } {
+                Ok(buf) => Ok(buf),
+                Err(why) => Err(NokhwaError::ReadFrameError(why.to_string())),
+            }
+        }
+
+        pub fn raw_bytes(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+            let buffer = self.read_buffer()?;
+            if self.device_format.format() == FrameFormat::NV12 {
+                if let Ok(buffer_2d) = buffer.cast::<IMF2DBuffer>() {
+                    return read_nv12_2d(&buffer_2d, self.device_format.resolution()).map(Cow::from);
+                }
+            }
+
+            let frame = MappedFrame::lock(buffer)?;
+            Ok(Cow::from(frame.to_vec()))
+        }
+
+        /// Reads the next frame without copying it out of Media Foundation's buffer, unlike [`raw_bytes`](Self::raw_bytes).
+        ///
+        /// The buffer stays locked until the returned [`MappedFrame`] is dropped, so drop it before reading the next
+        /// frame, or the source reader may run out of buffers.
+        ///
+        /// To check this by hand, open a 4K stream and compare the time per frame of `map_frame` and `raw_bytes`
+        /// in a release build: the two should return identical bytes, with `map_frame` skipping the per-frame copy.
+        pub fn map_frame(&mut self) -> Result<MappedFrame<'_>, NokhwaError> {
+            MappedFrame::lock(self.read_buffer()?)
+        }
+
+        pub fn stop_stream(&mut self) {
+            self.is_open.set(false);
+        }
+    }
+
+    // Hardware NV12 buffers pad each row to the adapter's alignment, so the rows are copied out with the pitch
+    // `Lock2D` reports instead of assuming they are `width` bytes apart, which would shear the image. Decoders often
+    // pad the height as well (1088 rows for 1080p), so the UV plane is found after the padded Y rows, counted from
+    // the locked length where the buffer reports it.
+    fn read_nv12_2d(buffer: &IMF2DBuffer, resolution: Resolution) -> Result<Vec<u8>, NokhwaError> {
+        let mut scanline_0 = std::ptr::null_mut::<u8>();
+        let mut pitch = 0_i32;
+        let mut locked_length = None;
+        let locked = match buffer.cast::<IMF2DBuffer2>() {
+            Ok(buffer_2) => {
+                let mut buffer_start = std::ptr::null_mut::<u8>();
+                let mut length = 0_u32;
+                let locked = unsafe {
+                    buffer_2.Lock2DSize(
+                        MF2DBuffer_LockFlags_Read,
+                        &mut scanline_0,
+                        &mut pitch,
+                        &mut buffer_start,
+                        &mut length,
+                    )
+                };
+                locked_length = Some(length as usize);
+                locked
+            }
+            Err(_) => unsafe { buffer.Lock2D(&mut scanline_0, &mut pitch) },
+        };
+        if let Err(why) = locked {
+            return Err(NokhwaError::ReadFrameError(why.to_string()));
+        }
+
+        let packed = if scanline_0.is_null() {
+            Err(NokhwaError::ReadFrameError("Buffer Pointer Null".to_string()))
+        } else if pitch < 0 {
+            // NV12 is always top-down, a negative pitch means this is not the buffer we asked for.
+            Err(NokhwaError::ReadFrameError("Bottom-up NV12 buffer".to_string()))
+        } else {
+            let stride = pitch as usize;
+            let height = resolution.height() as usize;
+            // NV12 has half as many UV rows as Y rows, so the Y plane is two thirds of the rows.
+            let luma_rows = locked_length
+                .and_then(|length| length.checked_div(stride))
+                .map(|rows| rows * 2 / 3)
+                .filter(|rows| *rows >= height)
+                .unwrap_or(height);
+            let luma_len = stride * luma_rows;
+            // SAFETY: a locked 2D buffer holds `pitch` bytes for every row of both planes, including the padded Y
+            // rows counted from its locked length, and stays locked until `Unlock2D` below.
+            let data = unsafe {
+                from_raw_parts(scanline_0, luma_len + stride * (height / 2))
             };
+            let mut packed = vec![0; nv12_predicted_size(resolution)];
+            pack_nv12_strided(resolution, &data[..luma_len], stride, &data[luma_len..], stride, &mut packed)
+                .map(|()| packed)
+        };
+
+        // swallow errors, the buffer is released either way
+        unsafe {
+            #[allow(clippy::let_underscore_drop)]
+            let _ = buffer.Unlock2D();
+        }
+        packed
+    }
+
+    /// A frame borrowed straight from a locked `IMFMediaBuffer`, returned by [`MediaFoundationDevice::map_frame`].
+    ///
+    /// Dereferences to the frame's bytes. The buffer is unlocked and released when this is dropped. This is not
+    /// [`Send`]: the buffer belongs to the COM apartment of the thread that read it, and must be released there.
+    pub struct MappedFrame<'a> {
+        buffer: IMFMediaBuffer,
+        data: *const u8,
+        len: usize,
+        _device: PhantomData<&'a mut MediaFoundationDevice>,
+    }
 
+    impl MappedFrame<'_> {
+        fn lock(buffer: IMFMediaBuffer) -> Result<Self, NokhwaError> {
             let mut buffer_valid_length = 0;
             let mut buffer_start_ptr = std::ptr::null_mut::<u8>();
 
@@ -1167,31 +1277,44 @@ pub mod wmf {
                 return Err(NokhwaError::ReadFrameError(why.to_string()));
             }
 
-            if buffer_start_ptr.is_null() {
+            // From here on, dropping `frame` unlocks the buffer, including on the error paths below.
+            let frame = MappedFrame {
+                buffer,
+                data: buffer_start_ptr,
+                len: buffer_valid_length as usize,
+                _device: PhantomData,
+            };
+
+            if frame.data.is_null() {
                 return Err(NokhwaError::ReadFrameError(
                     "Buffer Pointer Null".to_string(),
                 ));
             }
 
-            if buffer_valid_length == 0 {
+            if frame.len == 0 {
                 return Err(NokhwaError::ReadFrameError("Buffer Size is 0".to_string()));
             }
 
-            let mut data_slice = Vec::with_capacity(buffer_valid_length as usize);
+            Ok(frame)
+        }
+    }
 
-            unsafe {
-                // Copy pointer because we're bout to drop IMFSample
-                data_slice.extend_from_slice(std::slice::from_raw_parts_mut(
-                    buffer_start_ptr,
-                    buffer_valid_length as usize,
-                ) as &[u8]);
-            }
+    impl Deref for MappedFrame<'_> {
+        type Target = [u8];
 
-            Ok(Cow::from(data_slice))
+        fn deref(&self) -> &Self::Target {
+            // SAFETY: `lock` checked the pointer is non-null, and the buffer stays locked until `self` is dropped.
+            unsafe { from_raw_parts(self.data, self.len) }
         }
+    }
 
-        pub fn stop_stream(&mut self) {
-            self.is_open.set(false);
+    impl Drop for MappedFrame<'_> {
+        fn drop(&mut self) {
+            // swallow errors, the buffer is released either way
+            unsafe {
+                #[allow(clippy::let_underscore_drop)]
+                let _ = self.buffer.Unlock();
+            }
         }
     }
 
@@ -1229,6 +1352,8 @@ pub mod wmf {
         CameraFormat, CameraIndex, CameraInformation,
     };
     use std::borrow::Cow;
+    use std::marker::PhantomData;
+    use std::ops::Deref;
     use nokhwa_core::properties::{CameraControl, ControlValue, KnownCameraControl};
 
     pub fn initialize_mf() -> Result<(), NokhwaError> {
@@ -1326,9 +1451,27 @@ pub mod wmf {
             ))
         }
 
+        pub fn map_frame(&mut self) -> Result<MappedFrame<'_>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "Only on Windows".to_string(),
+            ))
+        }
+
         pub fn stop_stream(&mut self) {}
     }
 
+    pub struct MappedFrame<'a> {
+        _device: PhantomData<&'a mut MediaFoundationDevice>,
+    }
+
+    impl Deref for MappedFrame<'_> {
+        type Target = [u8];
+
+        fn deref(&self) -> &Self::Target {
+            &[]
+        }
+    }
+
     impl Drop for MediaFoundationDevice {
         fn drop(&mut self) {}
     }
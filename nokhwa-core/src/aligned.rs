@@ -0,0 +1,118 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Byte buffers aligned for SIMD loads and stores.
+
+use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
+
+/// The alignment of an [`AlignedBuf`] in bytes, enough for aligned AVX2 (32 byte) as well as SSE and NEON
+/// (16 byte) loads and stores.
+pub const SIMD_ALIGNMENT: usize = 32;
+
+// The unit of storage, which gives the buffer its alignment.
+#[derive(Copy, Clone)]
+#[repr(C, align(32))]
+struct Block([u8; SIMD_ALIGNMENT]);
+
+/// A zero-initialized byte buffer whose start is aligned to [`SIMD_ALIGNMENT`] bytes, unlike a `Vec<u8>`, whose
+/// alignment is only guaranteed to be 1. Use it as a decode destination for conversions that use aligned SIMD
+/// instructions, see [`FrameBuffer::decode_image_aligned`](crate::frame_buffer::FrameBuffer::decode_image_aligned).
+///
+/// It dereferences to `[u8]`. The allocation is rounded up to a multiple of [`SIMD_ALIGNMENT`], so the padding
+/// after the last byte can be read by a final full-width load.
+#[derive(Clone, Default)]
+pub struct AlignedBuf {
+    blocks: Vec<Block>,
+    len: usize,
+}
+
+impl AlignedBuf {
+    /// Allocates a zeroed buffer of `len` bytes.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        AlignedBuf {
+            blocks: vec![Block([0; SIMD_ALIGNMENT]); len.div_ceil(SIMD_ALIGNMENT)],
+            len,
+        }
+    }
+
+    /// Copies `data` into a new aligned buffer.
+    #[must_use]
+    pub fn from_slice(data: &[u8]) -> Self {
+        let mut buffer = Self::new(data.len());
+        buffer.copy_from_slice(data);
+        buffer
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copies the bytes into a `Vec<u8>`, which is not aligned.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        self[..].to_vec()
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `blocks` holds at least `len` initialized bytes, and `Block` is plain bytes with no padding.
+        unsafe { std::slice::from_raw_parts(self.blocks.as_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as in `deref`, and `&mut self` makes this the only reference to the bytes.
+        unsafe { std::slice::from_raw_parts_mut(self.blocks.as_mut_ptr().cast::<u8>(), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for AlignedBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for AlignedBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl PartialEq for AlignedBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for AlignedBuf {}
+
+impl Debug for AlignedBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlignedBuf").field("len", &self.len).finish_non_exhaustive()
+    }
+}
@@ -1,27 +1,625 @@
+use crate::decoder::{DecodeCapabilities, Decoder};
 use crate::error::{NokhwaError};
+use crate::format_request::RequestedFormat;
+use crate::frame_buffer::FrameBuffer;
 use crate::frame_format::FrameFormat;
-use crate::properties::{ControlId, ControlValue, Properties};
+use crate::platform::format_not_fulfilled;
+use crate::properties::{
+    ControlBody, ControlFlags, ControlId, ControlUnit, ControlValue, ControlValueDescriptor, ControlValuePrimitive,
+    PlatformSpecificControlId, Properties, PropertiesSnapshot,
+};
 use crate::types::{CameraFormat, FrameRate, Resolution};
+use crate::utils::Distance;
 use std::collections::HashMap;
-use crate::stream::Stream;
+use std::time::Duration;
+use crate::stream::{DecodedFrameCallback, IoMethod, Stream, StreamConfig};
+use image::{ImageBuffer, Pixel};
+
+// Whether `frame_format` is uncompressed. Custom formats might be either, so they do not count.
+fn is_known_uncompressed(frame_format: FrameFormat) -> bool {
+    !frame_format.is_compressed() && !matches!(frame_format, FrameFormat::Custom(_))
+}
 
 pub trait Setting {
     fn enumerate_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError>;
 
+    /// Lists the formats that deliver raw sensor data (see [`FrameFormat::RAW`]), such as `Bayer16` or `Luma16`.
+    ///
+    /// Passing one of these to [`set_format`](Setting::set_format) selects it as-is; backends must not
+    /// convert it to a processed format.
+    ///
+    /// # Errors
+    /// Errors if enumerating the formats fails.
+    fn enumerate_raw_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        Ok(self
+            .enumerate_formats()?
+            .into_iter()
+            .filter(|format| format.format().is_raw())
+            .collect())
+    }
+
+    /// Lists the formats that `D` can decode into its output, leaving out compressed formats this build has no
+    /// decoder for (see [`DecodeCapabilities`]).
+    ///
+    /// # Errors
+    /// Errors if enumerating the formats fails.
+    fn enumerate_formats_for<D: Decoder>(&self) -> Result<Vec<CameraFormat>, NokhwaError>
+    where
+        Self: Sized,
+    {
+        Ok(self
+            .enumerate_formats()?
+            .into_iter()
+            .filter(|format| {
+                D::ALLOWED_FORMATS.contains(&format.format())
+                    && DecodeCapabilities::can_decode(format.format())
+            })
+            .collect())
+    }
+
     fn enumerate_resolution_and_frame_rates(
         &self,
         frame_format: FrameFormat,
     ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError>;
 
+    /// The uncompressed format with the highest throughput (pixels per second, ties going to the larger resolution),
+    /// for apps that cannot take MJPEG or H264. High resolution cameras often only offer uncompressed formats at low resolutions.
+    ///
+    /// [`FrameFormat::Custom`] formats are left out, as whether they are compressed is unknown. Returns `None` if the
+    /// camera only offers compressed formats, or enumerating them fails.
+    fn max_uncompressed_format(&self) -> Option<CameraFormat> {
+        let pixel_rate = |format: &CameraFormat| {
+            f64::from(format.width())
+                * f64::from(format.height())
+                * f64::from(format.frame_rate().approximate_float().unwrap_or(0.0))
+        };
+        let area = |format: &CameraFormat| u64::from(format.width()) * u64::from(format.height());
+
+        self.enumerate_formats()
+            .ok()?
+            .into_iter()
+            .filter(|format| is_known_uncompressed(format.format()))
+            .max_by(|a, b| {
+                pixel_rate(a)
+                    .total_cmp(&pixel_rate(b))
+                    .then_with(|| area(a).cmp(&area(b)))
+                    .then_with(|| b.format().display_order().cmp(&a.format().display_order()))
+            })
+    }
+
+    /// Whether the camera offers an uncompressed format (see [`max_uncompressed_format`](Setting::max_uncompressed_format))
+    /// at `resolution`. Returns `false` if enumerating the formats fails.
+    fn has_uncompressed_at(&self, resolution: Resolution) -> bool {
+        self.enumerate_formats().is_ok_and(|formats| {
+            formats.iter().any(|format| {
+                format.resolution() == resolution && is_known_uncompressed(format.format())
+            })
+        })
+    }
+
+    /// Snaps `resolution` to the nearest one the camera supports in `frame_format`, e.g. for a resolution typed
+    /// in by a user. Ties go to the resolution whose aspect ratio is closest to `resolution`'s.
+    ///
+    /// # Errors
+    /// Errors if enumeration fails, or the camera supports no resolutions in `frame_format`.
+    fn clamp_resolution(
+        &self,
+        resolution: Resolution,
+        frame_format: FrameFormat,
+    ) -> Result<Resolution, NokhwaError> {
+        let aspect_difference = |candidate: &Resolution| (candidate.aspect_ratio() - resolution.aspect_ratio()).abs();
+
+        self.enumerate_resolution_and_frame_rates(frame_format)?
+            .into_keys()
+            .min_by(|a, b| {
+                a.distance_from(&resolution)
+                    .cmp(&b.distance_from(&resolution))
+                    .then_with(|| aspect_difference(a).total_cmp(&aspect_difference(b)))
+                    .then_with(|| a.cmp(b))
+            })
+            .ok_or_else(|| NokhwaError::GetPropertyError {
+                property: "clamp_resolution".to_string(),
+                error: format!("no resolutions are supported in {frame_format}"),
+            })
+    }
+
+    /// Lists the frame rates supported for `frame_format` at `resolution`.
+    ///
+    /// By default this picks the entry out of [`enumerate_resolution_and_frame_rates`](Setting::enumerate_resolution_and_frame_rates);
+    /// backends that can query a single combination (e.g. V4L2's `VIDIOC_ENUM_FRAMEINTERVALS`) should override it.
+    ///
+    /// # Errors
+    /// Errors if enumeration fails, or the camera does not support `resolution` in `frame_format`.
+    fn frame_rates_for(
+        &self,
+        frame_format: FrameFormat,
+        resolution: Resolution,
+    ) -> Result<Vec<FrameRate>, NokhwaError> {
+        self.enumerate_resolution_and_frame_rates(frame_format)?
+            .remove(&resolution)
+            .ok_or_else(|| NokhwaError::GetPropertyError {
+                property: "frame_rates_for".to_string(),
+                error: format!("{resolution} is not supported in {frame_format}"),
+            })
+    }
+
     fn set_format(&self, camera_format: CameraFormat) -> Result<(), NokhwaError>;
 
+    /// Sets the format `request` picks out of [`enumerate_formats`](Setting::enumerate_formats), and returns it.
+    ///
+    /// Some virtual cameras and drivers report no formats at all while still streaming their default one. If the
+    /// list is empty and `request` accepts [`FrameFormat::MJpeg`], 640x480 MJPEG ([`CameraFormat::default`]) is tried
+    /// as a last resort.
+    ///
+    /// # Errors
+    /// Errors with [`NokhwaError::no_formats_reported`] if the device reports no formats (and the fallback fails or is
+    /// not allowed), with [`NokhwaError::GetPropertyError`] if no format fulfills `request`, or if setting it fails.
+    fn set_requested_format(&self, request: RequestedFormat<'_>) -> Result<CameraFormat, NokhwaError> {
+        let formats = self.enumerate_formats()?;
+        if formats.is_empty() {
+            let fallback = CameraFormat::default();
+            if request.allowed_frame_formats().contains(&fallback.format()) && self.set_format(fallback).is_ok() {
+                return Ok(fallback);
+            }
+            return Err(NokhwaError::no_formats_reported());
+        }
+
+        let camera_format = request.fulfill(&formats).ok_or_else(format_not_fulfilled)?;
+        self.set_format(camera_format)?;
+        Ok(camera_format)
+    }
+
+    /// The format the device is currently set to, e.g. by the last [`set_format`](Setting::set_format), so it can be
+    /// restored after a temporary change. Returns `None` if the backend does not know it.
+    fn camera_format(&self) -> Option<CameraFormat> {
+        None
+    }
+
+    /// The literal `FourCC` the driver negotiated on the last [`set_format`](Setting::set_format), e.g. `*b"YUYV"`.
+    ///
+    /// This is reported even when it maps to [`FrameFormat::Custom`], so unknown formats can be named in bug reports.
+    /// Returns `None` if nothing has been negotiated yet, or the backend does not identify formats by `FourCC`.
+    fn negotiated_fourcc(&self) -> Option<[u8; 4]> {
+        None
+    }
+
+    /// Number of logical streams this device exposes, e.g. 2 for a stereo camera's left and right views,
+    /// or a depth camera's color and depth streams. Each can be opened with [`Capture::open_stream_n`].
+    fn stream_count(&self) -> usize {
+        1
+    }
+
     fn properties(&self) -> &Properties;
 
+    /// Sets the control `property` to `value`.
+    ///
+    /// Values are checked against the control's [`ControlValueDescriptor`]: integers outside its range, or not
+    /// on its `step` (see [`Range::is_on_step`](crate::ranges::Range::is_on_step)), are rejected. Use
+    /// [`set_property_clamped`](Setting::set_property_clamped) to clamp and round them instead.
+    ///
+    /// # Errors
+    /// Errors if the control does not exist, the value is rejected, or setting it fails.
     fn set_property(
         &mut self,
         property: &ControlId,
         value: ControlValue,
     ) -> Result<(), NokhwaError>;
+
+    /// Reads the current value of `property` from the device, rather than the value cached in
+    /// [`properties`](Setting::properties) when the camera was opened or the control was last set. Use this for
+    /// controls that change on their own, such as [`ControlId::FocusStatus`] or an automatically adjusted
+    /// [`ControlId::ExposureTime`].
+    ///
+    /// Backends that can query the driver (e.g. `VIDIOC_G_CTRL` on V4L2) override this and refresh their cache
+    /// with the value read. By default this returns the cached value.
+    ///
+    /// # Errors
+    /// Errors if the control does not exist, has no value, or reading it fails.
+    fn read_property(&mut self, property: &ControlId) -> Result<ControlValue, NokhwaError> {
+        self.properties()
+            .control_value(property)
+            .and_then(|control| control.value().clone())
+            .ok_or_else(|| NokhwaError::GetPropertyError {
+                property: property.to_string(),
+                error: "Not Found/Not Supported".to_string(),
+            })
+    }
+
+    /// Writes every value in `snapshot` back to the device, e.g. after [`Camera::reopen`]. Controls this camera
+    /// does not have, or that are read-only, are skipped.
+    ///
+    /// # Errors
+    /// Errors if setting any of the values fails. The values after it are not written.
+    fn restore_properties(&mut self, snapshot: &PropertiesSnapshot) -> Result<(), NokhwaError> {
+        let mut values = snapshot
+            .values()
+            .iter()
+            .filter(|(id, _)| {
+                self.properties()
+                    .control_value(id)
+                    .is_some_and(|control| !control.flags().contains(&ControlFlags::ReadOnly))
+            })
+            .map(|(id, value)| (*id, value.clone()))
+            .collect::<Vec<_>>();
+        values.sort_by_key(|(id, _)| *id);
+
+        for (id, value) in values {
+            self.set_property(&id, value)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the device specific control `id` straight from the backend, for controls `nokhwa` does not model
+    /// (e.g. a vendor V4L2 CID). What `id` refers to and which values it takes are entirely backend specific.
+    ///
+    /// By default this reads [`ControlId::PlatformSpecific`] out of [`properties`](Setting::properties).
+    ///
+    /// # Errors
+    /// Errors if the backend has no such control, or reading it fails.
+    fn get_platform_control(&self, id: PlatformSpecificControlId) -> Result<ControlValue, NokhwaError> {
+        self.properties()
+            .control_value(&ControlId::PlatformSpecific(id))
+            .and_then(|control| control.value().clone())
+            .ok_or_else(|| NokhwaError::GetPropertyError {
+                property: ControlId::PlatformSpecific(id).to_string(),
+                error: "Not Found/Not Supported".to_string(),
+            })
+    }
+
+    /// Writes `value` to the device specific control `id`, see [`get_platform_control`](Setting::get_platform_control).
+    /// The value is passed through as-is; what it means is entirely backend specific.
+    ///
+    /// By default this sets [`ControlId::PlatformSpecific`] with [`set_property`](Setting::set_property).
+    ///
+    /// # Errors
+    /// Errors if the backend has no such control, or rejects the value.
+    fn set_platform_control(
+        &mut self,
+        id: PlatformSpecificControlId,
+        value: ControlValue,
+    ) -> Result<(), NokhwaError> {
+        self.set_property(&ControlId::PlatformSpecific(id), value)
+    }
+
+    /// Like [`set_property`](Setting::set_property), but integer and float values outside the control's range are
+    /// clamped into it (and rounded to its `step`) instead of being rejected. Other values are passed through as-is.
+    ///
+    /// Returns the value that was actually set.
+    ///
+    /// # Errors
+    /// Errors if setting the (clamped) value fails.
+    fn set_property_clamped(
+        &mut self,
+        property: &ControlId,
+        value: ControlValue,
+    ) -> Result<ControlValue, NokhwaError> {
+        let clamped = match (
+            self.properties()
+                .control_value(property)
+                .map(ControlBody::descriptor),
+            value,
+        ) {
+            (Some(ControlValueDescriptor::Integer(range)), ControlValue::Integer(int)) => {
+                ControlValue::Integer(range.clamp_value(int))
+            }
+            (Some(ControlValueDescriptor::Float(range)), ControlValue::Float(float)) => {
+                ControlValue::Float(range.clamp_value(float))
+            }
+            (_, value) => value,
+        };
+
+        self.set_property(property, clamped.clone())?;
+        Ok(clamped)
+    }
+
+    /// Returns the shortest and longest shutter speed supported by [`ControlId::ExposureTime`].
+    ///
+    /// Returns `None` if the control is missing, unbounded, or does not report a [`ControlUnit::Time`] unit.
+    fn exposure_range(&self) -> Option<(Duration, Duration)> {
+        let control = self.properties().control_value(&ControlId::ExposureTime)?;
+        let Some(ControlUnit::Time(step)) = control.unit() else {
+            return None;
+        };
+
+        match control.descriptor() {
+            ControlValueDescriptor::Integer(range) => {
+                let minimum = u32::try_from(range.minimum()?).ok()?;
+                let maximum = u32::try_from(range.maximum()?).ok()?;
+                Some((step.checked_mul(minimum)?, step.checked_mul(maximum)?))
+            }
+            ControlValueDescriptor::Float(range) => {
+                let minimum = step.as_secs_f64() * range.minimum()?;
+                let maximum = step.as_secs_f64() * range.maximum()?;
+                Some((
+                    Duration::try_from_secs_f64(minimum).ok()?,
+                    Duration::try_from_secs_f64(maximum).ok()?,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the current [`ControlId::ZoomAbsolute`] as a fraction of its range, from `0.0` (widest) to `1.0`.
+    ///
+    /// Returns `None` if the camera has no zoom control, or its range or current value are unknown.
+    fn zoom_normalized(&self) -> Option<f32> {
+        let zoom = self.properties().control_value(&ControlId::ZoomAbsolute)?;
+        let (minimum, maximum, value) = match (zoom.descriptor(), zoom.value()) {
+            (ControlValueDescriptor::Integer(range), Some(ControlValue::Integer(value))) => (
+                range.minimum()? as f64,
+                range.maximum()? as f64,
+                *value as f64,
+            ),
+            (ControlValueDescriptor::Float(range), Some(ControlValue::Float(value))) => {
+                (range.minimum()?, range.maximum()?, *value)
+            }
+            _ => return None,
+        };
+
+        if maximum <= minimum {
+            return Some(0.0);
+        }
+        Some(((value - minimum) / (maximum - minimum)) as f32)
+    }
+
+    /// Sets [`ControlId::ZoomAbsolute`] to `zoom`, a fraction of its range from `0.0` (widest) to `1.0`,
+    /// so the same slider works whatever units the device's zoom control uses.
+    ///
+    /// # Errors
+    /// Errors if `zoom` is not within `0.0..=1.0`, the camera has no bounded zoom control, or setting it fails.
+    fn set_zoom_normalized(&mut self, zoom: f32) -> Result<(), NokhwaError> {
+        let unsupported = |error: &str| NokhwaError::SetPropertyError {
+            property: ControlId::ZoomAbsolute.to_string(),
+            value: zoom.to_string(),
+            error: error.to_string(),
+        };
+
+        if !(0.0..=1.0).contains(&zoom) {
+            return Err(unsupported("Zoom must be within 0.0..=1.0"));
+        }
+        let zoom = f64::from(zoom);
+
+        let value = match self
+            .properties()
+            .control_value(&ControlId::ZoomAbsolute)
+            .map(ControlBody::descriptor)
+        {
+            Some(ControlValueDescriptor::Integer(range)) => {
+                let (Some(minimum), Some(maximum)) = (range.minimum(), range.maximum()) else {
+                    return Err(unsupported("Zoom range is unbounded"));
+                };
+                let span = (maximum - minimum) as f64;
+                ControlValue::Integer(minimum + (span * zoom).round() as i64)
+            }
+            Some(ControlValueDescriptor::Float(range)) => {
+                let (Some(minimum), Some(maximum)) = (range.minimum(), range.maximum()) else {
+                    return Err(unsupported("Zoom range is unbounded"));
+                };
+                ControlValue::Float(minimum + (maximum - minimum) * zoom)
+            }
+            _ => return Err(unsupported("Not Found/Not Supported")),
+        };
+
+        self.set_property_clamped(&ControlId::ZoomAbsolute, value)
+            .map(|_| ())
+    }
+
+    /// Returns the current [`ControlId::FocusPointOfInterest`] as normalized `(x, y)` coordinates.
+    fn focus_point(&self) -> Option<(f32, f32)> {
+        let point = self.properties().control_value(&ControlId::FocusPointOfInterest)?;
+        match point.value() {
+            Some(ControlValue::Array(values)) => match values.as_slice() {
+                [ControlValuePrimitive::Float(x), ControlValuePrimitive::Float(y)] => Some((*x as f32, *y as f32)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Focuses on `point`, given as `(x, y)` coordinates normalized to `0.0..=1.0` from the top left of the frame,
+    /// e.g. where the user tapped on a preview. Sets [`ControlId::FocusPointOfInterest`].
+    ///
+    /// # Errors
+    /// Errors if either coordinate is outside `0.0..=1.0`, the camera does not support a focus point, or setting it fails.
+    fn set_focus_point(&mut self, point: (f32, f32)) -> Result<(), NokhwaError> {
+        let (x, y) = point;
+        let error = |error: &str| NokhwaError::SetPropertyError {
+            property: ControlId::FocusPointOfInterest.to_string(),
+            value: format!("({x}, {y})"),
+            error: error.to_string(),
+        };
+
+        if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+            return Err(error("Coordinates must be within 0.0..=1.0"));
+        }
+        if self
+            .properties()
+            .control_value(&ControlId::FocusPointOfInterest)
+            .is_none()
+        {
+            return Err(error("Not Found/Not Supported"));
+        }
+
+        self.set_property(
+            &ControlId::FocusPointOfInterest,
+            ControlValue::Array(vec![
+                ControlValuePrimitive::Float(f64::from(x)),
+                ControlValuePrimitive::Float(f64::from(y)),
+            ]),
+        )
+    }
+
+    /// Returns whether [`ControlId::WhiteBalanceMode`] is currently automatic, or `None` if it is not known.
+    fn auto_white_balance(&self) -> Option<bool> {
+        let mode = self.properties().control_value(&ControlId::WhiteBalanceMode)?;
+        match mode.value() {
+            Some(ControlValue::Boolean(auto)) => Some(*auto),
+            Some(ControlValue::Integer(auto)) => Some(*auto != 0),
+            _ => None,
+        }
+    }
+
+    /// Turns automatic white balance ([`ControlId::WhiteBalanceMode`], `V4L2_CID_AUTO_WHITE_BALANCE` on V4L2) on or off.
+    ///
+    /// # Errors
+    /// Errors if the camera has no white balance mode control, or setting it fails.
+    fn set_auto_white_balance(&mut self, auto: bool) -> Result<(), NokhwaError> {
+        let value = match self
+            .properties()
+            .control_value(&ControlId::WhiteBalanceMode)
+            .map(ControlBody::descriptor)
+        {
+            Some(ControlValueDescriptor::Boolean) => ControlValue::Boolean(auto),
+            Some(ControlValueDescriptor::Integer(_)) => ControlValue::Integer(i64::from(auto)),
+            _ => {
+                return Err(NokhwaError::SetPropertyError {
+                    property: ControlId::WhiteBalanceMode.to_string(),
+                    value: auto.to_string(),
+                    error: "Not Found/Not Supported".to_string(),
+                })
+            }
+        };
+
+        self.set_property(&ControlId::WhiteBalanceMode, value)
+    }
+
+    /// Switches off the automatic mode that overrides `property`, so a manual value written to it sticks:
+    /// [`ControlId::WhiteBalanceMode`] for [`ControlId::WhiteBalanceTemperature`], and [`ControlId::ExposureIsoMode`]
+    /// for [`ControlId::ExposureIsoSensitivity`]. Other controls, modes that are already manual, and cameras without
+    /// the mode control are left alone.
+    ///
+    /// Backends call this at the start of [`set_property`](Setting::set_property), so setting either control
+    /// **turns its automatic mode off** as a side effect, however it is set.
+    ///
+    /// # Errors
+    /// Errors if switching the mode fails.
+    fn switch_to_manual_for(&mut self, property: &ControlId) -> Result<(), NokhwaError> {
+        let mode = match property {
+            ControlId::WhiteBalanceTemperature => ControlId::WhiteBalanceMode,
+            ControlId::ExposureIsoSensitivity => ControlId::ExposureIsoMode,
+            _ => return Ok(()),
+        };
+        let Some(control) = self.properties().control_value(&mode) else {
+            return Ok(());
+        };
+
+        // Both modes are on/off, or a V4L2 style menu whose manual entry is 0. An unknown value counts as automatic.
+        let manual = match control.descriptor() {
+            ControlValueDescriptor::Boolean => ControlValue::Boolean(false),
+            ControlValueDescriptor::Integer(_) => ControlValue::Integer(0),
+            descriptor @ ControlValueDescriptor::Menu(_) => match descriptor.menu_label(0) {
+                Some(label) => ControlValue::KeyValue(label.to_string(), ControlValuePrimitive::Integer(0)),
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        if control.value().as_ref() == Some(&manual) {
+            return Ok(());
+        }
+
+        self.set_property(&mode, manual)
+    }
+
+    /// Sets a manual [`ControlId::WhiteBalanceTemperature`].
+    ///
+    /// Many drivers silently ignore the temperature while automatic white balance is on, so if it is
+    /// currently on this **turns it off first** (see [`switch_to_manual_for`](Setting::switch_to_manual_for)),
+    /// then writes the temperature.
+    ///
+    /// # Errors
+    /// Errors if automatic white balance could not be turned off, or setting the temperature fails.
+    fn set_white_balance_temperature(&mut self, value: ControlValue) -> Result<(), NokhwaError> {
+        self.switch_to_manual_for(&ControlId::WhiteBalanceTemperature)?;
+        self.set_property(&ControlId::WhiteBalanceTemperature, value)
+    }
+
+    /// Switches focus to manual ([`ControlId::FocusMode`] off) and holds [`ControlId::FocusAbsolute`] at `value`, or
+    /// wherever auto focus left it if `value` is `None`.
+    ///
+    /// Drivers ignore the focus position while auto focus is on, and many move the lens to a default position when
+    /// it is turned off, which shows as a visible jump. So the current position is [read](Setting::read_property)
+    /// first, and written back right after switching to manual.
+    ///
+    /// Returns the focus position that was set.
+    ///
+    /// # Errors
+    /// Errors if the camera has no focus mode or position control, the current position could not be read, or setting
+    /// either control fails.
+    fn set_manual_focus(&mut self, value: Option<ControlValue>) -> Result<ControlValue, NokhwaError> {
+        let position = match value {
+            Some(position) => position,
+            None => self.read_property(&ControlId::FocusAbsolute)?,
+        };
+
+        let manual = match self
+            .properties()
+            .control_value(&ControlId::FocusMode)
+            .map(ControlBody::descriptor)
+        {
+            Some(ControlValueDescriptor::Boolean) => ControlValue::Boolean(false),
+            Some(ControlValueDescriptor::Integer(_)) => ControlValue::Integer(0),
+            _ => {
+                return Err(NokhwaError::SetPropertyError {
+                    property: ControlId::FocusMode.to_string(),
+                    value: "manual".to_string(),
+                    error: "Not Found/Not Supported".to_string(),
+                })
+            }
+        };
+
+        self.set_property(&ControlId::FocusMode, manual)?;
+        self.set_property(&ControlId::FocusAbsolute, position.clone())?;
+        Ok(position)
+    }
+
+    /// Returns the current [`ControlId::ExposureIsoSensitivity`] as an ISO number, e.g. `400`.
+    ///
+    /// Returns `None` if the camera has no ISO control, or its current value is unknown.
+    fn iso(&self) -> Option<u32> {
+        let iso = self.properties().control_value(&ControlId::ExposureIsoSensitivity)?;
+        match iso.value() {
+            Some(ControlValue::Integer(value)) => u32::try_from(*value).ok(),
+            Some(ControlValue::Float(value)) if value.is_finite() && *value >= 0.0 => Some(value.round() as u32),
+            _ => None,
+        }
+    }
+
+    /// Sets a manual [`ControlId::ExposureIsoSensitivity`], clamped into the supported range
+    /// (see [`set_property_clamped`](Setting::set_property_clamped)).
+    ///
+    /// Like [`set_white_balance_temperature`](Setting::set_white_balance_temperature), the ISO is ignored while it
+    /// is automatic, so this **switches [`ControlId::ExposureIsoMode`] to manual first** if the camera has that
+    /// control.
+    ///
+    /// Returns the ISO that was set.
+    ///
+    /// # Errors
+    /// Errors if the camera has no ISO control, or switching the mode or setting the ISO fails.
+    fn set_iso(&mut self, iso: u32) -> Result<u32, NokhwaError> {
+        let value = match self
+            .properties()
+            .control_value(&ControlId::ExposureIsoSensitivity)
+            .map(ControlBody::descriptor)
+        {
+            Some(ControlValueDescriptor::Integer(_)) => ControlValue::Integer(i64::from(iso)),
+            Some(ControlValueDescriptor::Float(_)) => ControlValue::Float(f64::from(iso)),
+            _ => {
+                return Err(NokhwaError::SetPropertyError {
+                    property: ControlId::ExposureIsoSensitivity.to_string(),
+                    value: iso.to_string(),
+                    error: "Not Found/Not Supported".to_string(),
+                })
+            }
+        };
+
+        self.switch_to_manual_for(&ControlId::ExposureIsoSensitivity)?;
+
+        match self.set_property_clamped(&ControlId::ExposureIsoSensitivity, value)? {
+            ControlValue::Integer(set) => Ok(u32::try_from(set).unwrap_or(iso)),
+            ControlValue::Float(set) => Ok(set.round() as u32),
+            _ => Ok(iso),
+        }
+    }
 }
 
 #[cfg(feature = "async")]
@@ -45,13 +643,124 @@ pub trait AsyncSetting {
 }
 
 pub trait Capture {
-    // Implementations MUST guarantee that there can only ever be one stream open at once.
+    // Implementations MUST guarantee that there can only ever be one stream open at once, and MUST call
+    // `ensure_streamable` before opening it.
     fn open_stream(&mut self) -> Result<Stream, NokhwaError>;
 
     // Implementations MUST be multi-close tolerant.
     fn close_stream(&mut self) -> Result<(), NokhwaError>;
+
+    /// Whether this camera was opened with [`OpenOptions::read_only`](crate::platform::OpenOptions::read_only).
+    ///
+    /// Read-only cameras refuse to open a stream, see [`ensure_streamable`](Capture::ensure_streamable).
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// The check every stream-opening path goes through: [`open_stream`](Capture::open_stream) and any override
+    /// of [`open_stream_with`](Capture::open_stream_with) or [`open_stream_n`](Capture::open_stream_n) that does
+    /// not delegate to it MUST call this before touching the device. The provided methods all end up in
+    /// `open_stream`.
+    ///
+    /// # Errors
+    /// Errors with [`NokhwaError::read_only_stream`] if the camera [is read-only](Capture::is_read_only).
+    fn ensure_streamable(&self) -> Result<(), NokhwaError> {
+        if self.is_read_only() {
+            return Err(NokhwaError::read_only_stream());
+        }
+
+        Ok(())
+    }
+
+    /// Opens the logical stream at `index`, see [`Setting::stream_count`]. Stream `0` is the one
+    /// [`open_stream`](Capture::open_stream) opens.
+    ///
+    /// Multi-stream backends should override this, and allow one stream per index to be open at once.
+    ///
+    /// # Errors
+    /// Errors if `index` is out of range, or the stream fails to open.
+    fn open_stream_n(&mut self, index: usize) -> Result<Stream, NokhwaError> {
+        if index != 0 {
+            return Err(NokhwaError::OpenStreamError(format!(
+                "stream {index} does not exist, this device has a single stream"
+            )));
+        }
+
+        self.open_stream()
+    }
+
+    /// Takes a single still frame in a format chosen by `format`, typically at a higher resolution than the video
+    /// stream. Backends map this to their still capture path (a photo pin, `AVCapturePhotoOutput`, or a one-off
+    /// grab at a higher resolution), and restore the streaming format afterwards.
+    ///
+    /// # Errors
+    /// Errors if the backend does not support still capture, no format fulfills `format`, or the capture fails.
+    fn capture_still(&mut self, format: RequestedFormat<'_>) -> Result<FrameBuffer, NokhwaError> {
+        Err(NokhwaError::NotImplementedError(format!(
+            "still capture ({:?}) is not supported by this backend",
+            format.requested_format_type()
+        )))
+    }
+
+    /// Opens a stream with the given [`StreamConfig`]. Backends that support more than [`IoMethod::Mmap`], or that
+    /// capture on a thread of their own, should override this, honouring [`StreamConfig::first_frame_timeout`] with
+    /// [`Stream::wait_for_first_frame`] and calling [`StreamConfig::apply_to_current_thread`] on the capture thread.
+    /// By default only the default I/O method is accepted, with no thread priority or affinity.
+    ///
+    /// # Errors
+    /// Errors if the backend does not support the requested configuration, the stream fails to open, or the first
+    /// frame does not arrive within [`StreamConfig::first_frame_timeout`]. In that last case the stream is closed.
+    fn open_stream_with(&mut self, config: StreamConfig) -> Result<Stream, NokhwaError> {
+        if config.io_method != IoMethod::Mmap {
+            return Err(NokhwaError::NotImplementedError(format!(
+                "{:?} I/O is not supported by this backend",
+                config.io_method
+            )));
+        }
+        if config.thread_priority.is_some() || config.cpu_affinity.is_some() {
+            return Err(NokhwaError::NotImplementedError(
+                "capture thread priority and affinity are not supported by this backend".to_string(),
+            ));
+        }
+
+        let stream = self.open_stream()?;
+        if let Some(timeout) = config.first_frame_timeout {
+            if let Err(why) = stream.wait_for_first_frame(timeout) {
+                drop(stream);
+                self.close_stream()?;
+                return Err(why);
+            }
+        }
+
+        Ok(stream)
+    }
+
+    /// Opens a stream and passes each of its frames, decoded with `decoder`, to `on_frame` on a background thread.
+    /// See [`Stream::on_decoded_frame`].
+    ///
+    /// # Errors
+    /// Errors if the stream fails to open, or the decoding thread could not be spawned.
+    fn on_decoded_frame<D>(
+        &mut self,
+        decoder: D,
+        on_frame: impl FnMut(&ImageBuffer<D::OutputPixels, Vec<<D::OutputPixels as Pixel>::Subpixel>>)
+            + Send
+            + 'static,
+        on_error: impl FnMut(NokhwaError) + Send + 'static,
+    ) -> Result<DecodedFrameCallback, NokhwaError>
+    where
+        D: Decoder + Send + 'static,
+        Self: Sized,
+    {
+        self.open_stream()?.on_decoded_frame(decoder, on_frame, on_error)
+    }
 }
 
+/// Opens a [`Stream`] from async code. Its frames are awaited with [`Stream::await_frame`], which never blocks the
+/// executor, whether the backend captures on a thread of its own or waits on the device's readiness.
+///
+/// Backends that wait on readiness switch the device to non-blocking mode, so it must not also be read through
+/// the blocking [`Capture`] path while an async stream is open.
 #[cfg(feature = "async")]
 pub trait AsyncStream {
     async fn open_stream_async(&mut self) -> Result<Stream, NokhwaError>;
@@ -59,7 +768,441 @@ pub trait AsyncStream {
     async fn close_stream_async(&mut self) -> Result<(), NokhwaError>;
 }
 
-pub trait Camera: Setting + Capture {}
+pub trait Camera: Setting + Capture {
+    /// Closes any open stream and releases the OS device, so other applications can use it, while keeping this
+    /// camera's format and control values. [`reopen`](Camera::reopen) opens the device again and restores them.
+    ///
+    /// While released, opening a stream fails. Releasing an already released camera does nothing.
+    ///
+    /// Backends that can release their device override this along with [`reopen`](Camera::reopen) and
+    /// [`is_released`](Camera::is_released); by default it is not supported.
+    ///
+    /// # Errors
+    /// Errors if the backend cannot release its device, or closing the stream fails.
+    fn release(&mut self) -> Result<(), NokhwaError> {
+        Err(NokhwaError::NotImplementedError(
+            "releasing the device is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Opens the device again after [`release`](Camera::release), then restores the format and control values it
+    /// had, see [`Setting::restore_properties`]. Reopening a camera that is not released does nothing.
+    ///
+    /// # Errors
+    /// Errors if the device cannot be opened again (e.g. it was unplugged), or restoring its settings fails.
+    fn reopen(&mut self) -> Result<(), NokhwaError> {
+        Err(NokhwaError::NotImplementedError(
+            "reopening the device is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Whether the device is currently [released](Camera::release).
+    fn is_released(&self) -> bool {
+        false
+    }
+}
 
 #[cfg(feature = "async")]
 pub trait AsyncCamera: Camera + AsyncSetting + AsyncStream {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NokhwaResult;
+    use crate::format_request::RequestedFormatType;
+    use crate::properties::{ControlType, ControlValuePrimitiveDescriptor};
+    use crate::ranges::Range;
+    use crate::stream::StreamInnerTrait;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    // A camera with automatic white balance and ISO turned on, recording every control it writes, and how many
+    // writes came before each control it read.
+    struct AutoCamera {
+        properties: Properties,
+        written: Vec<(ControlId, ControlValue)>,
+        read: Vec<(ControlId, usize)>,
+    }
+
+    impl AutoCamera {
+        fn new() -> Self {
+            let control = |control_type, descriptor, value| {
+                ControlBody::new(control_type, HashSet::new(), descriptor, Some(value), None)
+            };
+            let menu = ControlValueDescriptor::menu_from_items([(0, "Manual".to_string()), (1, "Auto".to_string())]);
+            let auto = ControlValue::KeyValue("Auto".to_string(), ControlValuePrimitive::Integer(1));
+
+            AutoCamera {
+                properties: Properties::new(HashMap::from([
+                    (
+                        ControlId::WhiteBalanceMode,
+                        control(ControlType::BinaryMenu, ControlValueDescriptor::Boolean, ControlValue::Boolean(true)),
+                    ),
+                    (
+                        ControlId::WhiteBalanceTemperature,
+                        control(
+                            ControlType::Integer,
+                            ControlValueDescriptor::Integer(Range::new(4600, Some(2800), Some(6500), Some(1))),
+                            ControlValue::Integer(4600),
+                        ),
+                    ),
+                    (ControlId::ExposureIsoMode, control(ControlType::Menu, menu, auto)),
+                    (
+                        ControlId::ExposureIsoSensitivity,
+                        control(
+                            ControlType::Integer,
+                            ControlValueDescriptor::Integer(Range::new(100, Some(100), Some(1600), Some(1))),
+                            ControlValue::Integer(100),
+                        ),
+                    ),
+                ])),
+                written: Vec::new(),
+                read: Vec::new(),
+            }
+        }
+    }
+
+    // A camera with only `controls`.
+    fn camera_with(controls: impl IntoIterator<Item = (ControlId, ControlBody)>) -> AutoCamera {
+        AutoCamera {
+            properties: Properties::new(controls.into_iter().collect()),
+            written: Vec::new(),
+            read: Vec::new(),
+        }
+    }
+
+    fn integer_control(value: i64, minimum: i64, maximum: i64, step: i64) -> ControlBody {
+        ControlBody::new(
+            ControlType::Integer,
+            HashSet::new(),
+            ControlValueDescriptor::Integer(Range::new(value, Some(minimum), Some(maximum), Some(step))),
+            Some(ControlValue::Integer(value)),
+            None,
+        )
+    }
+
+    // V4L2_CID_BRIGHTNESS
+    const BRIGHTNESS: ControlId = ControlId::PlatformSpecific(0x0098_0900);
+
+    impl Setting for AutoCamera {
+        fn enumerate_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+            Ok(vec![])
+        }
+
+        fn enumerate_resolution_and_frame_rates(
+            &self,
+            _: FrameFormat,
+        ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError> {
+            Ok(HashMap::new())
+        }
+
+        fn set_format(&self, _: CameraFormat) -> Result<(), NokhwaError> {
+            Ok(())
+        }
+
+        fn properties(&self) -> &Properties {
+            &self.properties
+        }
+
+        fn set_property(&mut self, property: &ControlId, value: ControlValue) -> Result<(), NokhwaError> {
+            self.switch_to_manual_for(property)?;
+            self.properties.set_control_value(property, value.clone())?;
+            self.written.push((*property, value));
+            Ok(())
+        }
+
+        fn read_property(&mut self, property: &ControlId) -> Result<ControlValue, NokhwaError> {
+            self.read.push((*property, self.written.len()));
+            self.properties
+                .control_value(property)
+                .and_then(|control| control.value().clone())
+                .ok_or_else(|| NokhwaError::GetPropertyError {
+                    property: property.to_string(),
+                    error: "Not Found/Not Supported".to_string(),
+                })
+        }
+    }
+
+    #[test]
+    fn setting_white_balance_temperature_turns_auto_off_first() {
+        let mut camera = AutoCamera::new();
+        camera.set_property(&ControlId::WhiteBalanceTemperature, ControlValue::Integer(3200)).unwrap();
+        camera.set_white_balance_temperature(ControlValue::Integer(5000)).unwrap();
+
+        assert_eq!(
+            camera.written,
+            [
+                (ControlId::WhiteBalanceMode, ControlValue::Boolean(false)),
+                (ControlId::WhiteBalanceTemperature, ControlValue::Integer(3200)),
+                (ControlId::WhiteBalanceTemperature, ControlValue::Integer(5000)),
+            ]
+        );
+        assert_eq!(camera.auto_white_balance(), Some(false));
+    }
+
+    #[test]
+    fn setting_iso_picks_the_manual_menu_entry_first() {
+        let mut camera = AutoCamera::new();
+        assert_eq!(camera.set_iso(400).unwrap(), 400);
+
+        assert_eq!(
+            camera.written,
+            [
+                (
+                    ControlId::ExposureIsoMode,
+                    ControlValue::KeyValue("Manual".to_string(), ControlValuePrimitive::Integer(0)),
+                ),
+                (ControlId::ExposureIsoSensitivity, ControlValue::Integer(400)),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_property_clamped_clamps_and_rounds_to_the_step() {
+        // -64..=64 in steps of 10 from -64, so 64 itself is not on a step.
+        let mut camera = camera_with([(BRIGHTNESS, integer_control(0, -64, 64, 10))]);
+
+        let mut set = |value| camera.set_property_clamped(&BRIGHTNESS, ControlValue::Integer(value)).unwrap();
+        assert_eq!(set(100), ControlValue::Integer(56));
+        assert_eq!(set(3), ControlValue::Integer(6));
+        assert_eq!(set(-1000), ControlValue::Integer(-64));
+        assert_eq!(
+            camera.written,
+            [
+                (BRIGHTNESS, ControlValue::Integer(56)),
+                (BRIGHTNESS, ControlValue::Integer(6)),
+                (BRIGHTNESS, ControlValue::Integer(-64)),
+            ]
+        );
+        // The unclamped value is rejected.
+        assert!(camera.set_property(&BRIGHTNESS, ControlValue::Integer(100)).is_err());
+    }
+
+    #[test]
+    fn exposure_range_scales_by_the_time_unit() {
+        // V4L2_CID_EXPOSURE_ABSOLUTE counts in 100µs.
+        let exposure = integer_control(100, 3, 2047, 1).with_unit(ControlUnit::Time(Duration::from_micros(100)));
+        let camera = camera_with([(ControlId::ExposureTime, exposure)]);
+        assert_eq!(
+            camera.exposure_range(),
+            Some((Duration::from_micros(300), Duration::from_micros(204_700)))
+        );
+
+        let unitless = camera_with([(ControlId::ExposureTime, integer_control(100, 3, 2047, 1))]);
+        assert_eq!(unitless.exposure_range(), None);
+        assert_eq!(camera_with([]).exposure_range(), None);
+    }
+
+    #[test]
+    fn normalized_zoom_maps_onto_the_control_range() {
+        let mut camera = camera_with([(ControlId::ZoomAbsolute, integer_control(100, 100, 500, 1))]);
+        assert_eq!(camera.zoom_normalized(), Some(0.0));
+
+        camera.set_zoom_normalized(0.5).unwrap();
+        assert_eq!(camera.written, [(ControlId::ZoomAbsolute, ControlValue::Integer(300))]);
+        assert_eq!(camera.zoom_normalized(), Some(0.5));
+
+        assert!(matches!(camera.set_zoom_normalized(1.5), Err(NokhwaError::SetPropertyError { .. })));
+        let mut no_zoom = camera_with([]);
+        assert_eq!(no_zoom.zoom_normalized(), None);
+        assert!(matches!(
+            no_zoom.set_zoom_normalized(0.5),
+            Err(NokhwaError::SetPropertyError { error, .. }) if error == "Not Found/Not Supported"
+        ));
+    }
+
+    #[test]
+    fn focus_point_is_bounded_and_needs_the_control() {
+        let unit = ControlValuePrimitiveDescriptor::Float(Range::new(0.5, Some(0.0), Some(1.0), None));
+        let centre = ControlValue::Array(vec![ControlValuePrimitive::Float(0.5), ControlValuePrimitive::Float(0.5)]);
+        // `ControlType` has no array kind, only the descriptor is checked.
+        let point = ControlBody::new(
+            ControlType::Integer,
+            HashSet::new(),
+            ControlValueDescriptor::Array(unit),
+            Some(centre),
+            None,
+        );
+        let mut camera = camera_with([(ControlId::FocusPointOfInterest, point)]);
+
+        camera.set_focus_point((0.25, 1.0)).unwrap();
+        assert_eq!(camera.focus_point(), Some((0.25, 1.0)));
+        for outside in [(-0.1, 0.5), (0.5, 1.1), (f32::NAN, 0.5)] {
+            assert!(matches!(
+                camera.set_focus_point(outside),
+                Err(NokhwaError::SetPropertyError { error, .. }) if error == "Coordinates must be within 0.0..=1.0"
+            ));
+        }
+        assert_eq!(camera.written.len(), 1);
+
+        assert!(matches!(
+            camera_with([]).set_focus_point((0.5, 0.5)),
+            Err(NokhwaError::SetPropertyError { error, .. }) if error == "Not Found/Not Supported"
+        ));
+    }
+
+    #[test]
+    fn manual_focus_reads_the_position_before_switching_to_manual() {
+        let auto_focus = ControlBody::new(
+            ControlType::BinaryMenu,
+            HashSet::new(),
+            ControlValueDescriptor::Boolean,
+            Some(ControlValue::Boolean(true)),
+            None,
+        );
+        let focus = || {
+            camera_with([
+                (ControlId::FocusMode, auto_focus.clone()),
+                (ControlId::FocusAbsolute, integer_control(70, 0, 255, 1)),
+            ])
+        };
+
+        let mut camera = focus();
+        assert_eq!(camera.set_manual_focus(None).unwrap(), ControlValue::Integer(70));
+        assert_eq!(camera.read, [(ControlId::FocusAbsolute, 0)]);
+        assert_eq!(
+            camera.written,
+            [
+                (ControlId::FocusMode, ControlValue::Boolean(false)),
+                (ControlId::FocusAbsolute, ControlValue::Integer(70)),
+            ]
+        );
+
+        let mut camera = focus();
+        assert_eq!(camera.set_manual_focus(Some(ControlValue::Integer(20))).unwrap(), ControlValue::Integer(20));
+        assert!(camera.read.is_empty());
+        assert_eq!(camera.written[1], (ControlId::FocusAbsolute, ControlValue::Integer(20)));
+
+        assert!(camera_with([]).set_manual_focus(Some(ControlValue::Integer(20))).is_err());
+    }
+
+    #[test]
+    fn cameras_without_formats_fall_back_to_mjpeg_if_allowed() {
+        // `AutoCamera` lists no formats.
+        let camera = AutoCamera::new();
+        let request = |formats| RequestedFormat::with_formats(RequestedFormatType::AbsoluteHighestResolution, formats);
+
+        assert_eq!(
+            camera.set_requested_format(request(&[FrameFormat::Yuyv422, FrameFormat::MJpeg])).unwrap(),
+            CameraFormat::new(Resolution::new(640, 480), FrameFormat::MJpeg, FrameRate::default())
+        );
+        assert_eq!(
+            camera.set_requested_format(request(&[FrameFormat::Yuyv422])).unwrap_err().to_string(),
+            NokhwaError::no_formats_reported().to_string()
+        );
+    }
+
+    // A camera that only lists formats, with no controls.
+    struct ListedCamera(Vec<CameraFormat>, Properties);
+
+    impl Setting for ListedCamera {
+        fn enumerate_formats(&self) -> Result<Vec<CameraFormat>, NokhwaError> {
+            Ok(self.0.clone())
+        }
+
+        fn enumerate_resolution_and_frame_rates(
+            &self,
+            _: FrameFormat,
+        ) -> Result<HashMap<Resolution, Vec<FrameRate>>, NokhwaError> {
+            Ok(HashMap::new())
+        }
+
+        fn set_format(&self, _: CameraFormat) -> Result<(), NokhwaError> {
+            Ok(())
+        }
+
+        fn properties(&self) -> &Properties {
+            &self.1
+        }
+
+        fn set_property(&mut self, _: &ControlId, _: ControlValue) -> Result<(), NokhwaError> {
+            Ok(())
+        }
+    }
+
+    // Stands in for an RGB decoder that takes raw, MJPEG and H264 frames.
+    struct RgbDecoder;
+
+    impl Decoder for RgbDecoder {
+        const ALLOWED_FORMATS: &'static [FrameFormat] =
+            &[FrameFormat::Rgb888, FrameFormat::Yuyv422, FrameFormat::MJpeg, FrameFormat::H264];
+        type OutputPixels = image::Rgb<u8>;
+        type PixelContainer = Vec<u8>;
+
+        fn decode(&mut self, _: &FrameBuffer) -> Result<image::RgbImage, NokhwaError> {
+            Err(NokhwaError::NotImplementedError("decode".to_string()))
+        }
+
+        fn decode_buffer(&mut self, _: &FrameBuffer, _: &mut [u8]) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError("decode_buffer".to_string()))
+        }
+    }
+
+    #[test]
+    fn only_compressed_formats_this_build_decodes_are_offered() {
+        let formats = [FrameFormat::H264, FrameFormat::MJpeg, FrameFormat::Yuyv422, FrameFormat::Luma8]
+            .map(|frame_format| CameraFormat::new(Resolution::new(1280, 720), frame_format, FrameRate::frame_rate(30)));
+        let camera = ListedCamera(formats.to_vec(), Properties::empty());
+
+        let offered = camera
+            .enumerate_formats_for::<RgbDecoder>()
+            .unwrap()
+            .into_iter()
+            .map(|format| format.format())
+            .collect::<Vec<_>>();
+        if cfg!(feature = "mjpeg") {
+            assert_eq!(offered, [FrameFormat::MJpeg, FrameFormat::Yuyv422]);
+        } else {
+            assert_eq!(offered, [FrameFormat::Yuyv422]);
+        }
+    }
+
+    // A backend that opens streams fine but never delivers a frame on them.
+    #[derive(Default)]
+    struct SilentCamera {
+        senders: Vec<flume::Sender<FrameBuffer>>,
+        closed: usize,
+    }
+
+    struct SilentStream(Arc<flume::Receiver<FrameBuffer>>);
+
+    impl StreamInnerTrait for SilentStream {
+        fn receiver(&self) -> Arc<flume::Receiver<FrameBuffer>> {
+            self.0.clone()
+        }
+
+        fn stop(&mut self) -> NokhwaResult<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture for SilentCamera {
+        fn open_stream(&mut self) -> Result<Stream, NokhwaError> {
+            // Keep the sender, so the stream stays connected.
+            let (sender, receiver) = flume::unbounded();
+            self.senders.push(sender);
+            Ok(Stream::new(Box::new(SilentStream(Arc::new(receiver)))))
+        }
+
+        fn close_stream(&mut self) -> Result<(), NokhwaError> {
+            self.closed += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn first_frame_timeout_closes_streams_that_never_deliver() {
+        let mut camera = SilentCamera::default();
+        let timeout = Duration::from_millis(50);
+        let started = std::time::Instant::now();
+
+        let result = camera.open_stream_with(StreamConfig::new().with_first_frame_timeout(timeout));
+        assert!(matches!(result, Err(NokhwaError::ReadFrameError(why)) if why.starts_with("no frame arrived")));
+        assert!(started.elapsed() >= timeout);
+        assert_eq!(camera.closed, 1);
+
+        // Without a timeout the stream opens, frames or not.
+        assert!(camera.open_stream_with(StreamConfig::new()).is_ok());
+        assert_eq!(camera.closed, 1);
+    }
+}
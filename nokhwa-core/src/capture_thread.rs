@@ -0,0 +1,145 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Scheduling controls for the threads backends capture frames on, see
+//! [`StreamConfig::thread_priority`](crate::stream::StreamConfig::thread_priority).
+
+use crate::error::{NokhwaError, NokhwaResult};
+
+/// How the OS should schedule a capture thread relative to the rest of the application.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ThreadPriority {
+    Low,
+    Normal,
+    High,
+    /// A real-time scheduling class (`SCHED_FIFO` on Linux, `THREAD_PRIORITY_TIME_CRITICAL` on Windows).
+    /// On Linux this usually needs `CAP_SYS_NICE` or an `RLIMIT_RTPRIO` allowance.
+    RealTime,
+}
+
+/// Sets the priority of the calling thread.
+/// # Errors
+/// Errors if the OS refuses (e.g. raising priority without the needed privileges), or on platforms other than
+/// Linux and Windows.
+pub fn set_current_thread_priority(priority: ThreadPriority) -> NokhwaResult<()> {
+    os::set_priority(priority)
+        .map_err(|why| NokhwaError::GeneralError(format!("failed to set capture thread priority: {why}")))
+}
+
+/// Pins the calling thread to the CPUs set in `cpu_mask`, where bit `n` is CPU `n`.
+/// # Errors
+/// Errors if the mask holds no CPU this process may run on, or on platforms other than Linux and Windows.
+pub fn set_current_thread_affinity(cpu_mask: u64) -> NokhwaResult<()> {
+    os::set_affinity(cpu_mask)
+        .map_err(|why| NokhwaError::GeneralError(format!("failed to set capture thread affinity: {why}")))
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use super::ThreadPriority;
+    use std::io;
+
+    pub fn set_priority(priority: ThreadPriority) -> io::Result<()> {
+        // SAFETY: these calls only read the parameters passed in and act on the calling thread.
+        unsafe {
+            let (policy, sched_priority) = match priority {
+                ThreadPriority::RealTime => (libc::SCHED_FIFO, libc::sched_get_priority_min(libc::SCHED_FIFO)),
+                _ => (libc::SCHED_OTHER, 0),
+            };
+            let param = libc::sched_param { sched_priority };
+            let result = libc::pthread_setschedparam(libc::pthread_self(), policy, &raw const param);
+            if result != 0 {
+                return Err(io::Error::from_raw_os_error(result));
+            }
+
+            // Under SCHED_OTHER, Linux applies the nice value per thread.
+            let nice = match priority {
+                ThreadPriority::Low => 10,
+                ThreadPriority::Normal | ThreadPriority::RealTime => 0,
+                ThreadPriority::High => -10,
+            };
+            if libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, nice) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_affinity(cpu_mask: u64) -> io::Result<()> {
+        // SAFETY: `set` is a plain bitset that we fully initialize before passing it in.
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_ZERO(&mut set);
+            for cpu in (0..u64::BITS as usize).filter(|cpu| cpu_mask & (1 << cpu) != 0) {
+                libc::CPU_SET(cpu, &mut set);
+            }
+
+            if libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &raw const set) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod os {
+    use super::ThreadPriority;
+    use std::io;
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadAffinityMask, SetThreadPriority, THREAD_PRIORITY_HIGHEST, THREAD_PRIORITY_LOWEST,
+        THREAD_PRIORITY_NORMAL, THREAD_PRIORITY_TIME_CRITICAL,
+    };
+
+    pub fn set_priority(priority: ThreadPriority) -> io::Result<()> {
+        let priority = match priority {
+            ThreadPriority::Low => THREAD_PRIORITY_LOWEST,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::High => THREAD_PRIORITY_HIGHEST,
+            ThreadPriority::RealTime => THREAD_PRIORITY_TIME_CRITICAL,
+        };
+
+        // SAFETY: GetCurrentThread returns a pseudo handle that is always valid for the calling thread.
+        if unsafe { SetThreadPriority(GetCurrentThread(), priority) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn set_affinity(cpu_mask: u64) -> io::Result<()> {
+        // SAFETY: as above.
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), cpu_mask as usize) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod os {
+    use super::ThreadPriority;
+    use std::io;
+
+    pub fn set_priority(_priority: ThreadPriority) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform"))
+    }
+
+    pub fn set_affinity(_cpu_mask: u64) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform"))
+    }
+}
@@ -0,0 +1,1120 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Raw pixel conversion functions used by the decoders in [`pixel_format`](crate::pixel_format).
+//!
+//! Functions that write into a caller provided destination return [`NokhwaError::BufferTooSmall`] with the
+//! required length if it is too short.
+
+use crate::{
+    error::NokhwaError,
+    frame_format::{frame_format_layout, ColorRange, FormatLayout, FrameFormat},
+    types::Resolution,
+};
+#[cfg(feature = "mjpeg")]
+use crate::jpeg::is_valid_mjpeg;
+
+/// Converts a single limited range YCbCr 4:4:4 pixel into RGB using the BT.601 coefficients.
+/// See [`yuv444_to_rgb`] for full range pixels.
+#[must_use]
+#[inline]
+pub fn yuyv444_to_rgb(y: i32, u: i32, v: i32) -> [u8; 3] {
+    yuv444_to_rgb(y, u, v, ColorRange::Limited)
+}
+
+/// Converts a single YCbCr 4:4:4 pixel in the given [`ColorRange`] into RGB using the BT.601 coefficients.
+#[must_use]
+#[inline]
+pub fn yuv444_to_rgb(y: i32, u: i32, v: i32, range: ColorRange) -> [u8; 3] {
+    let du = u - 128;
+    let dv = v - 128;
+    // Coefficients are scaled by 256. Limited range also stretches 16-235 luma and 16-240 chroma to 0-255.
+    let (luma, [rv, gu, gv, bu]) = match range {
+        ColorRange::Limited => ((y - 16) * 298, [409, 100, 208, 516]),
+        ColorRange::Full => (y * 256, [359, 88, 183, 454]),
+    };
+    let red = ((luma + rv * dv + 128) >> 8).clamp(0, 255) as u8;
+    let green = ((luma - gu * du - gv * dv + 128) >> 8).clamp(0, 255) as u8;
+    let blue = ((luma + bu * du + 128) >> 8).clamp(0, 255) as u8;
+    [red, green, blue]
+}
+
+/// Returns the size in bytes of an RGB(A) image decoded from a YUYV 4:2:2 buffer of `data_len` bytes.
+#[must_use]
+#[inline]
+pub fn yuyv422_predicted_size(data_len: usize, rgba: bool) -> usize {
+    let pixel_size = if rgba { 4 } else { 3 };
+    // yuyv yields 2 3-byte pixels per yuyv chunk
+    (data_len / 4) * (2 * pixel_size)
+}
+
+/// Converts a YUYV 4:2:2 buffer into RGB888, or RGBA8888 if `rgba` is set.
+///
+/// The data is assumed to be in YUYV's [default range](FrameFormat::default_color_range), see
+/// [`yuyv422_to_rgb_with_range`] to override it.
+/// # Errors
+/// This may error when the data stream size is not divisible by 4.
+pub fn yuyv422_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
+    yuyv422_to_rgb_with_range(data, rgba, FrameFormat::Yuyv422.default_color_range())
+}
+
+/// Same as [`yuyv422_to_rgb`], for data in the given [`ColorRange`].
+/// # Errors
+/// This may error when the data stream size is not divisible by 4.
+pub fn yuyv422_to_rgb_with_range(data: &[u8], rgba: bool, range: ColorRange) -> Result<Vec<u8>, NokhwaError> {
+    let mut dest = vec![0; yuyv422_predicted_size(data.len(), rgba)];
+    buf_yuyv422_to_rgb_with_range(data, &mut dest, rgba, range)?;
+    Ok(dest)
+}
+
+/// Same as [`yuyv422_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the stream is invalid YUYV, or the destination buffer is not large enough, this will error.
+pub fn buf_yuyv422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
+    buf_yuyv422_to_rgb_with_range(data, dest, rgba, FrameFormat::Yuyv422.default_color_range())
+}
+
+/// Same as [`buf_yuyv422_to_rgb`], for data in the given [`ColorRange`].
+/// # Errors
+/// If the stream is invalid YUYV, or the destination buffer is not large enough, this will error.
+pub fn buf_yuyv422_to_rgb_with_range(
+    data: &[u8],
+    dest: &mut [u8],
+    rgba: bool,
+    range: ColorRange,
+) -> Result<(), NokhwaError> {
+    buf_packed_yuv422_to_rgb(FrameFormat::Yuyv422, data, dest, rgba, range)
+}
+
+/// Converts a UYVY 4:2:2 buffer (byte order U Y V Y) into RGB888, or RGBA8888 if `rgba` is set.
+/// # Errors
+/// This may error when the data stream size is not divisible by 4.
+pub fn uyvy422_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
+    packed_yuv422_to_rgb(FrameFormat::Uyvy422, data, rgba, FrameFormat::Uyvy422.default_color_range())
+}
+
+/// Same as [`uyvy422_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the stream is invalid UYVY, or the destination buffer is not large enough, this will error.
+pub fn buf_uyvy422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
+    buf_packed_yuv422_to_rgb(FrameFormat::Uyvy422, data, dest, rgba, FrameFormat::Uyvy422.default_color_range())
+}
+
+/// Converts a YVYU 4:2:2 buffer (byte order Y V Y U) into RGB888, or RGBA8888 if `rgba` is set.
+/// # Errors
+/// This may error when the data stream size is not divisible by 4.
+pub fn yvyu422_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
+    packed_yuv422_to_rgb(FrameFormat::Yvyu422, data, rgba, FrameFormat::Yvyu422.default_color_range())
+}
+
+/// Same as [`yvyu422_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the stream is invalid YVYU, or the destination buffer is not large enough, this will error.
+pub fn buf_yvyu422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
+    buf_packed_yuv422_to_rgb(FrameFormat::Yvyu422, data, dest, rgba, FrameFormat::Yvyu422.default_color_range())
+}
+
+/// Converts a buffer in any of the packed 4:2:2 formats ([`FrameFormat::Yuyv422`], [`FrameFormat::Uyvy422`] or
+/// [`FrameFormat::Yvyu422`]) in the given [`ColorRange`] into RGB888, or RGBA8888 if `rgba` is set. Their output is
+/// the same size, see [`yuyv422_predicted_size`].
+/// # Errors
+/// If `format` is not a packed 4:2:2 format, or the data stream size is not divisible by 4, this will error.
+pub fn packed_yuv422_to_rgb(
+    format: FrameFormat,
+    data: &[u8],
+    rgba: bool,
+    range: ColorRange,
+) -> Result<Vec<u8>, NokhwaError> {
+    let mut dest = vec![0; yuyv422_predicted_size(data.len(), rgba)];
+    buf_packed_yuv422_to_rgb(format, data, &mut dest, rgba, range)?;
+    Ok(dest)
+}
+
+/// Same as [`packed_yuv422_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If `format` is not a packed 4:2:2 format, the stream is invalid, or the destination buffer is not large enough,
+/// this will error.
+pub fn buf_packed_yuv422_to_rgb(
+    format: FrameFormat,
+    data: &[u8],
+    dest: &mut [u8],
+    rgba: bool,
+    range: ColorRange,
+) -> Result<(), NokhwaError> {
+    let destination = if rgba { "RGBA8888" } else { "RGB888" };
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: format,
+        destination: destination.to_string(),
+        error: why.to_string(),
+    };
+
+    // Offsets of the first luma, U, second luma and V samples in each 4 byte chunk.
+    let [y0, u, y1, v] = match format {
+        FrameFormat::Yuyv422 => [0, 1, 2, 3],
+        FrameFormat::Uyvy422 => [1, 0, 3, 2],
+        FrameFormat::Yvyu422 => [0, 3, 2, 1],
+        _ => return Err(error("Unsupported source format")),
+    };
+
+    if !data.len().is_multiple_of(4) {
+        return Err(error("Assertion failure, the YUV stream isn't 4:2:2! (wrong number of bytes)"));
+    }
+
+    let needed = yuyv422_predicted_size(data.len(), rgba);
+    if dest.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: dest.len() });
+    }
+
+    let pixel_size = if rgba { 4 } else { 3 };
+
+    for (chunk, out) in data
+        .chunks_exact(4)
+        .zip(dest.chunks_exact_mut(2 * pixel_size))
+    {
+        let u = i32::from(chunk[u]);
+        let v = i32::from(chunk[v]);
+
+        let (first, second) = out.split_at_mut(pixel_size);
+        first[..3].copy_from_slice(&yuv444_to_rgb(i32::from(chunk[y0]), u, v, range));
+        second[..3].copy_from_slice(&yuv444_to_rgb(i32::from(chunk[y1]), u, v, range));
+        if rgba {
+            first[3] = 255;
+            second[3] = 255;
+        }
+    }
+
+    Ok(())
+}
+
+// The layouts of the formats sized here, from the table in `frame_format`.
+const YUYV_LAYOUT: FormatLayout = frame_format_layout(FrameFormat::Yuyv422).unwrap();
+const NV12_LAYOUT: FormatLayout = frame_format_layout(FrameFormat::Nv12).unwrap();
+const I420_LAYOUT: FormatLayout = frame_format_layout(FrameFormat::I420).unwrap();
+const RGB_LAYOUT: FormatLayout = frame_format_layout(FrameFormat::Rgb888).unwrap();
+const RGBA_LAYOUT: FormatLayout = frame_format_layout(FrameFormat::RgbA8888).unwrap();
+
+// The layout of the RGB888 or RGBA8888 output of the `rgba` flag's conversions.
+const fn rgb_layout(rgba: bool) -> FormatLayout {
+    if rgba {
+        RGBA_LAYOUT
+    } else {
+        RGB_LAYOUT
+    }
+}
+
+/// Returns the size in bytes of an NV12 buffer of the given [`Resolution`].
+#[must_use]
+#[inline]
+pub fn nv12_predicted_size(resolution: Resolution) -> usize {
+    NV12_LAYOUT.byte_size(resolution)
+}
+
+/// Converts an NV12 buffer into RGB888, or RGBA8888 if `rgba` is set.
+///
+/// The data is assumed to be in NV12's [default range](FrameFormat::default_color_range), see
+/// [`nv12_to_rgb_with_range`] to override it.
+/// # Errors
+/// If the resolution is not even, or the stream is too short, this will error.
+pub fn nv12_to_rgb(
+    resolution: Resolution,
+    data: &[u8],
+    rgba: bool,
+) -> Result<Vec<u8>, NokhwaError> {
+    nv12_to_rgb_with_range(resolution, data, rgba, FrameFormat::Nv12.default_color_range())
+}
+
+/// Same as [`nv12_to_rgb`], for data in the given [`ColorRange`].
+/// # Errors
+/// If the resolution is not even, or the stream is too short, this will error.
+pub fn nv12_to_rgb_with_range(
+    resolution: Resolution,
+    data: &[u8],
+    rgba: bool,
+    range: ColorRange,
+) -> Result<Vec<u8>, NokhwaError> {
+    let width = resolution.width() as usize;
+    let mut dest = vec![0; rgb_layout(rgba).byte_size(resolution)];
+    buf_nv12_to_rgb_strided_with_range(resolution, data, width, width, &mut dest, rgba, range)?;
+    Ok(dest)
+}
+
+/// Same as [`nv12_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the resolution is not even, the stream is too short, or the destination buffer is not large enough, this will error.
+pub fn buf_nv12_to_rgb(
+    resolution: Resolution,
+    data: &[u8],
+    out: &mut [u8],
+    rgba: bool,
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    buf_nv12_to_rgb_strided(resolution, data, width, width, out, rgba)
+}
+
+/// Returns the size in bytes of an NV12 buffer whose Y rows are `y_stride` bytes apart and whose UV rows are
+/// `uv_stride` bytes apart, with the UV plane starting right after the last Y row.
+#[must_use]
+#[inline]
+pub fn nv12_strided_predicted_size(resolution: Resolution, y_stride: usize, uv_stride: usize) -> usize {
+    let height = resolution.height() as usize;
+    y_stride * height + uv_stride * (height / 2)
+}
+
+/// Same as [`nv12_to_rgb`], for buffers whose rows are padded past the image width, such as the
+/// `CVPixelBuffer`s `AVFoundation` and the 2D buffers Media Foundation hand out, which align each row to
+/// 16, 32 or 64 bytes.
+///
+/// `y_stride` and `uv_stride` are the distances in bytes between the starts of two rows of each plane, and the
+/// UV plane is expected right after the last Y row (see [`nv12_strided_predicted_size`]).
+/// # Errors
+/// If the resolution is not even, a stride is narrower than the image, or the stream is too short, this will error.
+pub fn nv12_to_rgb_strided(
+    resolution: Resolution,
+    data: &[u8],
+    y_stride: usize,
+    uv_stride: usize,
+    rgba: bool,
+) -> Result<Vec<u8>, NokhwaError> {
+    let mut dest = vec![0; rgb_layout(rgba).byte_size(resolution)];
+    buf_nv12_to_rgb_strided(resolution, data, y_stride, uv_stride, &mut dest, rgba)?;
+    Ok(dest)
+}
+
+/// Same as [`nv12_to_rgb_strided`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the resolution is not even, a stride is narrower than the image, the stream is too short, or the destination
+/// buffer is not large enough, this will error.
+pub fn buf_nv12_to_rgb_strided(
+    resolution: Resolution,
+    data: &[u8],
+    y_stride: usize,
+    uv_stride: usize,
+    out: &mut [u8],
+    rgba: bool,
+) -> Result<(), NokhwaError> {
+    buf_nv12_to_rgb_strided_with_range(
+        resolution,
+        data,
+        y_stride,
+        uv_stride,
+        out,
+        rgba,
+        FrameFormat::Nv12.default_color_range(),
+    )
+}
+
+/// Same as [`buf_nv12_to_rgb_strided`], for data in the given [`ColorRange`].
+/// # Errors
+/// If the resolution is not even, a stride is narrower than the image, the stream is too short, or the destination
+/// buffer is not large enough, this will error.
+pub fn buf_nv12_to_rgb_strided_with_range(
+    resolution: Resolution,
+    data: &[u8],
+    y_stride: usize,
+    uv_stride: usize,
+    out: &mut [u8],
+    rgba: bool,
+    range: ColorRange,
+) -> Result<(), NokhwaError> {
+    let destination = if rgba { "RGBA8888" } else { "RGB888" };
+    let pixel_size = if rgba { 4 } else { 3 };
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: FrameFormat::Nv12,
+        destination: destination.to_string(),
+        error: why.to_string(),
+    };
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    if y_stride < width || uv_stride < width {
+        return Err(error("bad stride"));
+    }
+    if data.len() < nv12_strided_predicted_size(resolution, y_stride, uv_stride) {
+        return Err(error("bad input buffer size"));
+    }
+    let needed = rgb_layout(rgba).byte_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let (y_plane, uv_plane) = data.split_at(y_stride * height);
+
+    for row in 0..height {
+        let y_row = &y_plane[row * y_stride..][..width];
+        let uv_row = &uv_plane[(row / 2) * uv_stride..][..width];
+        let out_row = &mut out[row * width * pixel_size..][..width * pixel_size];
+
+        for (column, (y, pixel)) in y_row.iter().zip(out_row.chunks_exact_mut(pixel_size)).enumerate() {
+            let uv_index = (column / 2) * 2;
+            let u = i32::from(uv_row[uv_index]);
+            let v = i32::from(uv_row[uv_index + 1]);
+
+            pixel[..3].copy_from_slice(&yuv444_to_rgb(i32::from(*y), u, v, range));
+            if rgba {
+                pixel[3] = 255;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies an NV12 image whose planes are padded past the image width into a tightly packed NV12 buffer, so it can be
+/// handed on as a plain [`FrameFormat::Nv12`] frame.
+///
+/// The planes are passed separately because some sources (e.g. planar `CVPixelBuffer`s) do not keep the UV plane
+/// right after the Y plane.
+/// # Errors
+/// If the resolution is not even, a stride is narrower than the image, a plane is too short, or the destination
+/// buffer is smaller than [`nv12_predicted_size`], this will error.
+pub fn pack_nv12_strided(
+    resolution: Resolution,
+    y_plane: &[u8],
+    y_stride: usize,
+    uv_plane: &[u8],
+    uv_stride: usize,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: FrameFormat::Nv12,
+        destination: "NV12".to_string(),
+        error: why.to_string(),
+    };
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    if y_stride < width || uv_stride < width {
+        return Err(error("bad stride"));
+    }
+    if y_plane.len() < y_stride * height || uv_plane.len() < uv_stride * (height / 2) {
+        return Err(error("bad input buffer size"));
+    }
+    let needed = nv12_predicted_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let (y_out, uv_out) = out.split_at_mut(width * height);
+    for row in 0..height {
+        y_out[row * width..][..width].copy_from_slice(&y_plane[row * y_stride..][..width]);
+    }
+    for row in 0..height / 2 {
+        uv_out[row * width..][..width].copy_from_slice(&uv_plane[row * uv_stride..][..width]);
+    }
+
+    Ok(())
+}
+
+/// Returns the size in bytes of an I420 buffer of the given [`Resolution`].
+#[must_use]
+#[inline]
+pub fn i420_predicted_size(resolution: Resolution) -> usize {
+    I420_LAYOUT.byte_size(resolution)
+}
+
+/// Where each plane of a packed I420 buffer starts and how many bytes one of its rows takes, for handing plane
+/// pointers to encoders such as libvpx or rav1e.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PlaneLayout {
+    pub y_offset: usize,
+    pub y_stride: usize,
+    pub u_offset: usize,
+    pub u_stride: usize,
+    pub v_offset: usize,
+    pub v_stride: usize,
+}
+
+impl PlaneLayout {
+    /// The layout of a tightly packed I420 buffer of the given [`Resolution`], as produced by
+    /// [`FrameBuffer::to_i420`](crate::frame_buffer::FrameBuffer::to_i420).
+    #[must_use]
+    pub fn i420(resolution: Resolution) -> Self {
+        let y_size = I420_LAYOUT.first_plane_size(resolution);
+        let chroma = I420_LAYOUT.chroma_resolution(resolution);
+        let chroma_width = chroma.width() as usize;
+        let chroma_size = chroma_width * chroma.height() as usize;
+
+        PlaneLayout {
+            y_offset: 0,
+            y_stride: resolution.width() as usize,
+            u_offset: y_size,
+            u_stride: chroma_width,
+            v_offset: y_size + chroma_size,
+            v_stride: chroma_width,
+        }
+    }
+}
+
+/// Converts a YUYV 4:2:2 buffer straight into I420 (planar 4:2:0): the Y plane, then the U plane, then the V plane.
+///
+/// Chroma is averaged over each pair of rows.
+/// # Errors
+/// If the resolution is not even, `yuyv` is shorter than `width * height * 2` (e.g. a truncated USB transfer),
+/// or the destination buffer is not large enough, this will error.
+pub fn convert_yuyv_to_i420_direct(
+    yuyv: &[u8],
+    resolution: Resolution,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: FrameFormat::Yuyv422,
+        destination: "I420".to_string(),
+        error: why.to_string(),
+    };
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    if yuyv.len() < YUYV_LAYOUT.byte_size(resolution) {
+        return Err(error("bad input buffer size"));
+    }
+    let needed = i420_predicted_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    // One row of YUYV is a frame one pixel tall.
+    let stride = YUYV_LAYOUT.byte_size(Resolution::new(resolution.width(), 1));
+    let chroma = I420_LAYOUT.chroma_resolution(resolution);
+    let chroma_width = chroma.width() as usize;
+    let (y_plane, chroma_planes) = out.split_at_mut(I420_LAYOUT.first_plane_size(resolution));
+    let (u_plane, v_plane) = chroma_planes.split_at_mut(chroma_width * chroma.height() as usize);
+
+    for (index, luma) in y_plane.iter_mut().enumerate() {
+        *luma = yuyv[index * 2];
+    }
+
+    for chroma_row in 0..chroma.height() as usize {
+        let upper = &yuyv[chroma_row * 2 * stride..][..stride];
+        let lower = &yuyv[(chroma_row * 2 + 1) * stride..][..stride];
+        let u_row = &mut u_plane[chroma_row * chroma_width..][..chroma_width];
+        let v_row = &mut v_plane[chroma_row * chroma_width..][..chroma_width];
+
+        for (column, (upper, lower)) in upper.chunks_exact(4).zip(lower.chunks_exact(4)).enumerate() {
+            u_row[column] = upper[1].midpoint(lower[1]);
+            v_row[column] = upper[3].midpoint(lower[3]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts an NV12 buffer into I420 by splitting its interleaved UV plane into separate U and V planes.
+/// # Errors
+/// If the resolution is not even, `nv12` is shorter than [`nv12_predicted_size`], or the
+/// destination buffer is not large enough, this will error.
+pub fn convert_nv12_to_i420_direct(
+    nv12: &[u8],
+    resolution: Resolution,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    convert_nv12_to_i420_strided(nv12, resolution, width, width, out)
+}
+
+/// Same as [`convert_nv12_to_i420_direct`], for NV12 buffers with padded rows (see [`nv12_to_rgb_strided`]).
+/// # Errors
+/// If the resolution is not even, a stride is narrower than the image, `nv12` is shorter than
+/// [`nv12_strided_predicted_size`], or the destination buffer is not large enough, this will error.
+pub fn convert_nv12_to_i420_strided(
+    nv12: &[u8],
+    resolution: Resolution,
+    y_stride: usize,
+    uv_stride: usize,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: FrameFormat::Nv12,
+        destination: "I420".to_string(),
+        error: why.to_string(),
+    };
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    if y_stride < width || uv_stride < width {
+        return Err(error("bad stride"));
+    }
+    if nv12.len() < nv12_strided_predicted_size(resolution, y_stride, uv_stride) {
+        return Err(error("bad input buffer size"));
+    }
+    let needed = i420_predicted_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let luma_size = width * height;
+    let chroma_width = width / 2;
+    let (y_plane, chroma) = out.split_at_mut(luma_size);
+    let (u_plane, v_plane) = chroma.split_at_mut(luma_size / 4);
+    let (nv12_y, nv12_uv) = nv12.split_at(y_stride * height);
+
+    for row in 0..height {
+        y_plane[row * width..][..width].copy_from_slice(&nv12_y[row * y_stride..][..width]);
+    }
+    for row in 0..height / 2 {
+        let uv_row = &nv12_uv[row * uv_stride..][..width];
+        for column in 0..chroma_width {
+            u_plane[row * chroma_width + column] = uv_row[column * 2];
+            v_plane[row * chroma_width + column] = uv_row[column * 2 + 1];
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a packed 32-bit RGB buffer, [`FrameFormat::RgbA8888`] or [`FrameFormat::ARgb8888`] as given by
+/// `format`, straight into I420 in the limited range of BT.601 video, as most encoders expect. Alpha is dropped.
+///
+/// Chroma is averaged over each 2x2 block of pixels.
+/// # Errors
+/// If `format` is not one of those two, the resolution is not even, `data` is shorter than the format needs at
+/// `resolution`, or the destination buffer is not large enough, this will error.
+pub fn convert_packed_rgb_to_i420_direct(
+    format: FrameFormat,
+    data: &[u8],
+    resolution: Resolution,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src: format,
+        destination: "I420".to_string(),
+        error: why.to_string(),
+    };
+
+    // Where red starts in each 4 byte pixel.
+    let red_offset = match format {
+        FrameFormat::RgbA8888 => 0,
+        FrameFormat::ARgb8888 => 1,
+        _ => return Err(error("Unsupported source format")),
+    };
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    let stride = RGBA_LAYOUT.byte_size(Resolution::new(resolution.width(), 1));
+    if data.len() < RGBA_LAYOUT.byte_size(resolution) {
+        return Err(error("bad input buffer size"));
+    }
+    let needed = i420_predicted_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let chroma = I420_LAYOUT.chroma_resolution(resolution);
+    let chroma_width = chroma.width() as usize;
+    let (y_plane, chroma_planes) = out.split_at_mut(I420_LAYOUT.first_plane_size(resolution));
+    let (u_plane, v_plane) = chroma_planes.split_at_mut(chroma_width * chroma.height() as usize);
+    let rgb = |pixel: &[u8]| {
+        let [red, green, blue] = [0, 1, 2].map(|channel| i32::from(pixel[red_offset + channel]));
+        (red, green, blue)
+    };
+
+    for (luma, pixel) in y_plane.iter_mut().zip(data.chunks_exact(4)) {
+        let (red, green, blue) = rgb(pixel);
+        *luma = (((66 * red + 129 * green + 25 * blue + 128) >> 8) + 16) as u8;
+    }
+
+    for chroma_row in 0..chroma.height() as usize {
+        let upper = &data[chroma_row * 2 * stride..][..stride];
+        let lower = &data[(chroma_row * 2 + 1) * stride..][..stride];
+
+        for (column, (upper, lower)) in upper.chunks_exact(8).zip(lower.chunks_exact(8)).enumerate() {
+            let (mut red, mut green, mut blue) = (0, 0, 0);
+            for pixel in [&upper[..4], &upper[4..], &lower[..4], &lower[4..]] {
+                let (r, g, b) = rgb(pixel);
+                red += r;
+                green += g;
+                blue += b;
+            }
+            // Sums of 4 pixels, so the usual `>> 8` becomes `>> 10`.
+            let index = chroma_row * chroma_width + column;
+            u_plane[index] = (((-38 * red - 74 * green + 112 * blue + 512) >> 10) + 128) as u8;
+            v_plane[index] = (((112 * red - 94 * green - 18 * blue + 512) >> 10) + 128) as u8;
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts an NV12 buffer into NV21, as Android's encoders expect, by copying the Y plane and swapping each
+/// interleaved U and V byte.
+/// # Errors
+/// If the resolution is not even, `nv12` is shorter than [`nv12_predicted_size`], or the destination buffer is not
+/// large enough, this will error.
+pub fn nv12_to_nv21(nv12: &[u8], resolution: Resolution, out: &mut [u8]) -> Result<(), NokhwaError> {
+    swap_semi_planar_chroma(FrameFormat::Nv12, FrameFormat::Nv21, nv12, resolution, out)
+}
+
+/// Converts an NV21 buffer into NV12. This is the inverse of [`nv12_to_nv21`].
+/// # Errors
+/// If the resolution is not even, `nv21` is shorter than [`nv12_predicted_size`], or the destination buffer is not
+/// large enough, this will error.
+pub fn nv21_to_nv12(nv21: &[u8], resolution: Resolution, out: &mut [u8]) -> Result<(), NokhwaError> {
+    swap_semi_planar_chroma(FrameFormat::Nv21, FrameFormat::Nv12, nv21, resolution, out)
+}
+
+/// Swaps the U and V bytes of an NV12 or NV21 buffer in place, turning one into the other without a copy.
+/// # Errors
+/// If the resolution is not even, or `data` is shorter than [`nv12_predicted_size`], this will error.
+pub fn swap_nv12_nv21_in_place(data: &mut [u8], resolution: Resolution) -> Result<(), NokhwaError> {
+    let luma_size = check_semi_planar(FrameFormat::Nv12, FrameFormat::Nv21, data, resolution)?;
+    for uv in data[luma_size..nv12_predicted_size(resolution)].chunks_exact_mut(2) {
+        uv.swap(0, 1);
+    }
+    Ok(())
+}
+
+// Checks that `data` holds a whole NV12 or NV21 frame, and returns the size of its Y plane.
+fn check_semi_planar(
+    src: FrameFormat,
+    destination: FrameFormat,
+    data: &[u8],
+    resolution: Resolution,
+) -> Result<usize, NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let error = |why: &str| NokhwaError::ProcessFrameError {
+        src,
+        destination: destination.to_string(),
+        error: why.to_string(),
+    };
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(error("bad resolution"));
+    }
+    if data.len() < nv12_predicted_size(resolution) {
+        return Err(error("bad input buffer size"));
+    }
+    Ok(width * height)
+}
+
+// Copies an NV12 or NV21 frame into `out` with the order of each chroma pair swapped.
+fn swap_semi_planar_chroma(
+    src: FrameFormat,
+    destination: FrameFormat,
+    data: &[u8],
+    resolution: Resolution,
+    out: &mut [u8],
+) -> Result<(), NokhwaError> {
+    let luma_size = check_semi_planar(src, destination, data, resolution)?;
+    let needed = nv12_predicted_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let (y_out, uv_out) = out[..needed].split_at_mut(luma_size);
+    y_out.copy_from_slice(&data[..luma_size]);
+    for (swapped, uv) in uv_out.chunks_exact_mut(2).zip(data[luma_size..needed].chunks_exact(2)) {
+        swapped[0] = uv[1];
+        swapped[1] = uv[0];
+    }
+    Ok(())
+}
+
+// Writes each pixel's channels into three consecutive planes of `plane_size` bytes.
+fn write_rgb_planes(pixels: impl Iterator<Item = [u8; 3]>, out: &mut [u8], plane_size: usize) {
+    let (red, rest) = out.split_at_mut(plane_size);
+    let (green, blue) = rest.split_at_mut(plane_size);
+
+    for (index, [r, g, b]) in pixels.enumerate() {
+        red[index] = r;
+        green[index] = g;
+        blue[index] = b;
+    }
+}
+
+/// Splits an interleaved RGB888 buffer into planar RGB (all red, then all green, then all blue).
+/// # Errors
+/// If `rgb` is not a whole number of pixels, or the destination buffer is not large enough, this will error.
+pub fn buf_rgb_to_planar_rgb(rgb: &[u8], out: &mut [u8]) -> Result<(), NokhwaError> {
+    if !rgb.len().is_multiple_of(3) {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Rgb888,
+            destination: "Planar RGB".to_string(),
+            error: format!("Bad buffer length: expected a multiple of 3 bytes, got {}", rgb.len()),
+        });
+    }
+    if out.len() < rgb.len() {
+        return Err(NokhwaError::BufferTooSmall { needed: rgb.len(), got: out.len() });
+    }
+
+    write_rgb_planes(
+        rgb.chunks_exact(3).map(|px| [px[0], px[1], px[2]]),
+        out,
+        rgb.len() / 3,
+    );
+    Ok(())
+}
+
+/// Interleaves a planar RGB buffer back into RGB888. This is the inverse of [`buf_rgb_to_planar_rgb`].
+/// # Errors
+/// If `planar` is not a whole number of pixels, or the destination buffer is not large enough, this will error.
+pub fn buf_planar_rgb_to_rgb(planar: &[u8], out: &mut [u8]) -> Result<(), NokhwaError> {
+    if !planar.len().is_multiple_of(3) {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Rgb888,
+            destination: "RGB888".to_string(),
+            error: format!("Bad buffer length: expected a multiple of 3 bytes, got {}", planar.len()),
+        });
+    }
+    if out.len() < planar.len() {
+        return Err(NokhwaError::BufferTooSmall { needed: planar.len(), got: out.len() });
+    }
+
+    let plane_size = planar.len() / 3;
+    let (red, rest) = planar.split_at(plane_size);
+    let (green, blue) = rest.split_at(plane_size);
+
+    for (index, px) in out.chunks_exact_mut(3).take(plane_size).enumerate() {
+        px[0] = red[index];
+        px[1] = green[index];
+        px[2] = blue[index];
+    }
+    Ok(())
+}
+
+/// Converts a YUYV 4:2:2 buffer in the given [`ColorRange`] straight into planar RGB, without an interleaved
+/// intermediate.
+/// # Errors
+/// If the stream is invalid YUYV, or the destination buffer is not large enough, this will error.
+pub fn buf_yuyv422_to_planar_rgb(data: &[u8], out: &mut [u8], range: ColorRange) -> Result<(), NokhwaError> {
+    if !data.len().is_multiple_of(4) {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Yuyv422,
+            destination: "Planar RGB".to_string(),
+            error: format!("Bad buffer length: expected a multiple of 4 bytes, got {}", data.len()),
+        });
+    }
+    let needed = yuyv422_predicted_size(data.len(), false);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let pixels = data.chunks_exact(4).flat_map(|yuyv| {
+        let u = i32::from(yuyv[1]);
+        let v = i32::from(yuyv[3]);
+        [
+            yuv444_to_rgb(i32::from(yuyv[0]), u, v, range),
+            yuv444_to_rgb(i32::from(yuyv[2]), u, v, range),
+        ]
+    });
+    write_rgb_planes(pixels, out, data.len() / 2);
+    Ok(())
+}
+
+/// Converts an NV12 buffer in the given [`ColorRange`] straight into planar RGB, without an interleaved intermediate.
+/// # Errors
+/// If the resolution is not even, the stream is too short, or the destination buffer is not large enough, this will error.
+pub fn buf_nv12_to_planar_rgb(
+    resolution: Resolution,
+    data: &[u8],
+    out: &mut [u8],
+    range: ColorRange,
+) -> Result<(), NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+
+    if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Nv12,
+            destination: "Planar RGB".to_string(),
+            error: format!("Bad resolution: expected an even width and height, got {resolution}"),
+        });
+    }
+    let expected = nv12_predicted_size(resolution);
+    if data.len() < expected {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Nv12,
+            destination: "Planar RGB".to_string(),
+            error: format!("Bad buffer length: expected {expected} bytes, got {}", data.len()),
+        });
+    }
+    let needed = RGB_LAYOUT.byte_size(resolution);
+    if out.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: out.len() });
+    }
+
+    let (y_plane, uv_plane) = data.split_at(NV12_LAYOUT.first_plane_size(resolution));
+    let pixels = (0..height).flat_map(|row| {
+        (0..width).map(move |column| {
+            let uv_index = (row / 2) * width + (column / 2) * 2;
+            yuv444_to_rgb(
+                i32::from(y_plane[row * width + column]),
+                i32::from(uv_plane[uv_index]),
+                i32::from(uv_plane[uv_index + 1]),
+                range,
+            )
+        })
+    });
+    write_rgb_planes(pixels, out, width * height);
+    Ok(())
+}
+
+/// Stretches RGB(A) decoded as if it were full range, but whose source was limited range, back to the full 0-255
+/// range, e.g. for the rare camera that sends limited range MJPEG. Alpha values of 255 are left as-is.
+///
+/// This only fixes the levels, so it is an approximation of decoding the source with the right range.
+pub fn buf_expand_limited_range(rgb: &mut [u8]) {
+    for value in rgb {
+        *value = ((i32::from(*value) - 16) * 255 / 219).clamp(0, 255) as u8;
+    }
+}
+
+/// Decodes a MJPEG buffer into RGB888, or RGBA8888 if `rgba` is set.
+/// # Errors
+/// If the stream is not valid JPEG, this will error. Frames rejected by [`is_valid_mjpeg`] return
+/// [`NokhwaError::CorruptFrameError`], so callers can drop them and keep streaming.
+#[cfg(feature = "mjpeg")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
+pub fn mjpeg_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
+    let destination = if rgba { "RGBA8888" } else { "RGB888" };
+    let map_error = |why: std::io::Error| NokhwaError::ProcessFrameError {
+        src: FrameFormat::MJpeg,
+        destination: destination.to_string(),
+        error: why.to_string(),
+    };
+
+    if !is_valid_mjpeg(data) {
+        return Err(NokhwaError::CorruptFrameError(FrameFormat::MJpeg));
+    }
+
+    let decompress = mozjpeg::Decompress::new_mem(data).map_err(map_error)?;
+    let mut started = if rgba {
+        decompress.rgba()
+    } else {
+        decompress.rgb()
+    }
+    .map_err(map_error)?;
+
+    let decoded = started.read_scanlines::<u8>().map_err(map_error)?;
+    started.finish().map_err(map_error)?;
+    Ok(decoded)
+}
+
+/// Same as [`mjpeg_to_rgb`] but with a destination buffer instead of a return `Vec<u8>`
+/// # Errors
+/// If the stream is not valid JPEG, or the destination buffer is not large enough, this will error.
+/// Truncated frames return [`NokhwaError::CorruptFrameError`], see [`mjpeg_to_rgb`].
+#[cfg(feature = "mjpeg")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
+pub fn buf_mjpeg_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
+    let destination = if rgba { "RGBA8888" } else { "RGB888" };
+    let map_error = |why: std::io::Error| NokhwaError::ProcessFrameError {
+        src: FrameFormat::MJpeg,
+        destination: destination.to_string(),
+        error: why.to_string(),
+    };
+
+    if !is_valid_mjpeg(data) {
+        return Err(NokhwaError::CorruptFrameError(FrameFormat::MJpeg));
+    }
+
+    let decompress = mozjpeg::Decompress::new_mem(data).map_err(map_error)?;
+    let mut started = if rgba {
+        decompress.rgba()
+    } else {
+        decompress.rgb()
+    }
+    .map_err(map_error)?;
+
+    let needed = started.min_flat_buffer_size();
+    if dest.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: dest.len() });
+    }
+
+    started.read_scanlines_into::<u8>(dest).map_err(map_error)?;
+    started.finish().map_err(map_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_buffer::FrameBuffer;
+
+    #[test]
+    fn packed_422_orders_decode_like_yuyv() {
+        // Two YUYV chunks, and the same samples in the other byte orders.
+        let yuyv = [82, 90, 145, 240, 41, 110, 210, 34];
+        let uyvy = [90, 82, 240, 145, 110, 41, 34, 210];
+        let yvyu = [82, 240, 145, 90, 41, 34, 210, 110];
+
+        let expected = yuyv422_to_rgb(&yuyv, false).unwrap();
+        assert_eq!(expected.len(), 4 * 3);
+        assert_eq!(uyvy422_to_rgb(&uyvy, false).unwrap(), expected);
+        assert_eq!(yvyu422_to_rgb(&yvyu, false).unwrap(), expected);
+
+        let rgba =
+            packed_yuv422_to_rgb(FrameFormat::Yvyu422, &yvyu, true, ColorRange::Limited).unwrap();
+        assert_eq!(rgba.len(), 4 * 4);
+        assert!(rgba
+            .chunks_exact(4)
+            .zip(expected.chunks_exact(3))
+            .all(|(rgba, rgb)| rgba[..3] == *rgb && rgba[3] == 255));
+    }
+
+    #[test]
+    fn packed_422_rejects_partial_chunks_and_other_formats() {
+        assert!(yuyv422_to_rgb(&[0; 6], false).is_err());
+        assert!(packed_yuv422_to_rgb(FrameFormat::Nv12, &[0; 8], false, ColorRange::Limited).is_err());
+    }
+
+    #[test]
+    fn padded_nv12_rows_decode_like_packed_nv12() {
+        let resolution = Resolution::new(4, 2);
+        let packed: Vec<u8> = (0..12).map(|i| i * 20).collect();
+        // Pad every row out to width + 16 with bytes that would show up if they were read.
+        let stride = 4 + 16;
+        let mut padded = vec![0xAA; nv12_strided_predicted_size(resolution, stride, stride)];
+        for (row, chunk) in packed.chunks_exact(4).enumerate() {
+            padded[row * stride..][..4].copy_from_slice(chunk);
+        }
+
+        let expected = nv12_to_rgb(resolution, &packed, false).unwrap();
+        assert_eq!(nv12_to_rgb_strided(resolution, &padded, stride, stride, false).unwrap(), expected);
+
+        let mut repacked = vec![0; nv12_predicted_size(resolution)];
+        let (y_plane, uv_plane) = padded.split_at(stride * 2);
+        pack_nv12_strided(resolution, y_plane, stride, uv_plane, stride, &mut repacked).unwrap();
+        assert_eq!(repacked, packed);
+    }
+
+    #[test]
+    fn nv12_to_nv21_and_back_is_identity() {
+        let resolution = Resolution::new(4, 2);
+        let nv12 = (0..nv12_predicted_size(resolution) as u8).collect::<Vec<_>>();
+
+        let mut nv21 = vec![0; nv12.len()];
+        nv12_to_nv21(&nv12, resolution, &mut nv21).unwrap();
+        assert_eq!(nv21[..8], nv12[..8]);
+        assert_eq!(nv21[8..], [9, 8, 11, 10]);
+
+        let mut round_trip = vec![0; nv12.len()];
+        nv21_to_nv12(&nv21, resolution, &mut round_trip).unwrap();
+        assert_eq!(round_trip, nv12);
+
+        let mut in_place = nv12.clone();
+        swap_nv12_nv21_in_place(&mut in_place, resolution).unwrap();
+        assert_eq!(in_place, nv21);
+
+        let frame = FrameBuffer::new(resolution, &nv12, FrameFormat::Nv12);
+        let converted = frame.to_nv21().unwrap();
+        assert_eq!(converted.source_frame_format(), FrameFormat::Nv21);
+        assert_eq!(converted.to_nv12().unwrap().buffer(), &nv12[..]);
+    }
+
+    #[test]
+    fn yuyv_to_i420_averages_chroma_over_row_pairs() {
+        let resolution = Resolution::new(4, 2);
+        let yuyv = [
+            1, 100, 2, 200, 3, 110, 4, 210, //
+            5, 102, 6, 202, 7, 114, 8, 214,
+        ];
+        let mut i420 = vec![0; i420_predicted_size(resolution)];
+
+        convert_yuyv_to_i420_direct(&yuyv, resolution, &mut i420).unwrap();
+        assert_eq!(i420, [1, 2, 3, 4, 5, 6, 7, 8, 101, 112, 201, 212]);
+    }
+
+    #[test]
+    fn truncated_yuyv_to_i420_is_an_error() {
+        let resolution = Resolution::new(4, 2);
+        let mut i420 = vec![0; i420_predicted_size(resolution)];
+
+        assert!(matches!(
+            convert_yuyv_to_i420_direct(&[0; 12], resolution, &mut i420),
+            Err(NokhwaError::ProcessFrameError { src: FrameFormat::Yuyv422, .. })
+        ));
+        assert!(matches!(
+            convert_yuyv_to_i420_direct(&[0; 16], resolution, &mut i420[..8]),
+            Err(NokhwaError::BufferTooSmall { needed: 12, got: 8 })
+        ));
+    }
+
+    #[test]
+    fn length_errors_report_expected_and_actual_sizes() {
+        let resolution = Resolution::new(2, 2);
+        let mut short = [0; 11];
+        assert!(matches!(
+            buf_rgb_to_planar_rgb(&[0; 12], &mut short),
+            Err(NokhwaError::BufferTooSmall { needed: 12, got: 11 })
+        ));
+        assert!(matches!(
+            buf_yuyv422_to_planar_rgb(&[0; 8], &mut short, ColorRange::Limited),
+            Err(NokhwaError::BufferTooSmall { needed: 12, got: 11 })
+        ));
+        assert!(matches!(
+            buf_nv12_to_planar_rgb(resolution, &[0; 6], &mut short, ColorRange::Limited),
+            Err(NokhwaError::BufferTooSmall { needed: 12, got: 11 })
+        ));
+
+        let mut out = [0; 12];
+        let message = |result: Result<(), NokhwaError>| match result {
+            Err(NokhwaError::ProcessFrameError { error, .. }) => error,
+            other => panic!("expected a ProcessFrameError, got {other:?}"),
+        };
+        assert_eq!(
+            message(buf_rgb_to_planar_rgb(&[0; 10], &mut out)),
+            "Bad buffer length: expected a multiple of 3 bytes, got 10"
+        );
+        assert_eq!(
+            message(buf_planar_rgb_to_rgb(&[0; 10], &mut out)),
+            "Bad buffer length: expected a multiple of 3 bytes, got 10"
+        );
+        assert_eq!(
+            message(buf_yuyv422_to_planar_rgb(&[0; 6], &mut out, ColorRange::Limited)),
+            "Bad buffer length: expected a multiple of 4 bytes, got 6"
+        );
+        assert_eq!(
+            message(buf_nv12_to_planar_rgb(resolution, &[0; 5], &mut out, ColorRange::Limited)),
+            "Bad buffer length: expected 6 bytes, got 5"
+        );
+    }
+
+    #[cfg(feature = "mjpeg")]
+    #[test]
+    fn truncated_mjpeg_frames_are_corrupt() {
+        let jpeg = crate::jpeg::tests::encode_rgb(16, 16, &[128; 16 * 16 * 3]);
+        // Zero padding after EOI is not truncation.
+        let mut padded = jpeg.clone();
+        padded.resize(jpeg.len() + 32, 0);
+        assert_eq!(mjpeg_to_rgb(&padded, false).unwrap().len(), 16 * 16 * 3);
+
+        let truncated = &jpeg[..jpeg.len() / 2];
+        assert!(!is_valid_mjpeg(truncated));
+        assert!(matches!(
+            mjpeg_to_rgb(truncated, false),
+            Err(NokhwaError::CorruptFrameError(FrameFormat::MJpeg))
+        ));
+        let mut dest = vec![0; 16 * 16 * 4];
+        assert!(matches!(
+            buf_mjpeg_to_rgb(truncated, &mut dest, true),
+            Err(NokhwaError::CorruptFrameError(FrameFormat::MJpeg))
+        ));
+    }
+}
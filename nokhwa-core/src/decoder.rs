@@ -18,6 +18,9 @@ pub trait Decoder {
         if !Self::ALLOWED_FORMATS.contains(&buffer.source_frame_format()) {
             return ControlFlow::Break(NokhwaError::ConversionError("unsupported".to_string()));
         }
+        if let Err(why) = buffer.validate_length() {
+            return ControlFlow::Break(why);
+        }
 
         ControlFlow::Continue(())
     }
@@ -30,25 +33,53 @@ pub trait Decoder {
 
     /// Decode to user-provided Buffer
     ///
-    /// Incase that the buffer is not large enough this should error.
+    /// Incase that the buffer is not large enough this should return [`NokhwaError::BufferTooSmall`] with the
+    /// number of subpixels it needs, so the caller can allocate once and retry.
     fn decode_buffer(
         &mut self,
         buffer: &FrameBuffer,
         output: &mut [<<Self as Decoder>::OutputPixels as Pixel>::Subpixel],
     ) -> Result<(), NokhwaError>;
 
+    /// The number of channels in each output pixel, e.g. 3 for [`Rgb<u8>`](image::Rgb).
+    #[must_use]
+    fn channels() -> usize {
+        <<Self as Decoder>::OutputPixels as Pixel>::CHANNEL_COUNT as usize
+    }
+
+    /// The size in bytes of each channel of an output pixel, e.g. 1 for [`Rgb<u8>`](image::Rgb).
+    #[must_use]
+    fn subpixel_size() -> usize {
+        size_of::<<<Self as Decoder>::OutputPixels as Pixel>::Subpixel>()
+    }
+
     /// Decoder Predicted Size
     fn predicted_size_of_frame(buffer: &FrameBuffer) -> Option<usize> {
         if !Self::ALLOWED_FORMATS.contains(&buffer.source_frame_format()) {
             return None;
         }
         let res = buffer.resolution();
-        Some(
-            res.x() as usize
-                * res.y() as usize
-                * size_of::<<<Self as Decoder>::OutputPixels as Pixel>::Subpixel>()
-                * <<Self as Decoder>::OutputPixels as Pixel>::CHANNEL_COUNT as usize,
-        )
+        Some(res.x() as usize * res.y() as usize * Self::subpixel_size() * Self::channels())
+    }
+}
+
+/// Reports which compressed [`FrameFormat`]s this build can decode, which depends on the enabled features:
+/// [`FrameFormat::MJpeg`] needs `mjpeg`. Uncompressed formats are always decodable.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct DecodeCapabilities;
+
+impl DecodeCapabilities {
+    /// The compressed formats this build can decode.
+    #[cfg(feature = "mjpeg")]
+    pub const COMPRESSED: &'static [FrameFormat] = &[FrameFormat::MJpeg];
+    /// The compressed formats this build can decode.
+    #[cfg(not(feature = "mjpeg"))]
+    pub const COMPRESSED: &'static [FrameFormat] = &[];
+
+    /// Whether this build can decode `frame_format`.
+    #[must_use]
+    pub fn can_decode(frame_format: FrameFormat) -> bool {
+        !FrameFormat::COMPRESSED.contains(&frame_format) || Self::COMPRESSED.contains(&frame_format)
     }
 }
 
@@ -15,8 +15,10 @@
  */
 use crate::{frame_format::FrameFormat, types::ApiBackend};
 use std::fmt::{Debug};
+use std::sync::Arc;
 use thiserror::Error;
 use crate::platform::Backends;
+use crate::types::CameraIndex;
 
 pub type NokhwaResult<T> = Result<T, NokhwaError>;
 
@@ -54,6 +56,12 @@ pub enum NokhwaError {
         destination: String,
         error: String,
     },
+    #[error("Frame {0} is truncated or corrupt and should be dropped")]
+    CorruptFrameError(FrameFormat),
+    #[error("Output buffer too small: needed {needed} bytes, got {got}")]
+    BufferTooSmall { needed: usize, got: usize },
+    #[error("Stream format changed, expected {expected} but got {got}")]
+    FormatChanged { expected: String, got: String },
     #[error("Could not stop stream: {0}")]
     StreamShutdownError(String),
     #[error("This operation is not supported by backend {0}.")]
@@ -64,4 +72,104 @@ pub enum NokhwaError {
     ConversionError(String),
     #[error("Permission denied by user.")]
     PermissionDenied,
+    #[error("Camera index {0:?} is an identifier (e.g. a device path or browser device ID), not a number")]
+    NonNumericIndex(String),
+    #[error("{message}: {source}")]
+    OsError {
+        message: String,
+        source: Arc<std::io::Error>,
+    },
+    #[error("{backend:?} camera {index}: {source}")]
+    WithContext {
+        backend: Backends,
+        index: CameraIndex,
+        source: Box<NokhwaError>,
+    },
+}
+
+impl NokhwaError {
+    /// The error a backend should return when it is compiled in but cannot run on this system,
+    /// e.g. a dynamically loaded system library or OS service it needs is missing.
+    #[must_use]
+    pub fn backend_unavailable(backend: Backends, detail: impl std::fmt::Display) -> Self {
+        NokhwaError::GeneralError(format!("backend {backend:?} unavailable: {detail}"))
+    }
+
+    /// Wraps an error from the OS, keeping it as this error's [`source`](std::error::Error::source).
+    #[must_use]
+    pub fn os_error(message: impl Into<String>, source: std::io::Error) -> Self {
+        NokhwaError::OsError {
+            message: message.into(),
+            source: Arc::new(source),
+        }
+    }
+
+    /// Tags this error with the backend and camera it came from, so applications managing several cameras can tell
+    /// where it happened. Backends should call this on errors leaving their public API.
+    ///
+    /// An error that already has context keeps it.
+    #[must_use]
+    pub fn with_context(self, backend: Backends, index: CameraIndex) -> Self {
+        if let NokhwaError::WithContext { .. } = self {
+            return self;
+        }
+
+        NokhwaError::WithContext {
+            backend,
+            index,
+            source: Box::new(self),
+        }
+    }
+
+    /// The error underneath any [`with_context`](NokhwaError::with_context), for matching on what went wrong.
+    #[must_use]
+    pub fn without_context(&self) -> &NokhwaError {
+        match self {
+            NokhwaError::WithContext { source, .. } => source,
+            other => other,
+        }
+    }
+
+    /// The error for a device that lists no formats at all, which some virtual cameras and drivers do.
+    #[must_use]
+    pub fn no_formats_reported() -> Self {
+        NokhwaError::StructureError {
+            structure: "formats".to_string(),
+            error: "device reported no supported formats".to_string(),
+        }
+    }
+
+    /// The error a camera opened with [`OpenOptions::read_only`](crate::platform::OpenOptions::read_only)
+    /// should return when asked to open a stream.
+    #[must_use]
+    pub fn read_only_stream() -> Self {
+        NokhwaError::OpenStreamError(
+            "camera was opened read-only, reopen it without `OpenOptions::read_only` to stream".to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+    use std::io;
+
+    #[test]
+    fn context_names_the_backend_and_camera_and_keeps_the_source_chain() {
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied");
+        let error = NokhwaError::os_error("failed to open /dev/video2", denied)
+            .with_context(Backends::Video4Linux2, CameraIndex::Index(2));
+
+        assert_eq!(error.to_string(), "Video4Linux2 camera 2: failed to open /dev/video2: Permission denied");
+        let source = error.source().unwrap();
+        assert_eq!(source.to_string(), "failed to open /dev/video2: Permission denied");
+        let os_error = source.source().unwrap().downcast_ref::<Arc<io::Error>>().unwrap();
+        assert_eq!(os_error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(matches!(error.without_context(), NokhwaError::OsError { .. }));
+
+        // The first context is kept.
+        let rewrapped = error.with_context(Backends::AVFoundation, CameraIndex::String("FaceTime".to_string()));
+        assert!(rewrapped.to_string().starts_with("Video4Linux2 camera 2: "));
+    }
 }
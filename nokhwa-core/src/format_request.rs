@@ -1,11 +1,15 @@
+use crate::decoder::Decoder;
 use crate::utils::Distance;
 use crate::{
     frame_format::FrameFormat,
     ranges::Range,
-    types::{CameraFormat, FrameRate, Resolution},
+    types::{CameraFormat, FrameRate, FrameRateSupport, Resolution},
 };
 use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
 use crate::ranges::ValidatableRange;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq)]
 enum ClosestType {
@@ -16,6 +20,7 @@ enum ClosestType {
 }
 
 #[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum CustomFormatRequestType {
     HighestFrameRate,
     HighestResolution,
@@ -23,10 +28,32 @@ pub enum CustomFormatRequestType {
     Exact,
 }
 
+/// A total order between formats a [`FormatRequest`] considers equally good, so the same device resolves to the
+/// same format on every platform: the larger [`Resolution`] area first, then the higher [`FrameRate`], then
+/// [`FrameFormat`] in declaration order.
+fn tie_break(a: &CameraFormat, b: &CameraFormat) -> Ordering {
+    let area = |format: &CameraFormat| u64::from(format.width()) * u64::from(format.height());
+
+    area(b)
+        .cmp(&area(a))
+        .then_with(|| b.frame_rate().cmp(&a.frame_rate()))
+        .then_with(|| a.format().cmp(&b.format()))
+        .then_with(|| a.cmp(b))
+}
+
+// The higher `Resolution` first, then the higher `FrameRate`.
+fn highest_resolution_first(a: &CameraFormat, b: &CameraFormat) -> Ordering {
+    b.resolution()
+        .cmp(&a.resolution())
+        .then(b.frame_rate().cmp(&a.frame_rate()))
+        .then_with(|| tie_break(a, b))
+}
+
 /// A helper for choosing a [`CameraFormat`].
 /// The use of this is completely optional - for a simpler way try [`crate::camera::Camera::enumerate_formats`].
 ///
 /// The `frame_format` field filters out the [`CameraFormat`]s by [`FrameFormat`].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum FormatRequest {
     /// Pick the closest [`CameraFormat`] to the one requested
     Closest {
@@ -34,12 +61,22 @@ pub enum FormatRequest {
         frame_rate: Option<Range<FrameRate>>,
         frame_format: Vec<FrameFormat>,
     },
+    /// Pick the highest [`FrameRate`] within `frame_rate`, optionally restricted to the `resolution` range.
     HighestFrameRate {
         frame_rate: Range<FrameRate>,
+        resolution: Option<Range<Resolution>>,
         frame_format: Vec<FrameFormat>,
     },
+    /// Pick the highest [`Resolution`] within `resolution`, optionally restricted to the `frame_rate` range.
     HighestResolution {
         resolution: Range<Resolution>,
+        frame_rate: Option<Range<FrameRate>>,
+        frame_format: Vec<FrameFormat>,
+    },
+    /// Pick the highest [`Resolution`] that still runs at `min_rate` or faster, e.g. the sharpest image a video call
+    /// can get while keeping 30 fps. Ties go to the higher [`FrameRate`].
+    HighestResolutionAtLeastRate {
+        min_rate: FrameRate,
         frame_format: Vec<FrameFormat>,
     },
     Exact {
@@ -50,6 +87,10 @@ pub enum FormatRequest {
 }
 
 impl FormatRequest {
+    /// Returns the formats in `list_of_formats` this request accepts, best match first.
+    ///
+    /// Formats that match equally well are ordered by larger resolution area, then higher frame rate, then
+    /// [`FrameFormat`], so the result does not depend on the order the backend enumerated them in.
     pub fn sort_formats(&self, list_of_formats: &[CameraFormat]) -> Vec<CameraFormat> {
         if list_of_formats.is_empty() {
             return vec![];
@@ -82,33 +123,52 @@ impl FormatRequest {
                         (frame_rate_distance + resolution_point_distance, fmt)
                     })
                     .collect::<Vec<(f32, &CameraFormat)>>();
-                distances.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+                distances.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| tie_break(a.1, b.1)));
                 distances.into_iter().map(|x| x.1).copied().collect()
             }
             FormatRequest::HighestFrameRate {
                 frame_rate,
+                resolution,
                 frame_format,
             } => {
                 let mut formats = list_of_formats
                     .iter()
                     .filter(|x| {
-                        frame_format.contains(&x.format()) && frame_rate.validate(&x.frame_rate()).is_ok()
+                        frame_format.contains(&x.format())
+                            && frame_rate.validate(&x.frame_rate()).is_ok()
+                            && resolution.is_none_or(|res| res.validate(&x.resolution()).is_ok())
                     })
                     .collect::<Vec<_>>();
-                formats.sort();
+                formats.sort_by(|a, b| {
+                    b.frame_rate()
+                        .cmp(&a.frame_rate())
+                        .then(b.resolution().cmp(&a.resolution()))
+                        .then_with(|| tie_break(a, b))
+                });
                 formats.into_iter().copied().collect()
             }
             FormatRequest::HighestResolution {
                 resolution,
+                frame_rate,
                 frame_format,
             } => {
                 let mut formats = list_of_formats
                     .iter()
                     .filter(|x| {
-                        frame_format.contains(&x.format()) && resolution.validate(&x.resolution()).is_ok()
+                        frame_format.contains(&x.format())
+                            && resolution.validate(&x.resolution()).is_ok()
+                            && frame_rate.is_none_or(|rate| rate.validate(&x.frame_rate()).is_ok())
                     })
                     .collect::<Vec<_>>();
-                formats.sort();
+                formats.sort_by(|a, b| highest_resolution_first(a, b));
+                formats.into_iter().copied().collect()
+            }
+            FormatRequest::HighestResolutionAtLeastRate { min_rate, frame_format } => {
+                let mut formats = list_of_formats
+                    .iter()
+                    .filter(|x| frame_format.contains(&x.format()) && x.frame_rate() >= *min_rate)
+                    .collect::<Vec<_>>();
+                formats.sort_by(|a, b| highest_resolution_first(a, b));
                 formats.into_iter().copied().collect()
             }
             FormatRequest::Exact {
@@ -124,7 +184,7 @@ impl FormatRequest {
                             && frame_rate == &x.frame_rate()
                     })
                     .collect::<Vec<_>>();
-                formats.sort();
+                formats.sort_by(|a, b| tie_break(a, b));
                 formats.into_iter().copied().collect()
             }
         }
@@ -137,6 +197,542 @@ impl FormatRequest {
             return None;
         }
 
-        Some(self.sort_formats(list_of_formats).remove(0))
+        self.sort_formats(list_of_formats).into_iter().next()
+    }
+
+    /// Like [`resolve`](FormatRequest::resolve), for backends that report the frame rates of each [`Resolution`] and
+    /// [`FrameFormat`] as a [`FrameRateSupport`] rather than a list of formats.
+    ///
+    /// [`FormatRequest::Closest`] snaps its preferred frame rate into each stepwise range with
+    /// [`FrameRateSupport::closest`], so it can pick a rate between the ends of the range. The other requests
+    /// consider the discrete rates and both ends of the stepwise range.
+    #[must_use]
+    pub fn resolve_with_support(
+        &self,
+        supported: &[(Resolution, FrameFormat, FrameRateSupport)],
+    ) -> Option<CameraFormat> {
+        let closest_rate = match self {
+            FormatRequest::Closest { frame_rate, .. } => frame_rate.map(|rate| rate.preferred()),
+            _ => None,
+        };
+
+        let candidates = supported
+            .iter()
+            .flat_map(|(resolution, frame_format, support)| {
+                let stepwise = support
+                    .stepwise
+                    .map(|range| [range.minimum(), range.maximum()])
+                    .into_iter()
+                    .flatten()
+                    .flatten();
+                let snapped = closest_rate.and_then(|rate| support.closest(rate));
+
+                support
+                    .discrete
+                    .iter()
+                    .copied()
+                    .chain(stepwise)
+                    .chain(snapped)
+                    .map(|frame_rate| CameraFormat::new(*resolution, *frame_format, frame_rate))
+            })
+            .collect::<Vec<_>>();
+
+        self.resolve(&candidates)
+    }
+
+    /// Explains how this request filters `list_of_formats`, for working out why [`resolve`](FormatRequest::resolve)
+    /// returned `None`.
+    ///
+    /// Criteria are checked in order - [`FrameFormat`], then [`Resolution`], then [`FrameRate`] - and each rejected
+    /// format is counted against the first criterion it fails.
+    #[must_use]
+    pub fn explain(&self, list_of_formats: &[CameraFormat]) -> FormatResolveReport {
+        let mut report = FormatResolveReport {
+            total: list_of_formats.len(),
+            ..FormatResolveReport::default()
+        };
+
+        for format in list_of_formats {
+            if !self.frame_formats().contains(&format.format()) {
+                report.rejected_by_frame_format += 1;
+            } else if !self.accepts_resolution(format.resolution()) {
+                report.rejected_by_resolution += 1;
+            } else if !self.accepts_frame_rate(format.frame_rate()) {
+                report.rejected_by_frame_rate += 1;
+            } else {
+                report.matched += 1;
+            }
+        }
+
+        report.nearest = self.resolve(list_of_formats).or_else(|| {
+            let (resolution, frame_rate) = self.preferred_points();
+            FormatRequest::Closest {
+                resolution: resolution.map(Range::exact),
+                frame_rate: frame_rate.map(Range::exact),
+                frame_format: list_of_formats.iter().map(CameraFormat::format).collect(),
+            }
+            .resolve(list_of_formats)
+        });
+
+        report
+    }
+
+    fn frame_formats(&self) -> &[FrameFormat] {
+        match self {
+            FormatRequest::Closest { frame_format, .. }
+            | FormatRequest::HighestFrameRate { frame_format, .. }
+            | FormatRequest::HighestResolution { frame_format, .. }
+            | FormatRequest::HighestResolutionAtLeastRate { frame_format, .. }
+            | FormatRequest::Exact { frame_format, .. } => frame_format,
+        }
+    }
+
+    fn accepts_resolution(&self, value: Resolution) -> bool {
+        match self {
+            FormatRequest::Closest { .. } | FormatRequest::HighestResolutionAtLeastRate { .. } => true,
+            FormatRequest::HighestFrameRate { resolution, .. } => {
+                resolution.is_none_or(|res| res.validate(&value).is_ok())
+            }
+            FormatRequest::HighestResolution { resolution, .. } => resolution.validate(&value).is_ok(),
+            FormatRequest::Exact { resolution, .. } => *resolution == value,
+        }
+    }
+
+    fn accepts_frame_rate(&self, value: FrameRate) -> bool {
+        match self {
+            FormatRequest::Closest { .. } => true,
+            FormatRequest::HighestFrameRate { frame_rate, .. } => frame_rate.validate(&value).is_ok(),
+            FormatRequest::HighestResolution { frame_rate, .. } => {
+                frame_rate.is_none_or(|rate| rate.validate(&value).is_ok())
+            }
+            FormatRequest::HighestResolutionAtLeastRate { min_rate, .. } => value >= *min_rate,
+            FormatRequest::Exact { frame_rate, .. } => *frame_rate == value,
+        }
+    }
+
+    // The resolution and frame rate this request would ideally get.
+    fn preferred_points(&self) -> (Option<Resolution>, Option<FrameRate>) {
+        match self {
+            FormatRequest::Closest {
+                resolution,
+                frame_rate,
+                ..
+            } => (
+                resolution.map(|res| res.preferred()),
+                frame_rate.map(|rate| rate.preferred()),
+            ),
+            FormatRequest::HighestFrameRate {
+                frame_rate,
+                resolution,
+                ..
+            } => (resolution.map(|res| res.preferred()), Some(frame_rate.preferred())),
+            FormatRequest::HighestResolution {
+                resolution,
+                frame_rate,
+                ..
+            } => (Some(resolution.preferred()), frame_rate.map(|rate| rate.preferred())),
+            FormatRequest::HighestResolutionAtLeastRate { min_rate, .. } => (None, Some(*min_rate)),
+            FormatRequest::Exact {
+                resolution,
+                frame_rate,
+                ..
+            } => (Some(*resolution), Some(*frame_rate)),
+        }
+    }
+}
+
+/// A breakdown of how a [`FormatRequest`] filtered a list of [`CameraFormat`]s, see [`FormatRequest::explain`].
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct FormatResolveReport {
+    /// Number of formats considered.
+    pub total: usize,
+    /// Formats whose [`FrameFormat`] was not in the request's `frame_format` list.
+    pub rejected_by_frame_format: usize,
+    /// Formats whose [`Resolution`] was outside the requested range.
+    pub rejected_by_resolution: usize,
+    /// Formats whose [`FrameRate`] was outside the requested range.
+    pub rejected_by_frame_rate: usize,
+    /// Formats that passed every criterion.
+    pub matched: usize,
+    /// The format [`FormatRequest::resolve`] picks or, if nothing matched, the available format closest
+    /// to the requested resolution and frame rate regardless of the criteria.
+    pub nearest: Option<CameraFormat>,
+}
+
+impl Display for FormatResolveReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} formats matched ({} rejected by frame format, {} by resolution, {} by frame rate)",
+            self.matched,
+            self.total,
+            self.rejected_by_frame_format,
+            self.rejected_by_resolution,
+            self.rejected_by_frame_rate
+        )?;
+        if let Some(nearest) = self.nearest {
+            write!(f, ", nearest: {nearest}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of [`CameraFormat`] a [`RequestedFormat`] asks for.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum RequestedFormatType {
+    /// Pick the highest [`Resolution`], then the highest [`FrameRate`] at that resolution.
+    AbsoluteHighestResolution,
+    /// Pick the highest [`FrameRate`], then the highest [`Resolution`] at that frame rate.
+    AbsoluteHighestFrameRate,
+    /// Pick the highest [`Resolution`] available at exactly the given [`FrameRate`].
+    HighestResolution(FrameRate),
+    /// Pick the highest [`FrameRate`] available at exactly the given [`Resolution`].
+    HighestFrameRate(Resolution),
+    /// Pick the highest [`Resolution`] available at the given [`FrameRate`] or faster.
+    HighestResolutionAtLeastRate(FrameRate),
+    /// Pick exactly this [`CameraFormat`], if the decoder can decode its [`FrameFormat`].
+    Exact(CameraFormat),
+    /// Pick the [`CameraFormat`] with the [`Resolution`] and [`FrameRate`] closest to this one.
+    Closest(CameraFormat),
+    /// Pick any [`CameraFormat`] the decoder can decode, preferring the largest [`Resolution`], then the highest
+    /// [`FrameRate`].
+    None,
+}
+
+/// A request for a [`CameraFormat`] that the chosen [`Decoder`] is able to decode.
+///
+/// This compiles down to a [`FormatRequest`] whose `frame_format` list is the decoder's
+/// [`Decoder::ALLOWED_FORMATS`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RequestedFormat<'a> {
+    requested_format: RequestedFormatType,
+    wanted_decoder: &'a [FrameFormat],
+}
+
+impl RequestedFormat<'static> {
+    /// Creates a new [`RequestedFormat`] accepting the formats `D` can decode.
+    #[must_use]
+    pub fn new<D: Decoder>(requested: RequestedFormatType) -> RequestedFormat<'static> {
+        RequestedFormat {
+            requested_format: requested,
+            wanted_decoder: D::ALLOWED_FORMATS,
+        }
+    }
+}
+
+impl<'a> RequestedFormat<'a> {
+    /// Creates a new [`RequestedFormat`] accepting an explicit list of [`FrameFormat`]s.
+    #[must_use]
+    pub fn with_formats(requested: RequestedFormatType, decoder: &'a [FrameFormat]) -> Self {
+        RequestedFormat {
+            requested_format: requested,
+            wanted_decoder: decoder,
+        }
+    }
+
+    /// Gets the [`RequestedFormatType`]
+    #[must_use]
+    pub fn requested_format_type(&self) -> RequestedFormatType {
+        self.requested_format
+    }
+
+    /// Gets the [`FrameFormat`]s this request accepts.
+    #[must_use]
+    pub fn allowed_frame_formats(&self) -> &'a [FrameFormat] {
+        self.wanted_decoder
+    }
+
+    /// Compiles this request into the equivalent [`FormatRequest`].
+    #[must_use]
+    pub fn format_request(&self) -> FormatRequest {
+        let frame_format = self.wanted_decoder.to_vec();
+
+        match self.requested_format {
+            RequestedFormatType::AbsoluteHighestResolution => FormatRequest::HighestResolution {
+                resolution: Range::default(),
+                frame_rate: None,
+                frame_format,
+            },
+            RequestedFormatType::AbsoluteHighestFrameRate => FormatRequest::HighestFrameRate {
+                frame_rate: Range::default(),
+                resolution: None,
+                frame_format,
+            },
+            RequestedFormatType::HighestResolution(frame_rate) => {
+                FormatRequest::HighestResolution {
+                    resolution: Range::default(),
+                    frame_rate: Some(Range::exact(frame_rate)),
+                    frame_format,
+                }
+            }
+            RequestedFormatType::HighestFrameRate(resolution) => FormatRequest::HighestFrameRate {
+                frame_rate: Range::default(),
+                resolution: Some(Range::exact(resolution)),
+                frame_format,
+            },
+            RequestedFormatType::HighestResolutionAtLeastRate(min_rate) => {
+                FormatRequest::HighestResolutionAtLeastRate { min_rate, frame_format }
+            }
+            RequestedFormatType::Exact(camera_format) => FormatRequest::Exact {
+                resolution: camera_format.resolution(),
+                frame_rate: camera_format.frame_rate(),
+                frame_format: frame_format
+                    .into_iter()
+                    .filter(|x| *x == camera_format.format())
+                    .collect(),
+            },
+            RequestedFormatType::Closest(camera_format) => FormatRequest::Closest {
+                resolution: Some(Range::exact(camera_format.resolution())),
+                frame_rate: Some(Range::exact(camera_format.frame_rate())),
+                frame_format,
+            },
+            RequestedFormatType::None => FormatRequest::Closest {
+                resolution: None,
+                frame_rate: None,
+                frame_format,
+            },
+        }
+    }
+
+    /// Picks the [`CameraFormat`] from `list_of_formats` that fulfills this request.
+    #[must_use]
+    pub fn fulfill(&self, list_of_formats: &[CameraFormat]) -> Option<CameraFormat> {
+        self.format_request().resolve(list_of_formats)
+    }
+}
+
+impl From<RequestedFormat<'_>> for FormatRequest {
+    fn from(value: RequestedFormat<'_>) -> Self {
+        value.format_request()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stepwise(slowest: i32, fastest: i32) -> Range<FrameRate> {
+        let fastest = FrameRate::frame_rate(fastest);
+        Range::new(fastest, Some(FrameRate::frame_rate(slowest)), Some(fastest), None)
+    }
+
+    #[test]
+    fn stepwise_ranges_are_merged() {
+        let mut support = FrameRateSupport::default();
+        support.add_stepwise(stepwise(5, 15));
+        support.add_stepwise(stepwise(10, 30));
+
+        let merged = support.stepwise.unwrap();
+        assert_eq!(merged.minimum(), Some(FrameRate::frame_rate(5)));
+        assert_eq!(merged.maximum(), Some(FrameRate::frame_rate(30)));
+        assert!(support.supports(FrameRate::frame_rate(25)));
+    }
+
+    #[test]
+    fn every_requested_format_type_resolves_against_a_known_list() {
+        let format = |width, height, frame_format, fps| {
+            CameraFormat::new(Resolution::new(width, height), frame_format, FrameRate::frame_rate(fps))
+        };
+        // The NV12 entry is listed first and is the fastest, but the decoder below cannot use it.
+        let listed = [
+            format(320, 240, FrameFormat::Nv12, 120),
+            format(640, 480, FrameFormat::Yuyv422, 30),
+            format(640, 480, FrameFormat::Yuyv422, 60),
+            format(1280, 720, FrameFormat::Yuyv422, 30),
+            format(1920, 1080, FrameFormat::MJpeg, 15),
+            format(1920, 1080, FrameFormat::MJpeg, 30),
+        ];
+        let decodable = [FrameFormat::MJpeg, FrameFormat::Yuyv422];
+        let resolve = |requested| RequestedFormat::with_formats(requested, &decodable).fulfill(&listed);
+
+        let cases = [
+            (
+                RequestedFormatType::AbsoluteHighestResolution,
+                Some(format(1920, 1080, FrameFormat::MJpeg, 30)),
+            ),
+            (
+                RequestedFormatType::AbsoluteHighestFrameRate,
+                Some(format(640, 480, FrameFormat::Yuyv422, 60)),
+            ),
+            (
+                RequestedFormatType::HighestResolution(FrameRate::frame_rate(30)),
+                Some(format(1920, 1080, FrameFormat::MJpeg, 30)),
+            ),
+            (
+                RequestedFormatType::HighestFrameRate(Resolution::new(640, 480)),
+                Some(format(640, 480, FrameFormat::Yuyv422, 60)),
+            ),
+            (
+                RequestedFormatType::HighestResolutionAtLeastRate(FrameRate::frame_rate(31)),
+                Some(format(640, 480, FrameFormat::Yuyv422, 60)),
+            ),
+            (
+                RequestedFormatType::Exact(format(1280, 720, FrameFormat::Yuyv422, 30)),
+                Some(format(1280, 720, FrameFormat::Yuyv422, 30)),
+            ),
+            (RequestedFormatType::Exact(format(320, 240, FrameFormat::Nv12, 120)), None),
+            (
+                RequestedFormatType::Closest(format(1280, 720, FrameFormat::MJpeg, 25)),
+                Some(format(1280, 720, FrameFormat::Yuyv422, 30)),
+            ),
+            (RequestedFormatType::None, Some(format(1920, 1080, FrameFormat::MJpeg, 30))),
+        ];
+        for (requested, expected) in cases {
+            assert_eq!(resolve(requested), expected, "{requested:?}");
+        }
+    }
+
+    #[test]
+    fn closest_picks_a_rate_inside_a_stepwise_range() {
+        let resolution = Resolution::new(640, 480);
+        let supported = [(
+            resolution,
+            FrameFormat::Yuyv422,
+            FrameRateSupport {
+                discrete: vec![],
+                stepwise: Some(stepwise(5, 30)),
+            },
+        )];
+        let request = FormatRequest::Closest {
+            resolution: Some(Range::exact(resolution)),
+            frame_rate: Some(Range::exact(FrameRate::frame_rate(24))),
+            frame_format: vec![FrameFormat::Yuyv422],
+        };
+
+        let format = request.resolve_with_support(&supported).unwrap();
+        assert_eq!(format.frame_rate(), FrameRate::frame_rate(24));
+
+        // Without the support, only the ends of the range are offered.
+        let listed = [
+            CameraFormat::new(resolution, FrameFormat::Yuyv422, FrameRate::frame_rate(5)),
+            CameraFormat::new(resolution, FrameFormat::Yuyv422, FrameRate::frame_rate(30)),
+        ];
+        assert_eq!(request.resolve(&listed).unwrap().frame_rate(), FrameRate::frame_rate(30));
+    }
+
+    // Exactly 1280x720 YUYV at 30 fps.
+    fn exact_720p() -> FormatRequest {
+        FormatRequest::Exact {
+            resolution: Resolution::new(1280, 720),
+            frame_rate: FrameRate::frame_rate(30),
+            frame_format: vec![FrameFormat::Yuyv422],
+        }
+    }
+
+    #[test]
+    fn explain_counts_formats_rejected_by_frame_format() {
+        let mjpeg = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::MJpeg, FrameRate::frame_rate(30));
+
+        let report = exact_720p().explain(&[mjpeg]);
+        assert_eq!((report.total, report.rejected_by_frame_format, report.matched), (1, 1, 0));
+        assert_eq!(report.nearest, Some(mjpeg));
+        assert!(report.to_string().starts_with("0 of 1 formats matched (1 rejected by frame format, 0 by resolution"));
+    }
+
+    #[test]
+    fn explain_counts_formats_rejected_by_resolution() {
+        let vga = CameraFormat::new(Resolution::new(640, 480), FrameFormat::Yuyv422, FrameRate::frame_rate(30));
+        let hd = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::Yuyv422, FrameRate::frame_rate(30));
+
+        let report = exact_720p().explain(&[vga, hd]);
+        assert_eq!((report.total, report.rejected_by_resolution, report.matched), (2, 1, 1));
+        assert_eq!(report.nearest, Some(hd));
+    }
+
+    #[test]
+    fn explain_counts_formats_rejected_by_frame_rate() {
+        let fast = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::Yuyv422, FrameRate::frame_rate(45));
+        let slow = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::Yuyv422, FrameRate::frame_rate(15));
+
+        let report = exact_720p().explain(&[fast, slow]);
+        assert_eq!((report.total, report.rejected_by_frame_rate, report.matched), (2, 2, 0));
+        // 15 fps is as far from 30 as 45 is, so the tie goes to the higher rate.
+        assert_eq!(report.nearest, Some(fast));
+    }
+
+    #[test]
+    fn resolving_does_not_depend_on_enumeration_order() {
+        let format = |width, height, frame_format, fps| {
+            CameraFormat::new(Resolution::new(width, height), frame_format, FrameRate::frame_rate(fps))
+        };
+        // 800x600 and 1000x600 are as far from 900x600 as each other, and come in two frame formats.
+        let listed = [
+            format(800, 600, FrameFormat::MJpeg, 30),
+            format(800, 600, FrameFormat::Yuyv422, 30),
+            format(1000, 600, FrameFormat::Yuyv422, 30),
+            format(1000, 600, FrameFormat::MJpeg, 30),
+            format(640, 480, FrameFormat::Yuyv422, 30),
+        ];
+        let frame_format = vec![FrameFormat::MJpeg, FrameFormat::Yuyv422];
+        let requests = [
+            FormatRequest::Closest {
+                resolution: Some(Range::exact(Resolution::new(900, 600))),
+                frame_rate: Some(Range::exact(FrameRate::frame_rate(30))),
+                frame_format: frame_format.clone(),
+            },
+            FormatRequest::HighestFrameRate {
+                frame_rate: Range::default(),
+                resolution: None,
+                frame_format: frame_format.clone(),
+            },
+            FormatRequest::Exact {
+                resolution: Resolution::new(800, 600),
+                frame_rate: FrameRate::frame_rate(30),
+                frame_format,
+            },
+        ];
+
+        for request in requests {
+            let expected = request.sort_formats(&listed);
+            for rotation in 0..listed.len() {
+                let mut shuffled = listed.to_vec();
+                shuffled.rotate_left(rotation);
+                assert_eq!(request.sort_formats(&shuffled), expected);
+                shuffled.reverse();
+                assert_eq!(request.sort_formats(&shuffled), expected);
+                assert_eq!(request.resolve(&shuffled), expected.first().copied());
+            }
+        }
+    }
+
+    #[test]
+    fn at_least_rate_prefers_resolution_once_the_floor_is_met() {
+        let full_hd = CameraFormat::new(Resolution::new(1920, 1080), FrameFormat::MJpeg, FrameRate::frame_rate(30));
+        let listed = [
+            CameraFormat::new(Resolution::new(1280, 720), FrameFormat::MJpeg, FrameRate::frame_rate(60)),
+            full_hd,
+            CameraFormat::new(Resolution::new(3840, 2160), FrameFormat::MJpeg, FrameRate::frame_rate(15)),
+        ];
+        let request = |fps| FormatRequest::HighestResolutionAtLeastRate {
+            min_rate: FrameRate::frame_rate(fps),
+            frame_format: vec![FrameFormat::MJpeg],
+        };
+
+        assert_eq!(request(30).resolve(&listed), Some(full_hd));
+        assert_eq!(request(31).resolve(&listed), Some(listed[0]));
+        assert_eq!(request(61).resolve(&listed), None);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn closest_requests_survive_a_serde_round_trip() {
+        let ntsc = FrameRate::try_new(30000, 1001).unwrap();
+        let target = CameraFormat::new(Resolution::new(1280, 720), FrameFormat::Yuyv422, ntsc);
+        let request = RequestedFormat::with_formats(RequestedFormatType::Closest(target), &[FrameFormat::Yuyv422]);
+
+        let json = serde_json::to_string(&request.requested_format_type()).unwrap();
+        let requested_format_type = serde_json::from_str::<RequestedFormatType>(&json).unwrap();
+        assert_eq!(requested_format_type, RequestedFormatType::Closest(target));
+
+        let json = serde_json::to_string(&request.format_request()).unwrap();
+        let format_request = serde_json::from_str::<FormatRequest>(&json).unwrap();
+        assert_eq!(serde_json::to_string(&format_request).unwrap(), json);
+        let FormatRequest::Closest { resolution, frame_rate, frame_format } = format_request else {
+            panic!("expected a Closest request, got {json}");
+        };
+        assert_eq!(resolution.map(|range| range.preferred()), Some(target.resolution()));
+        assert_eq!(frame_rate.map(|range| range.preferred()), Some(target.frame_rate()));
+        assert_eq!(frame_format, [FrameFormat::Yuyv422]);
     }
 }
@@ -14,19 +14,130 @@
  * limitations under the License.
  */
 
-use crate::frame_format::FrameFormat;
+use crate::aligned::AlignedBuf;
+#[cfg(feature = "mjpeg")]
+use crate::conversions::{buf_expand_limited_range, mjpeg_to_rgb};
+use crate::conversions::{
+    buf_nv12_to_planar_rgb, buf_rgb_to_planar_rgb, buf_yuyv422_to_planar_rgb, convert_nv12_to_i420_direct,
+    convert_packed_rgb_to_i420_direct, convert_yuyv_to_i420_direct, i420_predicted_size, nv12_predicted_size, nv12_to_nv21, nv12_to_rgb_with_range,
+    nv21_to_nv12, packed_yuv422_to_rgb, PlaneLayout,
+};
+use crate::decoder::{Decoder, StaticDecoder};
+use crate::error::{NokhwaError, NokhwaResult};
+use crate::frame_format::{frame_format_layout, ColorRange, FrameFormat, ScanType, TransferFunction};
+use crate::h26x;
 use crate::types::Resolution;
 use bytes::Bytes;
+use image::{ImageBuffer, Pixel, Rgb};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+#[cfg(feature = "image-save")]
+use std::{fs::File, io::BufWriter, path::Path};
+#[cfg(unix)]
+use std::os::fd::RawFd;
+
+// How many samples `FrameBuffer::fingerprint` reads.
+const FINGERPRINT_SAMPLES: usize = 4096;
+
+static TIMESTAMP_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// The point [`FrameBuffer::timestamp_monotonic`] measures from, fixed the first time it is asked for and the same
+/// for every camera and backend in the process.
+///
+/// Call this once at startup to pin it, so frames captured before their timestamps are first read do not read
+/// as zero. Add a [`timestamp_monotonic`](FrameBuffer::timestamp_monotonic) to it to get the frame's [`Instant`].
+#[must_use]
+pub fn timestamp_epoch() -> Instant {
+    *TIMESTAMP_EPOCH.get_or_init(Instant::now)
+}
+
+/// The clock a backend's native frame timestamps are measured on.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimestampClock {
+    /// `CLOCK_MONOTONIC`, which V4L2 stamps buffers with (`V4L2_BUF_FLAG_TIMESTAMP_MONOTONIC`). On Linux this is
+    /// also the clock behind [`Instant`].
+    Monotonic,
+    /// The Core Media host time clock (`CMClockGetHostTimeClock`, backed by `mach_absolute_time`) that
+    /// `AVFoundation` sample buffer presentation times are on, converted to nanoseconds.
+    HostTime,
+    /// The `QueryPerformanceCounter` clock that Media Foundation sample times are on, counted in 100 nanosecond
+    /// units. On Windows this is also the clock behind [`Instant`].
+    PerformanceCounter,
+}
+
+/// A frame timestamp as its backend reported it, on the backend's own [`TimestampClock`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct RawTimestamp {
+    clock: TimestampClock,
+    time: Duration,
+}
+
+impl RawTimestamp {
+    /// A timestamp `time` after the origin of `clock`.
+    #[must_use]
+    pub fn new(clock: TimestampClock, time: Duration) -> Self {
+        RawTimestamp { clock, time }
+    }
+
+    #[must_use]
+    pub fn clock(&self) -> TimestampClock {
+        self.clock
+    }
+
+    /// The time since the origin of the [`clock`](RawTimestamp::clock), e.g. since boot for
+    /// [`TimestampClock::Monotonic`].
+    #[must_use]
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Converts this timestamp to an [`Instant`], given `clock_now`, the same clock read just before calling this.
+    /// A timestamp after `clock_now` converts to now.
+    #[must_use]
+    pub fn to_instant(&self, clock_now: Duration) -> Instant {
+        let now = Instant::now();
+        now.checked_sub(clock_now.saturating_sub(self.time)).unwrap_or(now)
+    }
+}
+
+/// How [`FrameBuffer::deinterlace`] turns an interlaced frame into a progressive one.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum Deinterlace {
+    /// Keeps both fields as they are. Full detail for still scenes, but moving edges stay combed.
+    Weave,
+    /// Keeps the field captured first and fills in the other field's rows by averaging the rows above and below.
+    /// Removes combing at the cost of half the vertical detail.
+    Bob,
+}
 
 /// A buffer returned by a camera to accommodate custom decoding.
 /// Contains information of Resolution, the buffer's [`FrameFormat`], and the buffer.
 ///
 /// Note that decoding on the main thread **will** decrease your performance and lead to dropped frames.
+///
+/// A `FrameBuffer` owns its bytes, never borrowing the driver's buffers, so it can be kept past the stream loop or
+/// sent to another thread. Cloning is cheap: the bytes are immutable and reference counted, so a clone shares them
+/// instead of copying. A clone carries over everything else as well, including the format, resolution,
+/// [timestamp](FrameBuffer::timestamp), [color range](FrameBuffer::color_range) and
+/// [transfer function](FrameBuffer::transfer_function), and compares equal to the original. On Unix the dma-buf descriptor (`dmabuf_fd`) is copied as a plain number, and like the original's
+/// it is only valid while the stream is open.
 #[derive(Clone, Debug, Hash, PartialOrd, PartialEq, Eq)]
 pub struct FrameBuffer {
     resolution: Resolution,
     buffer: Bytes,
     source_frame_format: FrameFormat,
+    #[cfg(unix)]
+    dmabuf_fd: Option<RawFd>,
+    duplicate_of_previous: bool,
+    timestamp: Option<Instant>,
+    raw_timestamp: Option<RawTimestamp>,
+    color_range: Option<ColorRange>,
+    transfer_function: TransferFunction,
+    scan_type: ScanType,
 }
 
 impl FrameBuffer {
@@ -38,9 +149,146 @@ impl FrameBuffer {
             resolution: res,
             buffer: Bytes::copy_from_slice(buf),
             source_frame_format,
+            #[cfg(unix)]
+            dmabuf_fd: None,
+            duplicate_of_previous: false,
+            timestamp: None,
+            raw_timestamp: None,
+            color_range: None,
+            transfer_function: TransferFunction::default(),
+            scan_type: ScanType::Progressive,
         }
     }
 
+    /// Attaches the dma-buf file descriptor this frame was captured into, see [`IoMethod::DmaBuf`](crate::stream::IoMethod::DmaBuf).
+    #[cfg(unix)]
+    #[must_use]
+    pub fn with_dmabuf_fd(mut self, fd: RawFd) -> Self {
+        self.dmabuf_fd = Some(fd);
+        self
+    }
+
+    /// Get the dma-buf file descriptor backing this frame, if the stream was opened with
+    /// [`IoMethod::DmaBuf`](crate::stream::IoMethod::DmaBuf).
+    ///
+    /// The descriptor is owned by the stream and only stays valid while the stream is open.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn dmabuf_fd(&self) -> Option<RawFd> {
+        self.dmabuf_fd
+    }
+
+    /// Whether this frame looks identical to the one the stream delivered before it, which cameras do when they
+    /// cannot keep up. Only set by streams with [duplicate detection](crate::stream::Stream::set_duplicate_detection) on.
+    #[must_use]
+    pub fn is_duplicate_of_previous(&self) -> bool {
+        self.duplicate_of_previous
+    }
+
+    pub(crate) fn with_duplicate_of_previous(mut self, duplicate_of_previous: bool) -> Self {
+        self.duplicate_of_previous = duplicate_of_previous;
+        self
+    }
+
+    /// Stamps this frame with the time it was captured. Backends with a hardware timestamp should convert it to an
+    /// [`Instant`]; otherwise [`StreamStatsRecorder::send_frame`](crate::stream::StreamStatsRecorder::send_frame)
+    /// stamps the frame as it is sent.
+    #[must_use]
+    pub fn with_timestamp(mut self, timestamp: Instant) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// When this frame was captured, if the backend recorded it. Frames from different cameras can be compared by
+    /// this, see [`SyncGroup`](crate::stream::SyncGroup).
+    #[must_use]
+    pub fn timestamp(&self) -> Option<Instant> {
+        self.timestamp
+    }
+
+    /// Stamps this frame with the timestamp its backend reported, keeping it as the
+    /// [`raw_timestamp`](FrameBuffer::raw_timestamp) and converting it to the [`timestamp`](FrameBuffer::timestamp)
+    /// with [`RawTimestamp::to_instant`]. `clock_now` is the raw timestamp's clock read just before calling this.
+    ///
+    /// Each backend converts its native timestamp to a [`RawTimestamp`] as follows:
+    /// - V4L2: the `v4l2_buffer` timestamp is already a [`TimestampClock::Monotonic`] time.
+    /// - `AVFoundation`: the sample buffer's presentation time is a `CMTime` on the host time clock, so in
+    ///   nanoseconds it is a [`TimestampClock::HostTime`].
+    ///
+    /// Frames from other backends are stamped as they are sent, see [`with_timestamp`](FrameBuffer::with_timestamp).
+    #[must_use]
+    pub fn with_raw_timestamp(mut self, raw_timestamp: RawTimestamp, clock_now: Duration) -> Self {
+        self.timestamp = Some(raw_timestamp.to_instant(clock_now));
+        self.raw_timestamp = Some(raw_timestamp);
+        self
+    }
+
+    /// The timestamp as the backend reported it, on its own clock, if it set one with
+    /// [`with_raw_timestamp`](FrameBuffer::with_raw_timestamp).
+    #[must_use]
+    pub fn raw_timestamp(&self) -> Option<RawTimestamp> {
+        self.raw_timestamp
+    }
+
+    /// The [`timestamp`](FrameBuffer::timestamp) as the time since [`timestamp_epoch`], which is the same for
+    /// every backend, so frames can be compared with each other and with audio or other sensors on one timeline.
+    /// A frame timestamped before the epoch, or not timestamped at all, reads as zero.
+    #[must_use]
+    pub fn timestamp_monotonic(&self) -> Duration {
+        self.timestamp
+            .map_or(Duration::ZERO, |timestamp| timestamp.saturating_duration_since(timestamp_epoch()))
+    }
+
+    /// Overrides the [`ColorRange`] this frame's YUV data is decoded with. Backends that know the range the device
+    /// sends (e.g. `AVFoundation`'s full range NV12, or Media Foundation's `MF_MT_VIDEO_NOMINAL_RANGE`) should set it.
+    #[must_use]
+    pub fn with_color_range(mut self, color_range: ColorRange) -> Self {
+        self.color_range = Some(color_range);
+        self
+    }
+
+    /// The [`ColorRange`] this frame is decoded with: the one set with [`with_color_range`](FrameBuffer::with_color_range),
+    /// or otherwise its format's [default](FrameFormat::default_color_range).
+    #[must_use]
+    pub fn color_range(&self) -> ColorRange {
+        self.color_range
+            .unwrap_or_else(|| self.source_frame_format.default_color_range())
+    }
+
+    /// Overrides the [`TransferFunction`] of this frame, for backends whose device sends linear data.
+    #[must_use]
+    pub fn with_transfer_function(mut self, transfer_function: TransferFunction) -> Self {
+        self.transfer_function = transfer_function;
+        self
+    }
+
+    /// The [`TransferFunction`] of this frame, which the RGB it decodes to keeps. [`TransferFunction::Srgb`] unless
+    /// the backend set it with [`with_transfer_function`](FrameBuffer::with_transfer_function).
+    #[must_use]
+    pub fn transfer_function(&self) -> TransferFunction {
+        self.transfer_function
+    }
+
+    /// Whether this frame decodes to sRGB encoded RGB, which should be uploaded to an sRGB texture format.
+    #[must_use]
+    pub fn is_srgb(&self) -> bool {
+        self.transfer_function == TransferFunction::Srgb
+    }
+
+    /// Marks this frame as interlaced, for backends whose [`CameraFormat::scan_type`](crate::types::CameraFormat::scan_type)
+    /// is not progressive.
+    #[must_use]
+    pub fn with_scan_type(mut self, scan_type: ScanType) -> Self {
+        self.scan_type = scan_type;
+        self
+    }
+
+    /// Whether this frame is progressive or holds two interlaced fields, see [`deinterlace`](FrameBuffer::deinterlace).
+    #[must_use]
+    pub fn scan_type(&self) -> ScanType {
+        self.scan_type
+    }
+
     /// Get the [`Resolution`] of this buffer.
     #[must_use]
     pub fn resolution(&self) -> Resolution {
@@ -64,4 +312,1048 @@ impl FrameBuffer {
     pub fn source_frame_format(&self) -> FrameFormat {
         self.source_frame_format
     }
+
+    /// Checks that this buffer holds at least as many bytes as its [`FrameFormat`] needs at its [`Resolution`]
+    /// (see [`FrameFormat::byte_size`]), catching frames cut short by e.g. a partial USB transfer, and returns
+    /// the image's bytes.
+    ///
+    /// Drivers may hand out buffers padded past the end of the image, so any excess is sliced off rather than
+    /// rejected. Compressed formats vary in size, so they always pass with the whole buffer.
+    /// # Errors
+    /// If the buffer is too short, this will error with the expected and actual lengths.
+    pub fn validate_length(&self) -> NokhwaResult<&[u8]> {
+        match self.source_frame_format.byte_size(self.resolution) {
+            Some(expected) if expected > self.buffer.len() => Err(NokhwaError::StructureError {
+                structure: "FrameBuffer".to_string(),
+                error: format!(
+                    "{} at {} needs {expected} bytes, got {}",
+                    self.source_frame_format,
+                    self.resolution,
+                    self.buffer.len()
+                ),
+            }),
+            Some(expected) => Ok(&self.buffer[..expected]),
+            None => Ok(&self.buffer),
+        }
+    }
+
+    /// Returns whether this is a keyframe, for H264 ([`FrameFormat::H264`], [`FrameFormat::Avc1`]) and
+    /// [`FrameFormat::H265`] buffers. A recorder passing the bitstream through should start at one.
+    ///
+    /// Returns `None` for other formats, or if the buffer holds no picture data. See [`h26x::is_keyframe`].
+    #[must_use]
+    pub fn is_keyframe(&self) -> Option<bool> {
+        h26x::is_keyframe(self.source_frame_format, &self.buffer)
+    }
+
+    /// Returns whether this buffer has to go through a [`Decoder`](crate::decoder::Decoder) (or a conversion such
+    /// as [`to_rgb`](FrameBuffer::to_rgb)) to get pixels: `true` for uncompressed formats, `false` for
+    /// [compressed](FrameFormat::is_compressed) ones and for [`FrameFormat::Rgb888`].
+    ///
+    /// Compressed frames are meant to be passed through as is, e.g. by a recorder writing the MJPEG or H264
+    /// bitstream from [`buffer`](FrameBuffer::buffer) to disk. Nothing on the way from a
+    /// [`Stream`](crate::stream::Stream) to the caller decodes frames, so no decoder is needed for that.
+    ///
+    /// RGB888 is already the packed RGB most consumers decode to, so its bytes can be used as pixels directly
+    /// (see [`into_rgb_image`](FrameBuffer::into_rgb_image)). Other RGB layouts, such as RGBA or ARGB, still need
+    /// their channels rearranged and count as requiring a decode.
+    #[must_use]
+    pub fn requires_decode(&self) -> bool {
+        !self.source_frame_format.is_compressed() && self.source_frame_format != FrameFormat::Rgb888
+    }
+
+    /// Decodes this buffer into interleaved RGB888, `width * height * 3` bytes, in its [`color_range`](FrameBuffer::color_range).
+    /// # Errors
+    /// If the source format is not supported, or the buffer does not match its resolution, this will error.
+    pub fn to_rgb(&self) -> NokhwaResult<Vec<u8>> {
+        let data = self.validate_length()?;
+
+        let rgb = match self.source_frame_format {
+            #[cfg(feature = "mjpeg")]
+            FrameFormat::MJpeg => self.mjpeg_to_rgb()?,
+            FrameFormat::Rgb888 => data.to_vec(),
+            FrameFormat::Yuyv422 | FrameFormat::Uyvy422 | FrameFormat::Yvyu422 => {
+                packed_yuv422_to_rgb(self.source_frame_format, data, false, self.color_range())?
+            }
+            FrameFormat::Nv12 => nv12_to_rgb_with_range(self.resolution, data, false, self.color_range())?,
+            FrameFormat::Luma8 => data.iter().flat_map(|&luma| [luma; 3]).collect(),
+            other => {
+                return Err(NokhwaError::ProcessFrameError {
+                    src: other,
+                    destination: "RGB888".to_string(),
+                    error: "Unsupported source format".to_string(),
+                })
+            }
+        };
+
+        Ok(rgb)
+    }
+
+    /// Converts this buffer into an RGB image. If the source is already [`FrameFormat::Rgb888`], its bytes are
+    /// moved into the image instead of copied, as long as no clone of this buffer shares them. Other sources are
+    /// decoded with [`to_rgb`](FrameBuffer::to_rgb).
+    /// # Errors
+    /// If the source format is not supported, or the buffer does not match its resolution, this will error.
+    pub fn into_rgb_image(self) -> NokhwaResult<ImageBuffer<Rgb<u8>, Vec<u8>>> {
+        let rgb = if self.source_frame_format == FrameFormat::Rgb888 {
+            let len = self.validate_length()?.len();
+            let mut rgb = Vec::from(self.buffer);
+            rgb.truncate(len);
+            rgb
+        } else {
+            self.to_rgb()?
+        };
+
+        let expected = FrameFormat::Rgb888.byte_size(self.resolution).unwrap_or_default();
+        let got = rgb.len();
+        ImageBuffer::from_raw(self.resolution.width(), self.resolution.height(), rgb).ok_or_else(|| {
+            NokhwaError::ProcessFrameError {
+                src: self.source_frame_format,
+                destination: "RGB888".to_string(),
+                error: format!("Bad buffer length: expected {expected} bytes, got {got}"),
+            }
+        })
+    }
+
+    /// Decodes this buffer to RGB and saves it to `path` as `format`, e.g. for debugging or snapshots.
+    /// # Errors
+    /// If decoding fails, `format` has no encoder compiled in (`image-save` enables PNG and JPEG), or writing
+    /// the file fails, this will error.
+    #[cfg(feature = "image-save")]
+    pub fn save(&self, path: impl AsRef<Path>, format: image::ImageFormat) -> NokhwaResult<()> {
+        self.to_rgb_image()?
+            .save_with_format(path, format)
+            .map_err(|why| self.save_error(format, &why))
+    }
+
+    /// Decodes this buffer to RGB and saves it to `path` as a JPEG of the given `quality` (1-100).
+    /// # Errors
+    /// If decoding, encoding or writing the file fails, this will error.
+    #[cfg(feature = "image-save")]
+    pub fn save_jpeg(&self, path: impl AsRef<Path>, quality: u8) -> NokhwaResult<()> {
+        let image = self.to_rgb_image()?;
+        let file = File::create(path).map_err(|why| NokhwaError::os_error("failed to create the JPEG file", why))?;
+
+        image::codecs::jpeg::JpegEncoder::new_with_quality(BufWriter::new(file), quality)
+            .encode_image(&image)
+            .map_err(|why| self.save_error(image::ImageFormat::Jpeg, &why))
+    }
+
+    #[cfg(feature = "image-save")]
+    fn to_rgb_image(&self) -> NokhwaResult<image::RgbImage> {
+        let rgb = self.to_rgb()?;
+        let expected = FrameFormat::Rgb888.byte_size(self.resolution).unwrap_or_default();
+        let got = rgb.len();
+        image::RgbImage::from_raw(self.resolution.width(), self.resolution.height(), rgb).ok_or_else(|| {
+            NokhwaError::ProcessFrameError {
+                src: self.source_frame_format,
+                destination: "RGB888".to_string(),
+                error: format!("Bad buffer length: expected {expected} bytes, got {got}"),
+            }
+        })
+    }
+
+    #[cfg(feature = "image-save")]
+    fn save_error(&self, format: image::ImageFormat, why: &image::ImageError) -> NokhwaError {
+        NokhwaError::ProcessFrameError {
+            src: self.source_frame_format,
+            destination: format!("{format:?}"),
+            error: why.to_string(),
+        }
+    }
+
+    /// Decodes this buffer with `D` into tightly packed rows, ready for e.g. `wgpu`'s `Queue::write_texture`.
+    ///
+    /// Returns the decoded data, its [`Resolution`], and the row stride in bytes. The stride is
+    /// always `width * channels`: rows never carry padding, even if the source buffer did.
+    /// # Errors
+    /// If decoding fails, this will error.
+    pub fn decode_packed<D>(&self) -> NokhwaResult<(Vec<u8>, Resolution, usize)>
+    where
+        D: StaticDecoder<PixelContainer = Vec<u8>>,
+        D::OutputPixels: Pixel<Subpixel = u8>,
+    {
+        let image = D::decode_static(self)?;
+        let resolution = Resolution::new(image.width(), image.height());
+        let stride = resolution.width() as usize * D::channels();
+        Ok((image.into_raw(), resolution, stride))
+    }
+
+    /// Decodes this buffer with `D` into an [`AlignedBuf`], whose start is aligned for SIMD loads and stores.
+    ///
+    /// The bytes are laid out as [`StaticDecoder::decode_static_to_buffer`] writes them, tightly packed at
+    /// [`FrameBuffer::resolution`].
+    /// # Errors
+    /// If `D` cannot decode this buffer's format, or decoding fails, this will error.
+    pub fn decode_image_aligned<D>(&self) -> NokhwaResult<AlignedBuf>
+    where
+        D: StaticDecoder,
+        D::OutputPixels: Pixel<Subpixel = u8>,
+    {
+        let size = D::predicted_size_of_frame(self).ok_or_else(|| NokhwaError::ProcessFrameError {
+            src: self.source_frame_format,
+            destination: std::any::type_name::<D>().to_string(),
+            error: "Unsupported source format".to_string(),
+        })?;
+        let mut output = AlignedBuf::new(size);
+        D::decode_static_to_buffer(self, &mut output)?;
+        Ok(output)
+    }
+
+    /// Decodes this buffer into planar RGB (`CHW`): the red plane, then the green plane, then the blue plane,
+    /// each `width * height` bytes. This is the layout most ML inference frameworks expect.
+    ///
+    /// Uncompressed sources are converted straight into planes without an interleaved intermediate.
+    /// # Errors
+    /// If the source format is not supported, or the buffer does not match its resolution, this will error.
+    pub fn to_planar_rgb(&self) -> NokhwaResult<Vec<u8>> {
+        let data = self.validate_length()?;
+        // Three planes of one byte per pixel take as much space as interleaved RGB888.
+        let mut output = vec![0; FrameFormat::Rgb888.byte_size(self.resolution).unwrap_or_default()];
+        let plane_size = output.len() / 3;
+
+        match self.source_frame_format {
+            #[cfg(feature = "mjpeg")]
+            FrameFormat::MJpeg => {
+                let rgb = self.mjpeg_to_rgb()?;
+                buf_rgb_to_planar_rgb(self.exact_len(&rgb, output.len())?, &mut output)?;
+            }
+            FrameFormat::Rgb888 => buf_rgb_to_planar_rgb(data, &mut output)?,
+            FrameFormat::Yuyv422 => buf_yuyv422_to_planar_rgb(data, &mut output, self.color_range())?,
+            FrameFormat::Nv12 => {
+                buf_nv12_to_planar_rgb(self.resolution, data, &mut output, self.color_range())?;
+            }
+            FrameFormat::Luma8 => {
+                for plane in output.chunks_exact_mut(plane_size) {
+                    plane.copy_from_slice(data);
+                }
+            }
+            other => {
+                return Err(NokhwaError::ProcessFrameError {
+                    src: other,
+                    destination: "Planar RGB".to_string(),
+                    error: "Unsupported source format".to_string(),
+                })
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Decodes this buffer into RGB as an [`ndarray::Array3`] of shape `(height, width, 3)` (`HWC`).
+    ///
+    /// The decoded pixels become the array's storage directly, without another copy.
+    /// # Errors
+    /// If the source format is not supported, or the buffer does not match its resolution, this will error.
+    #[cfg(feature = "ndarray")]
+    pub fn to_ndarray_hwc(&self) -> NokhwaResult<ndarray::Array3<u8>> {
+        let width = self.resolution.width() as usize;
+        let height = self.resolution.height() as usize;
+        self.shape_ndarray((height, width, 3), self.to_rgb()?, "ndarray HWC")
+    }
+
+    /// Decodes this buffer into RGB as an [`ndarray::Array3`] of shape `(3, height, width)` (`CHW`), the planes of
+    /// [`to_planar_rgb`](FrameBuffer::to_planar_rgb).
+    /// # Errors
+    /// If the source format is not supported, or the buffer does not match its resolution, this will error.
+    #[cfg(feature = "ndarray")]
+    pub fn to_ndarray_chw(&self) -> NokhwaResult<ndarray::Array3<u8>> {
+        let width = self.resolution.width() as usize;
+        let height = self.resolution.height() as usize;
+        self.shape_ndarray((3, height, width), self.to_planar_rgb()?, "ndarray CHW")
+    }
+
+    #[cfg(feature = "ndarray")]
+    fn shape_ndarray(
+        &self,
+        shape: (usize, usize, usize),
+        data: Vec<u8>,
+        destination: &str,
+    ) -> NokhwaResult<ndarray::Array3<u8>> {
+        ndarray::Array3::from_shape_vec(shape, data).map_err(|why| NokhwaError::ProcessFrameError {
+            src: self.source_frame_format,
+            destination: destination.to_string(),
+            error: why.to_string(),
+        })
+    }
+
+    /// Converts this buffer into a newly allocated I420 (planar 4:2:0) buffer of [`i420_predicted_size`] bytes.
+    ///
+    /// Supports the same sources as [`to_i420_buffer`](FrameBuffer::to_i420_buffer), and produces the same output.
+    /// # Errors
+    /// If the source format is not supported, or the conversion fails, this will error.
+    pub fn to_i420(&self) -> NokhwaResult<Vec<u8>> {
+        let mut output = vec![0; i420_predicted_size(self.resolution)];
+        self.to_i420_buffer(&mut output)?;
+        Ok(output)
+    }
+
+    /// Converts this buffer into a packed I420 buffer like [`to_i420`](FrameBuffer::to_i420), along with the
+    /// [`PlaneLayout`] giving the offset and stride of each plane in it.
+    /// # Errors
+    /// If the source format is not supported, or the conversion fails, this will error.
+    pub fn to_i420_planes(&self) -> NokhwaResult<(Vec<u8>, PlaneLayout)> {
+        Ok((self.to_i420()?, PlaneLayout::i420(self.resolution)))
+    }
+
+    /// Converts this buffer into I420 (planar 4:2:0), writing into `output`.
+    /// Supports [`FrameFormat::Yuyv422`], [`FrameFormat::Nv12`], [`FrameFormat::I420`], [`FrameFormat::RgbA8888`]
+    /// and [`FrameFormat::ARgb8888`] sources; RGB is converted with [`convert_packed_rgb_to_i420_direct`].
+    /// # Errors
+    /// If the source format is not supported, or the conversion fails, this will error. If `output` is smaller than
+    /// [`i420_predicted_size`] this returns [`NokhwaError::BufferTooSmall`].
+    pub fn to_i420_buffer(&self, output: &mut [u8]) -> NokhwaResult<()> {
+        match self.source_frame_format {
+            FrameFormat::Yuyv422 => convert_yuyv_to_i420_direct(&self.buffer, self.resolution, output),
+            FrameFormat::Nv12 => convert_nv12_to_i420_direct(&self.buffer, self.resolution, output),
+            FrameFormat::RgbA8888 | FrameFormat::ARgb8888 => {
+                convert_packed_rgb_to_i420_direct(self.source_frame_format, &self.buffer, self.resolution, output)
+            }
+            FrameFormat::I420 => {
+                let size = i420_predicted_size(self.resolution);
+                if self.buffer.len() < size {
+                    return Err(NokhwaError::ProcessFrameError {
+                        src: FrameFormat::I420,
+                        destination: "I420".to_string(),
+                        error: format!("Bad buffer length: expected {size} bytes, got {}", self.buffer.len()),
+                    });
+                }
+                if output.len() < size {
+                    return Err(NokhwaError::BufferTooSmall {
+                        needed: size,
+                        got: output.len(),
+                    });
+                }
+                output[..size].copy_from_slice(&self.buffer[..size]);
+                Ok(())
+            }
+            other => Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: "I420".to_string(),
+                error: "Unsupported source format".to_string(),
+            }),
+        }
+    }
+
+    /// Converts an [`FrameFormat::Nv12`] frame into [`FrameFormat::Nv21`], see [`nv12_to_nv21`]. An NV21 frame is
+    /// returned as is. Like [`crop`](FrameBuffer::crop), the result keeps everything but the dma-buf.
+    /// # Errors
+    /// If the frame is in any other format, or is not a valid NV12 frame, this will error.
+    pub fn to_nv21(&self) -> NokhwaResult<FrameBuffer> {
+        match self.source_frame_format {
+            FrameFormat::Nv12 => self.with_converted_nv(FrameFormat::Nv21, nv12_to_nv21),
+            FrameFormat::Nv21 => Ok(self.clone()),
+            other => Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: FrameFormat::Nv21.to_string(),
+                error: "Unsupported source format".to_string(),
+            }),
+        }
+    }
+
+    /// Converts an [`FrameFormat::Nv21`] frame into [`FrameFormat::Nv12`], the inverse of
+    /// [`to_nv21`](FrameBuffer::to_nv21). An NV12 frame is returned as is.
+    /// # Errors
+    /// If the frame is in any other format, or is not a valid NV21 frame, this will error.
+    pub fn to_nv12(&self) -> NokhwaResult<FrameBuffer> {
+        match self.source_frame_format {
+            FrameFormat::Nv21 => self.with_converted_nv(FrameFormat::Nv12, nv21_to_nv12),
+            FrameFormat::Nv12 => Ok(self.clone()),
+            other => Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: FrameFormat::Nv12.to_string(),
+                error: "Unsupported source format".to_string(),
+            }),
+        }
+    }
+
+    fn with_converted_nv(
+        &self,
+        frame_format: FrameFormat,
+        convert: fn(&[u8], Resolution, &mut [u8]) -> NokhwaResult<()>,
+    ) -> NokhwaResult<FrameBuffer> {
+        let mut converted = vec![0; nv12_predicted_size(self.resolution)];
+        convert(&self.buffer, self.resolution, &mut converted)?;
+        Ok(FrameBuffer {
+            buffer: Bytes::from(converted),
+            timestamp: self.timestamp,
+            raw_timestamp: self.raw_timestamp,
+            color_range: self.color_range,
+            transfer_function: self.transfer_function,
+            scan_type: self.scan_type,
+            ..FrameBuffer::new(self.resolution, &[], frame_format)
+        })
+    }
+
+    /// Turns an interlaced frame into a progressive one with `method`, using its [`scan_type`](FrameBuffer::scan_type)
+    /// to tell which field came first. Progressive frames are returned as is.
+    ///
+    /// Supports the 8 bit packed formats [`FrameFormat::Luma8`], [`FrameFormat::Rgb888`], [`FrameFormat::RgbA8888`],
+    /// [`FrameFormat::ARgb8888`], [`FrameFormat::Yuyv422`], [`FrameFormat::Uyvy422`] and [`FrameFormat::Yvyu422`].
+    /// Like [`crop`](FrameBuffer::crop), the result keeps everything but the dma-buf.
+    /// # Errors
+    /// If the format is not supported, or the buffer is the wrong length for its resolution, this will error.
+    pub fn deinterlace(&self, method: Deinterlace) -> NokhwaResult<FrameBuffer> {
+        let kept_parity = match self.scan_type {
+            ScanType::Progressive => return Ok(self.clone()),
+            ScanType::InterlacedTff => 0,
+            ScanType::InterlacedBff => 1,
+        };
+        if !matches!(
+            self.source_frame_format,
+            FrameFormat::Luma8
+                | FrameFormat::Rgb888
+                | FrameFormat::RgbA8888
+                | FrameFormat::ARgb8888
+                | FrameFormat::Yuyv422
+                | FrameFormat::Uyvy422
+                | FrameFormat::Yvyu422
+        ) {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.source_frame_format,
+                destination: "Deinterlaced".to_string(),
+                error: "Unsupported source format".to_string(),
+            });
+        }
+        let mut progressive = self.validate_length()?.to_vec();
+        let height = self.resolution.height() as usize;
+        if method == Deinterlace::Bob && height > 1 {
+            let stride = progressive.len() / height;
+            for row in (0..height).filter(|row| row % 2 != kept_parity) {
+                // The kept field's rows on either side, or the only one there is at the top and bottom edges.
+                let above = row.checked_sub(1).unwrap_or(row + 1);
+                let below = if row + 1 < height { row + 1 } else { above };
+                for column in 0..stride {
+                    progressive[row * stride + column] =
+                        progressive[above * stride + column].midpoint(progressive[below * stride + column]);
+                }
+            }
+        }
+
+        Ok(FrameBuffer {
+            buffer: Bytes::from(progressive),
+            timestamp: self.timestamp,
+            raw_timestamp: self.raw_timestamp,
+            color_range: self.color_range,
+            transfer_function: self.transfer_function,
+            ..FrameBuffer::new(self.resolution, &[], self.source_frame_format)
+        })
+    }
+
+    /// Copies out the `size` region whose top left corner is at `origin`, e.g. the centered crop from
+    /// [`Resolution::crop_to_aspect`].
+    ///
+    /// Supports the packed pixel formats [`FrameFormat::Luma8`], [`FrameFormat::Luma16`], [`FrameFormat::Rgb888`],
+    /// [`FrameFormat::RgbA8888`] and [`FrameFormat::ARgb8888`]; decode other formats first. The cropped buffer keeps
+    /// the [timestamp](FrameBuffer::timestamp), [color range](FrameBuffer::color_range),
+    /// [transfer function](FrameBuffer::transfer_function) and [scan type](FrameBuffer::scan_type), but not the
+    /// dma-buf.
+    /// # Errors
+    /// If the format is not supported, the buffer is the wrong length for its resolution, or the region does not
+    /// fit inside the frame, this will error.
+    pub fn crop(&self, origin: (u32, u32), size: Resolution) -> NokhwaResult<FrameBuffer> {
+        let error = |error: String| NokhwaError::ProcessFrameError {
+            src: self.source_frame_format,
+            destination: "Crop".to_string(),
+            error,
+        };
+
+        let bytes_per_pixel = match self.source_frame_format {
+            FrameFormat::Luma8 => 1,
+            FrameFormat::Luma16 => 2,
+            FrameFormat::Rgb888 => 3,
+            FrameFormat::RgbA8888 | FrameFormat::ARgb8888 => 4,
+            _ => return Err(error("Unsupported source format".to_string())),
+        };
+        let data = self.validate_length()?;
+
+        let (x, y) = origin;
+        let fits = |start: u32, len: u32, limit: u32| start.checked_add(len).is_some_and(|end| end <= limit);
+        if !fits(x, size.width(), self.resolution.width()) || !fits(y, size.height(), self.resolution.height()) {
+            return Err(error(format!(
+                "{size} at ({x}, {y}) does not fit inside {}",
+                self.resolution
+            )));
+        }
+
+        // Starting on an odd row swaps which field is on top.
+        let scan_type = match (self.scan_type, y % 2) {
+            (ScanType::InterlacedTff, 1) => ScanType::InterlacedBff,
+            (ScanType::InterlacedBff, 1) => ScanType::InterlacedTff,
+            (scan_type, _) => scan_type,
+        };
+        let stride = self.resolution.width() as usize * bytes_per_pixel;
+        let row_len = size.width() as usize * bytes_per_pixel;
+        let mut cropped = Vec::with_capacity(row_len * size.height() as usize);
+        for row in data.chunks_exact(stride).skip(y as usize).take(size.height() as usize) {
+            let start = x as usize * bytes_per_pixel;
+            cropped.extend_from_slice(&row[start..start + row_len]);
+        }
+
+        Ok(FrameBuffer {
+            buffer: Bytes::from(cropped),
+            timestamp: self.timestamp,
+            raw_timestamp: self.raw_timestamp,
+            color_range: self.color_range,
+            transfer_function: self.transfer_function,
+            scan_type,
+            ..FrameBuffer::new(size, &[], self.source_frame_format)
+        })
+    }
+
+    /// Sums the absolute differences between the luma samples of this frame and `other`, read straight from the
+    /// Y samples without converting to RGB. A cheap motion measure: identical frames give `0`.
+    ///
+    /// Supports [`FrameFormat::Luma8`], the planar 4:2:0 formats ([`FrameFormat::Nv12`], [`FrameFormat::Nv21`],
+    /// [`FrameFormat::I420`], [`FrameFormat::Yv12`]) and the packed 4:2:2 formats ([`FrameFormat::Yuyv422`],
+    /// [`FrameFormat::Yvyu422`], [`FrameFormat::Uyvy422`]).
+    /// # Errors
+    /// If the frames differ in format or resolution, the format is not supported, or either buffer is the wrong
+    /// length for its resolution, this will error.
+    pub fn frame_difference(&self, other: &FrameBuffer) -> NokhwaResult<u64> {
+        if self.source_frame_format != other.source_frame_format || self.resolution != other.resolution {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.source_frame_format,
+                destination: "Frame Difference".to_string(),
+                error: format!(
+                    "Mismatched frames: {} {} and {} {}",
+                    self.resolution, self.source_frame_format, other.resolution, other.source_frame_format
+                ),
+            });
+        }
+
+        let (ours, step) = self.luma_samples("Frame Difference")?;
+        let (theirs, _) = other.luma_samples("Frame Difference")?;
+
+        Ok(ours
+            .iter()
+            .step_by(step)
+            .zip(theirs.iter().step_by(step))
+            .map(|(a, b)| u64::from(a.abs_diff(*b)))
+            .sum())
+    }
+
+    // A cheap fingerprint of this frame for duplicate detection: its luma (or raw bytes, for formats without
+    // separable luma) sampled at about `FINGERPRINT_SAMPLES` evenly spaced points, plus its length.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        let (samples, step) = self.luma_samples("Fingerprint").unwrap_or((&self.buffer, 1));
+        let stride = step * (samples.len() / step / FINGERPRINT_SAMPLES).max(1);
+
+        let mut hasher = DefaultHasher::new();
+        samples.iter().step_by(stride).for_each(|sample| hasher.write_u8(*sample));
+        hasher.write_usize(self.buffer.len());
+        hasher.finish()
+    }
+
+    // The luma samples of this buffer: a slice starting at the first Y sample, and the distance between samples.
+    fn luma_samples(&self, destination: &str) -> NokhwaResult<(&[u8], usize)> {
+        let data = self.validate_length()?;
+        let first_plane = frame_format_layout(self.source_frame_format)
+            .map(|layout| layout.first_plane_size(self.resolution))
+            .unwrap_or_default();
+
+        match self.source_frame_format {
+            FrameFormat::Luma8 | FrameFormat::Nv12 | FrameFormat::Nv21 | FrameFormat::I420 | FrameFormat::Yv12 => {
+                Ok((&data[..first_plane], 1))
+            }
+            FrameFormat::Yuyv422 | FrameFormat::Yvyu422 => Ok((&data[..first_plane], 2)),
+            FrameFormat::Uyvy422 => Ok((&data[1..first_plane], 2)),
+            other => Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: destination.to_string(),
+                error: "Unsupported source format".to_string(),
+            }),
+        }
+    }
+
+    // libjpeg always decodes as full range, so a limited range override is applied to its output.
+    #[cfg(feature = "mjpeg")]
+    fn mjpeg_to_rgb(&self) -> NokhwaResult<Vec<u8>> {
+        let mut rgb = mjpeg_to_rgb(&self.buffer, false)?;
+        if self.color_range() == ColorRange::Limited {
+            buf_expand_limited_range(&mut rgb);
+        }
+        Ok(rgb)
+    }
+
+    // Checks that a decoded image for `to_planar_rgb` holds exactly as many bytes as this buffer's resolution needs,
+    // since a JPEG carries its own dimensions.
+    #[cfg(feature = "mjpeg")]
+    fn exact_len<'a>(&self, data: &'a [u8], len: usize) -> NokhwaResult<&'a [u8]> {
+        if data.len() != len {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.source_frame_format,
+                destination: "Planar RGB".to_string(),
+                error: format!("Bad buffer length: expected {len} bytes, got {}", data.len()),
+            });
+        }
+        Ok(data)
+    }
+}
+
+// A decoded image of some decoder's output type, and how many bytes it holds.
+type CachedImage = (Arc<dyn Any + Send + Sync>, usize);
+
+/// A [`FrameBuffer`] that remembers what it has been decoded to, for when one frame is decoded several times, e.g.
+/// to RGB for display and to Luma for detection.
+///
+/// Each decoder type is run at most once: decoding again with the same [`Decoder`] type returns the cached image,
+/// while decoding with a different type decodes the shared source bytes again. Results are keyed by the decoder's
+/// type alone, so two instances of a stateful decoder configured differently share one cache entry.
+///
+/// # Memory
+/// Every cached image is kept, in full, until the `CachedBuffer` is dropped or [`CachedBuffer::clear`]ed, on top of
+/// the source bytes. A 1920x1080 frame decoded to RGB and Luma holds about 8 MiB of decoded data, so do not keep
+/// many of these around, e.g. in a frame history. [`CachedBuffer::cached_bytes`] reports the current cost.
+#[derive(Debug)]
+pub struct CachedBuffer {
+    buffer: FrameBuffer,
+    decoded: Mutex<HashMap<TypeId, CachedImage>>,
+}
+
+impl CachedBuffer {
+    /// Wraps `buffer`, with nothing decoded yet.
+    #[must_use]
+    pub fn new(buffer: FrameBuffer) -> Self {
+        CachedBuffer {
+            buffer,
+            decoded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The source buffer.
+    #[must_use]
+    pub fn buffer(&self) -> &FrameBuffer {
+        &self.buffer
+    }
+
+    /// Unwraps the source buffer, dropping everything decoded from it.
+    #[must_use]
+    pub fn into_inner(self) -> FrameBuffer {
+        self.buffer
+    }
+
+    /// Decodes the buffer with `D`, or returns the image a previous call decoded with `D`.
+    /// # Errors
+    /// If decoding fails, this will error. Failures are not cached.
+    pub fn decode<D>(&self) -> NokhwaResult<Arc<ImageBuffer<D::OutputPixels, D::PixelContainer>>>
+    where
+        D: StaticDecoder + 'static,
+        ImageBuffer<D::OutputPixels, D::PixelContainer>: Send + Sync + 'static,
+    {
+        self.cached_or_else::<D>(|| D::decode_static(&self.buffer))
+    }
+
+    /// Decodes the buffer with `decoder`, or returns the image a previous call decoded with a `D`.
+    /// # Errors
+    /// If decoding fails, this will error. Failures are not cached.
+    pub fn decode_with<D>(&self, decoder: &mut D) -> NokhwaResult<Arc<ImageBuffer<D::OutputPixels, D::PixelContainer>>>
+    where
+        D: Decoder + 'static,
+        ImageBuffer<D::OutputPixels, D::PixelContainer>: Send + Sync + 'static,
+    {
+        self.cached_or_else::<D>(|| decoder.decode(&self.buffer))
+    }
+
+    /// Whether an image decoded with `D` is cached.
+    #[must_use]
+    pub fn is_cached<D: Decoder + 'static>(&self) -> bool {
+        self.decoded
+            .lock()
+            .is_ok_and(|decoded| decoded.contains_key(&TypeId::of::<D>()))
+    }
+
+    /// How many bytes of decoded images are cached, not counting the source buffer.
+    #[must_use]
+    pub fn cached_bytes(&self) -> usize {
+        self.decoded
+            .lock()
+            .map_or(0, |decoded| decoded.values().map(|(_, bytes)| bytes).sum())
+    }
+
+    /// Drops every cached image. Images already handed out stay alive until their last [`Arc`] is dropped.
+    pub fn clear(&self) {
+        if let Ok(mut decoded) = self.decoded.lock() {
+            decoded.clear();
+        }
+    }
+
+    // The lock is not held while decoding, so decoders of different types can run at once. If two threads decode
+    // with the same type at once, both decode and the first result to land is kept.
+    fn cached_or_else<D>(
+        &self,
+        decode: impl FnOnce() -> NokhwaResult<ImageBuffer<D::OutputPixels, D::PixelContainer>>,
+    ) -> NokhwaResult<Arc<ImageBuffer<D::OutputPixels, D::PixelContainer>>>
+    where
+        D: Decoder + 'static,
+        ImageBuffer<D::OutputPixels, D::PixelContainer>: Send + Sync + 'static,
+    {
+        let key = TypeId::of::<D>();
+        if let Some(image) = self.cached::<D>(key) {
+            return Ok(image);
+        }
+
+        let image = decode()?;
+        let bytes = image.as_raw().len() * D::subpixel_size();
+        let image = Arc::new(image);
+
+        let Ok(mut decoded) = self.decoded.lock() else {
+            return Ok(image);
+        };
+        let (cached, _) = decoded.entry(key).or_insert_with(|| (Arc::clone(&image) as Arc<dyn Any + Send + Sync>, bytes));
+        Ok(Arc::clone(cached).downcast().unwrap_or(image))
+    }
+
+    fn cached<D>(&self, key: TypeId) -> Option<Arc<ImageBuffer<D::OutputPixels, D::PixelContainer>>>
+    where
+        D: Decoder + 'static,
+        ImageBuffer<D::OutputPixels, D::PixelContainer>: Send + Sync + 'static,
+    {
+        let decoded = self.decoded.lock().ok()?;
+        let (image, _) = decoded.get(&key)?;
+        Arc::clone(image).downcast().ok()
+    }
+}
+
+impl From<FrameBuffer> for CachedBuffer {
+    fn from(buffer: FrameBuffer) -> Self {
+        CachedBuffer::new(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::buf_planar_rgb_to_rgb;
+    use crate::pixel_format::{LumaAFormat, PassthroughFormat};
+    use image::Rgba;
+
+    #[test]
+    fn requires_decode_skips_compressed_and_rgb888() {
+        let resolution = Resolution::new(2, 2);
+        let decode = |format| FrameBuffer::new(resolution, &[0; 16], format).requires_decode();
+
+        assert!(!decode(FrameFormat::MJpeg));
+        assert!(!decode(FrameFormat::H264));
+        assert!(!decode(FrameFormat::Rgb888));
+        assert!(decode(FrameFormat::RgbA8888));
+        assert!(decode(FrameFormat::Yuyv422));
+        assert!(decode(FrameFormat::Nv12));
+    }
+
+    #[test]
+    fn validate_length_rejects_short_nv12_buffers() {
+        let resolution = Resolution::new(4, 2);
+        let short = FrameBuffer::new(resolution, &[0; 11], FrameFormat::Nv12);
+        match short.validate_length() {
+            Err(NokhwaError::StructureError { error, .. }) => {
+                assert!(error.ends_with("needs 12 bytes, got 11"), "{error}");
+            }
+            other => panic!("expected a StructureError, got {other:?}"),
+        }
+
+        // Driver padding past the image is trimmed off.
+        let padded = FrameBuffer::new(resolution, &[0; 16], FrameFormat::Nv12);
+        assert_eq!(padded.validate_length().unwrap().len(), 12);
+
+        // Odd sizes keep a chroma sample for their last row and column.
+        let odd = Resolution::new(1, 1);
+        assert!(FrameBuffer::new(odd, &[0; 1], FrameFormat::Nv12).validate_length().is_err());
+        assert_eq!(FrameBuffer::new(odd, &[0; 3], FrameFormat::Nv12).validate_length().unwrap().len(), 3);
+
+        let jpeg = FrameBuffer::new(resolution, &[0; 5], FrameFormat::MJpeg);
+        assert_eq!(jpeg.validate_length().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn bob_fills_the_other_field_from_the_kept_one() {
+        // A 1x4 Luma8 frame: rows 0 and 2 are the top field, rows 1 and 3 the bottom one.
+        let resolution = Resolution::new(1, 4);
+        let frame = FrameBuffer::new(resolution, &[10, 200, 30, 200], FrameFormat::Luma8);
+
+        let top_first = frame.clone().with_scan_type(ScanType::InterlacedTff);
+        assert_eq!(top_first.deinterlace(Deinterlace::Bob).unwrap().buffer(), &[10, 20, 30, 30]);
+        assert_eq!(top_first.deinterlace(Deinterlace::Weave).unwrap().buffer(), frame.buffer());
+
+        let bottom_first = frame.clone().with_scan_type(ScanType::InterlacedBff);
+        assert_eq!(bottom_first.deinterlace(Deinterlace::Bob).unwrap().buffer(), &[200, 200, 200, 200]);
+
+        let progressive = frame.deinterlace(Deinterlace::Bob).unwrap();
+        assert_eq!(progressive.buffer(), &[10, 200, 30, 200]);
+        assert_eq!(progressive.scan_type(), ScanType::Progressive);
+    }
+
+    #[test]
+    fn i420_planes_at_720p() {
+        let resolution = Resolution::new(1280, 720);
+        let frame = FrameBuffer::new(resolution, &[0; 1280 * 720 * 2], FrameFormat::Yuyv422);
+
+        let (planes, layout) = frame.to_i420_planes().unwrap();
+        assert_eq!(planes.len(), 1280 * 720 * 3 / 2);
+        assert_eq!(
+            layout,
+            PlaneLayout {
+                y_offset: 0,
+                y_stride: 1280,
+                u_offset: 921_600,
+                u_stride: 640,
+                v_offset: 1_152_000,
+                v_stride: 640,
+            }
+        );
+    }
+
+    #[test]
+    fn to_i420_matches_to_i420_buffer() {
+        let resolution = Resolution::new(4, 2);
+        let pattern = |len: usize| (0..len).map(|index| (index * 37 % 256) as u8).collect::<Vec<_>>();
+
+        for format in [FrameFormat::Nv12, FrameFormat::Yuyv422, FrameFormat::RgbA8888, FrameFormat::ARgb8888] {
+            let frame = FrameBuffer::new(resolution, &pattern(format.byte_size(resolution).unwrap()), format);
+            let mut output = vec![0; i420_predicted_size(resolution)];
+            frame.to_i420_buffer(&mut output).unwrap();
+            assert_eq!(frame.to_i420().unwrap(), output, "{format}");
+        }
+    }
+
+    #[test]
+    fn packed_rgb_to_i420_uses_limited_range() {
+        let resolution = Resolution::new(2, 2);
+        let white = FrameBuffer::new(resolution, &[255; 16], FrameFormat::RgbA8888).to_i420().unwrap();
+        assert_eq!(white, [235, 235, 235, 235, 128, 128]);
+
+        // Opaque pure red, with alpha first.
+        let red = FrameBuffer::new(resolution, &[255, 255, 0, 0].repeat(4), FrameFormat::ARgb8888)
+            .to_i420()
+            .unwrap();
+        assert_eq!(red, [82, 82, 82, 82, 90, 240]);
+    }
+
+    #[test]
+    fn crop_copies_the_centered_square() {
+        // Each luma sample is its column, plus ten times its row.
+        let resolution = Resolution::new(4, 2);
+        let frame = FrameBuffer::new(resolution, &[0, 1, 2, 3, 10, 11, 12, 13], FrameFormat::Luma8);
+
+        let (origin, size) = resolution.crop_to_aspect(1.0);
+        let square = frame.crop(origin, size).unwrap();
+        assert_eq!(square.resolution(), Resolution::new(2, 2));
+        assert_eq!(square.buffer(), &[1, 2, 11, 12]);
+
+        assert!(frame.crop((3, 0), size).is_err());
+        assert!(FrameBuffer::new(resolution, &[0; 16], FrameFormat::Yuyv422).crop(origin, size).is_err());
+    }
+
+    #[test]
+    fn rgb888_into_rgb_image_moves_the_bytes() {
+        let resolution = Resolution::new(2, 2);
+        let frame = FrameBuffer::new(resolution, &[7; 12], FrameFormat::Rgb888);
+        let pointer = frame.buffer().as_ptr();
+
+        let image = frame.into_rgb_image().unwrap();
+        assert_eq!(image.as_raw().as_ptr(), pointer);
+        assert_eq!(image.as_raw(), &[7; 12]);
+
+        // A clone still holding the bytes forces a copy.
+        let frame = FrameBuffer::new(resolution, &[7; 12], FrameFormat::Rgb888);
+        let clone = frame.clone();
+        let image = frame.into_rgb_image().unwrap();
+        assert_ne!(image.as_raw().as_ptr(), clone.buffer().as_ptr());
+    }
+
+    #[test]
+    fn clones_share_the_bytes_and_keep_everything_else() {
+        let raw_timestamp = RawTimestamp::new(TimestampClock::Monotonic, Duration::from_millis(1500));
+        let yuyv = [16, 128, 235, 128, 16, 128, 235, 128];
+        let frame = FrameBuffer::new(Resolution::new(2, 2), &yuyv, FrameFormat::Yuyv422)
+            .with_timestamp(Instant::now())
+            .with_raw_timestamp(raw_timestamp, Duration::from_millis(1600))
+            .with_color_range(ColorRange::Full)
+            .with_transfer_function(TransferFunction::Linear)
+            .with_scan_type(ScanType::InterlacedBff)
+            .with_duplicate_of_previous(true);
+        #[cfg(unix)]
+        let frame = frame.with_dmabuf_fd(7);
+
+        let clone = frame.clone();
+        assert_eq!(clone, frame);
+        assert_eq!(clone.buffer().as_ptr(), frame.buffer().as_ptr());
+        assert_eq!(clone.source_frame_format(), FrameFormat::Yuyv422);
+        assert_eq!(clone.resolution(), Resolution::new(2, 2));
+        assert_eq!(clone.timestamp(), frame.timestamp());
+        assert_eq!(clone.raw_timestamp(), Some(raw_timestamp));
+        assert_eq!(clone.color_range(), ColorRange::Full);
+        assert_eq!(clone.transfer_function(), TransferFunction::Linear);
+        assert_eq!(clone.scan_type(), ScanType::InterlacedBff);
+        assert!(clone.is_duplicate_of_previous());
+        #[cfg(unix)]
+        assert_eq!(clone.dmabuf_fd(), Some(7));
+    }
+
+    #[test]
+    fn decode_packed_rows_are_width_times_channels() {
+        let resolution = Resolution::new(3, 2);
+        let rgb = (0..18).collect::<Vec<u8>>();
+        let (data, decoded, stride) = FrameBuffer::new(resolution, &rgb, FrameFormat::Rgb888)
+            .decode_packed::<PassthroughFormat<Rgb<u8>>>()
+            .unwrap();
+        assert_eq!((decoded, stride), (resolution, 9));
+        assert_eq!(data, rgb);
+
+        let rgba = FrameBuffer::new(resolution, &[0; 24], FrameFormat::RgbA8888);
+        assert_eq!(rgba.decode_packed::<PassthroughFormat<Rgba<u8>>>().unwrap().2, 12);
+        let luma = FrameBuffer::new(resolution, &[0; 6], FrameFormat::Luma8);
+        let (data, _, stride) = luma.decode_packed::<LumaAFormat>().unwrap();
+        assert_eq!((data.len(), stride), (12, 6));
+    }
+
+    #[test]
+    fn aligned_decodes_start_on_a_simd_boundary() {
+        use crate::aligned::SIMD_ALIGNMENT;
+
+        // Sizes that are not a multiple of the alignment, so only the allocation can line them up.
+        for width in [1, 3, 7, 11] {
+            let rgb = (0..width * 3).map(|value| u8::try_from(value).unwrap()).collect::<Vec<u8>>();
+            let frame = FrameBuffer::new(Resolution::new(width, 1), &rgb, FrameFormat::Rgb888);
+            let aligned = frame.decode_image_aligned::<PassthroughFormat<Rgb<u8>>>().unwrap();
+
+            assert_eq!(aligned.as_ptr() as usize % SIMD_ALIGNMENT, 0, "{width}x1");
+            assert_eq!(&aligned[..], rgb.as_slice());
+        }
+        assert_eq!(AlignedBuf::from_slice(&[1; 5]).as_ptr() as usize % SIMD_ALIGNMENT, 0);
+    }
+
+    #[test]
+    fn planar_rgb_round_trips_exactly() {
+        let resolution = Resolution::new(3, 2);
+        let rgb = (0..18).map(|value| value * 13).collect::<Vec<u8>>();
+
+        let planar = FrameBuffer::new(resolution, &rgb, FrameFormat::Rgb888).to_planar_rgb().unwrap();
+        assert_eq!(&planar[..6], &[0, 39, 78, 117, 156, 195]);
+        let mut interleaved = vec![0; rgb.len()];
+        buf_planar_rgb_to_rgb(&planar, &mut interleaved).unwrap();
+        assert_eq!(interleaved, rgb);
+
+        assert!(buf_planar_rgb_to_rgb(&planar[..17], &mut interleaved).is_err());
+    }
+
+    #[test]
+    fn decoded_buffers_are_srgb_unless_the_backend_says_otherwise() {
+        let resolution = Resolution::new(2, 2);
+        let nv21 = FrameBuffer::new(resolution, &[16, 16, 16, 16, 128, 128], FrameFormat::Nv21);
+        assert_eq!(nv21.transfer_function(), TransferFunction::Srgb);
+        assert!(nv21.is_srgb());
+        assert!(nv21.to_nv12().unwrap().is_srgb());
+        let rgb = FrameBuffer::new(resolution, &[0; 12], FrameFormat::Rgb888);
+        assert!(rgb.crop((0, 0), Resolution::new(1, 1)).unwrap().is_srgb());
+
+        let linear = nv21.with_transfer_function(TransferFunction::Linear);
+        assert!(!linear.is_srgb());
+        assert_eq!(linear.to_nv12().unwrap().transfer_function(), TransferFunction::Linear);
+    }
+
+    #[test]
+    fn frame_difference_compares_luma_only() {
+        let resolution = Resolution::new(2, 2);
+        let nv12 = |data: &[u8]| FrameBuffer::new(resolution, data, FrameFormat::Nv12);
+        let yuyv = |data: &[u8]| FrameBuffer::new(resolution, data, FrameFormat::Yuyv422);
+
+        let still = nv12(&[10, 20, 30, 40, 128, 128]);
+        assert_eq!(still.frame_difference(&still).unwrap(), 0);
+        assert_eq!(still.frame_difference(&nv12(&[10, 25, 30, 30, 0, 255])).unwrap(), 15);
+
+        let packed = yuyv(&[10, 128, 20, 128, 30, 128, 40, 128]);
+        assert_eq!(packed.frame_difference(&yuyv(&[12, 0, 20, 0, 30, 0, 35, 0])).unwrap(), 7);
+
+        let mismatched = |error: NokhwaError| {
+            matches!(error, NokhwaError::ProcessFrameError { error, .. } if error.starts_with("Mismatched frames"))
+        };
+        assert!(mismatched(still.frame_difference(&packed).unwrap_err()));
+        let wider = FrameBuffer::new(Resolution::new(4, 1), &[10, 20, 30, 40, 128, 128], FrameFormat::Nv12);
+        assert!(mismatched(still.frame_difference(&wider).unwrap_err()));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarrays_are_shaped_hwc_and_chw() {
+        // Each pixel is [column, row, 9], so every sample says where it came from.
+        let resolution = Resolution::new(3, 2);
+        let rgb = (0..2u8).flat_map(|row| (0..3u8).flat_map(move |column| [column, row, 9])).collect::<Vec<_>>();
+        let frame = FrameBuffer::new(resolution, &rgb, FrameFormat::Rgb888);
+
+        let hwc = frame.to_ndarray_hwc().unwrap();
+        assert_eq!(hwc.shape(), [2, 3, 3]);
+        assert_eq!((hwc[[1, 2, 0]], hwc[[1, 2, 1]], hwc[[1, 2, 2]]), (2, 1, 9));
+
+        let chw = frame.to_ndarray_chw().unwrap();
+        assert_eq!(chw.shape(), [3, 2, 3]);
+        assert_eq!((chw[[0, 1, 2]], chw[[1, 1, 2]], chw[[2, 1, 2]]), (2, 1, 9));
+        assert_eq!(chw[[0, 0, 1]], 1);
+    }
+
+    #[cfg(feature = "image-save")]
+    #[test]
+    fn saved_frames_reload_as_the_same_image() {
+        let directory = tempfile::tempdir().unwrap();
+        let rgb = (0..48).map(|value| value * 5).collect::<Vec<u8>>();
+        let frame = FrameBuffer::new(Resolution::new(4, 4), &rgb, FrameFormat::Rgb888);
+
+        let png = directory.path().join("frame.png");
+        frame.save(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!(image::open(&png).unwrap().into_rgb8().into_raw(), rgb);
+
+        let jpeg = directory.path().join("frame.jpg");
+        frame.save_jpeg(&jpeg, 90).unwrap();
+        assert_eq!(image::open(&jpeg).unwrap().into_rgb8().dimensions(), (4, 4));
+
+        assert!(frame.save(directory.path().join("missing").join("frame.png"), image::ImageFormat::Png).is_err());
+    }
+
+    // Copies Luma8 frames as they are, counting how often it runs.
+    #[derive(Default)]
+    struct CountingDecoder {
+        decodes: usize,
+    }
+
+    impl Decoder for CountingDecoder {
+        const ALLOWED_FORMATS: &'static [FrameFormat] = &[FrameFormat::Luma8];
+
+        type OutputPixels = image::Luma<u8>;
+        type PixelContainer = Vec<u8>;
+
+        fn decode(&mut self, buffer: &FrameBuffer) -> NokhwaResult<ImageBuffer<Self::OutputPixels, Vec<u8>>> {
+            self.decodes += 1;
+            let resolution = buffer.resolution();
+            ImageBuffer::from_raw(resolution.width(), resolution.height(), buffer.buffer().to_vec())
+                .ok_or_else(|| NokhwaError::ProcessFrameError {
+                    src: buffer.source_frame_format(),
+                    destination: "Luma8".to_string(),
+                    error: "Bad buffer length".to_string(),
+                })
+        }
+
+        fn decode_buffer(&mut self, buffer: &FrameBuffer, output: &mut [u8]) -> NokhwaResult<()> {
+            self.decodes += 1;
+            output.copy_from_slice(buffer.buffer());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn cached_buffers_decode_once_per_decoder_type() {
+        let cached = CachedBuffer::new(FrameBuffer::new(Resolution::new(2, 2), &[1, 2, 3, 4], FrameFormat::Luma8));
+        let mut decoder = CountingDecoder::default();
+
+        let first = cached.decode_with(&mut decoder).unwrap();
+        let second = cached.decode_with(&mut decoder).unwrap();
+        assert_eq!(decoder.decodes, 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(cached.is_cached::<CountingDecoder>());
+        assert_eq!(cached.cached_bytes(), 4);
+
+        cached.clear();
+        assert!(!cached.is_cached::<CountingDecoder>());
+        cached.decode_with(&mut decoder).unwrap();
+        assert_eq!(decoder.decodes, 2);
+    }
 }
@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+use crate::types::Resolution;
 use std::fmt::{Display, Formatter};
 
 /// Describes a frame format (i.e. how the bytes themselves are encoded). Often called `FourCC`.
@@ -147,6 +148,7 @@ impl FrameFormat {
         FrameFormat::VP9,
         FrameFormat::Yuyv422,
         FrameFormat::Uyvy422,
+        FrameFormat::Yvyu422,
         FrameFormat::Nv12,
         FrameFormat::Nv21,
         FrameFormat::Yv12,
@@ -155,6 +157,98 @@ impl FrameFormat {
     ];
 
     pub const GRAYSCALE: &'static [FrameFormat] = &[FrameFormat::Luma8, FrameFormat::Luma16];
+
+    /// Unprocessed sensor output: Bayer mosaics and high bit depth luma or depth, as opposed to the debayered
+    /// 8-bit formats drivers usually default to.
+    pub const RAW: &'static [FrameFormat] = &[
+        FrameFormat::Bayer8,
+        FrameFormat::Bayer16,
+        FrameFormat::Luma16,
+        FrameFormat::Depth16,
+    ];
+
+    /// The order formats are listed in for people to pick from, see [`display_order`](FrameFormat::display_order):
+    /// uncompressed formats (YUV, then RGB, then grayscale, depth and Bayer) before compressed ones (MJPEG first).
+    /// [`FrameFormat::Custom`] formats come after all of these.
+    pub const DISPLAY_ORDER: &'static [FrameFormat] = &[
+        FrameFormat::Yuyv422,
+        FrameFormat::Uyvy422,
+        FrameFormat::Yvyu422,
+        FrameFormat::Nv12,
+        FrameFormat::Nv21,
+        FrameFormat::I420,
+        FrameFormat::Yv12,
+        FrameFormat::Ayuv444,
+        FrameFormat::Yvu9,
+        FrameFormat::Rgb888,
+        FrameFormat::RgbA8888,
+        FrameFormat::ARgb8888,
+        FrameFormat::Rgb565,
+        FrameFormat::Rgb555,
+        FrameFormat::Rgb332,
+        FrameFormat::Luma8,
+        FrameFormat::Luma16,
+        FrameFormat::Depth16,
+        FrameFormat::Bayer8,
+        FrameFormat::Bayer16,
+        FrameFormat::MJpeg,
+        FrameFormat::H264,
+        FrameFormat::Avc1,
+        FrameFormat::H265,
+        FrameFormat::Av1,
+        FrameFormat::VP8,
+        FrameFormat::VP9,
+        FrameFormat::H263,
+        FrameFormat::Mpeg4,
+        FrameFormat::XVid,
+        FrameFormat::Mpeg2,
+        FrameFormat::Mpeg1,
+    ];
+
+    /// A sort key that lists formats in a stable order regardless of what order the driver reports them in: this
+    /// format's position in [`DISPLAY_ORDER`](FrameFormat::DISPLAY_ORDER). All [`FrameFormat::Custom`] formats share
+    /// the last position.
+    #[must_use]
+    pub fn display_order(self) -> usize {
+        Self::DISPLAY_ORDER
+            .iter()
+            .position(|format| *format == self)
+            .unwrap_or(Self::DISPLAY_ORDER.len())
+    }
+
+    /// Whether this is one of the [`RAW`](FrameFormat::RAW) sensor formats.
+    #[must_use]
+    pub fn is_raw(self) -> bool {
+        Self::RAW.contains(&self)
+    }
+
+    /// Whether this is one of the [`COMPRESSED`](FrameFormat::COMPRESSED) formats, such as MJPEG or H264.
+    #[must_use]
+    pub fn is_compressed(self) -> bool {
+        Self::COMPRESSED.contains(&self)
+    }
+
+    /// The number of bytes one tightly packed frame of this format takes at `resolution`, or `None` for
+    /// compressed (or unknown) formats whose size varies from frame to frame. See [`frame_format_layout`].
+    #[must_use]
+    pub fn byte_size(self, resolution: Resolution) -> Option<usize> {
+        frame_format_layout(self).map(|layout| layout.byte_size(resolution))
+    }
+
+    /// The [`ColorRange`] a camera most likely encodes this format's YUV data in.
+    ///
+    /// MJPEG follows JFIF, which uses the full range, while raw YUV formats (YUYV, NV12 and similar) use the limited
+    /// range of BT.601 video. Formats that are not YUV return [`ColorRange::Full`].
+    #[must_use]
+    pub fn default_color_range(self) -> ColorRange {
+        if Self::CHROMA.contains(&self)
+            || matches!(self, FrameFormat::Yvyu422 | FrameFormat::I420 | FrameFormat::Ayuv444)
+        {
+            ColorRange::Limited
+        } else {
+            ColorRange::Full
+        }
+    }
 }
 
 impl Display for FrameFormat {
@@ -163,9 +257,317 @@ impl Display for FrameFormat {
     }
 }
 
+/// The range of values the luma and chroma samples of a YUV frame span.
+///
+/// Decoding a frame with the wrong range washes out (full decoded as limited) or crushes (limited decoded as full)
+/// its blacks and whites. See [`FrameFormat::default_color_range`] for what is assumed when the backend does not say.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorRange {
+    /// "TV" or "video" range: luma spans 16-235 and chroma 16-240.
+    Limited,
+    /// "PC" or "JPEG" range: every sample spans 0-255.
+    Full,
+}
+
+impl Display for ColorRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// How a frame's samples map to light intensity once decoded to RGB.
+///
+/// This matters when uploading frames to a GPU: sRGB encoded data belongs in an sRGB texture format (e.g. `wgpu`'s
+/// `Rgba8UnormSrgb` or Vulkan's `R8G8B8A8_SRGB`), so sampling and blending happen in linear light, while linear data
+/// belongs in a plain `Unorm` format. Camera output is sRGB encoded unless the backend says otherwise.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransferFunction {
+    /// The sRGB (and BT.709) gamma curve, with BT.709 primaries.
+    #[default]
+    Srgb,
+    /// Samples are proportional to light intensity, as from some depth, infrared or scientific cameras.
+    Linear,
+}
+
+impl Display for TransferFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Whether a frame holds one picture, or two fields captured at different times interleaved line by line, as
+/// analog capture cards and broadcast sources deliver. Interlaced frames show combing on motion unless
+/// [deinterlaced](crate::frame_buffer::FrameBuffer::deinterlace).
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScanType {
+    #[default]
+    Progressive,
+    /// Interlaced, with the top field (the even rows, counting from 0) captured first.
+    InterlacedTff,
+    /// Interlaced, with the bottom field (the odd rows) captured first.
+    InterlacedBff,
+}
+
+impl ScanType {
+    #[must_use]
+    pub fn is_interlaced(self) -> bool {
+        self != ScanType::Progressive
+    }
+}
+
+impl Display for ScanType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// How the samples of an uncompressed [`FrameFormat`] are split across planes.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlaneArrangement {
+    /// All samples of a pixel are stored together in a single plane, e.g. YUYV or RGB.
+    Packed,
+    /// A luma plane followed by one plane of interleaved chroma, e.g. NV12.
+    SemiPlanar,
+    /// A luma plane followed by a separate plane for each chroma channel, e.g. I420.
+    Planar,
+}
+
+impl PlaneArrangement {
+    /// The number of planes a frame with this arrangement has.
+    #[must_use]
+    pub const fn plane_count(self) -> usize {
+        match self {
+            PlaneArrangement::Packed => 1,
+            PlaneArrangement::SemiPlanar => 2,
+            PlaneArrangement::Planar => 3,
+        }
+    }
+}
+
+/// The byte layout of an uncompressed [`FrameFormat`], see [`frame_format_layout`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormatLayout {
+    bits_per_pixel: u32,
+    horizontal_subsampling: u32,
+    vertical_subsampling: u32,
+    arrangement: PlaneArrangement,
+}
+
+impl FormatLayout {
+    /// The average number of bits one pixel takes across all planes, e.g. 12 for NV12 or 16 for YUYV.
+    #[must_use]
+    pub const fn bits_per_pixel(self) -> u32 {
+        self.bits_per_pixel
+    }
+
+    /// How many pixels share one chroma sample, horizontally and vertically: `(2, 2)` for 4:2:0, `(2, 1)` for
+    /// 4:2:2. Formats without chroma (RGB, grayscale, Bayer) are `(1, 1)`.
+    #[must_use]
+    pub const fn subsampling(self) -> (u32, u32) {
+        (self.horizontal_subsampling, self.vertical_subsampling)
+    }
+
+    #[must_use]
+    pub const fn arrangement(self) -> PlaneArrangement {
+        self.arrangement
+    }
+
+    /// The number of bytes one tightly packed frame with this layout takes at `resolution`.
+    ///
+    /// Subsampled chroma is rounded up, so the last column or row of an odd sized frame keeps its chroma: a 1x1
+    /// NV12 frame takes 3 bytes, and a 3x1 YUYV frame 8.
+    #[must_use]
+    pub fn byte_size(self, resolution: Resolution) -> usize {
+        match self.arrangement {
+            PlaneArrangement::Packed => {
+                let width = resolution.width().next_multiple_of(self.horizontal_subsampling) as usize;
+                width * resolution.height() as usize * self.bits_per_pixel as usize / 8
+            }
+            // Every plane holds one byte per sample, and each chroma pixel has a U and a V sample.
+            PlaneArrangement::SemiPlanar | PlaneArrangement::Planar => {
+                let chroma = self.chroma_resolution(resolution);
+                self.first_plane_size(resolution) + 2 * chroma.width() as usize * chroma.height() as usize
+            }
+        }
+    }
+
+    /// The number of bytes the first plane (luma, for semi-planar and planar layouts) takes at `resolution`.
+    /// For packed layouts this is the whole frame.
+    #[must_use]
+    pub fn first_plane_size(self, resolution: Resolution) -> usize {
+        match self.arrangement {
+            PlaneArrangement::Packed => self.byte_size(resolution),
+            PlaneArrangement::SemiPlanar | PlaneArrangement::Planar => {
+                resolution.width() as usize * resolution.height() as usize
+            }
+        }
+    }
+
+    /// The size of the chroma planes of a frame at `resolution`, rounded up, or `resolution` itself for formats that
+    /// are not subsampled.
+    #[must_use]
+    pub fn chroma_resolution(self, resolution: Resolution) -> Resolution {
+        Resolution::new(
+            resolution.width().div_ceil(self.horizontal_subsampling),
+            resolution.height().div_ceil(self.vertical_subsampling),
+        )
+    }
+}
+
+// Generates `frame_format_layout` and `FrameFormat::UNCOMPRESSED` from one table, so the two can't disagree.
+macro_rules! frame_format_layouts {
+    ($( $format:ident => $bits:literal, $horizontal:literal x $vertical:literal, $arrangement:ident; )*) => {
+        /// The byte layout of `frame_format`: its bits per pixel, chroma subsampling and whether it is packed or
+        /// planar. This is the single source of truth for frame sizes, see [`FrameFormat::byte_size`].
+        ///
+        /// Returns `None` for compressed and [`FrameFormat::Custom`] formats, whose layout is unknown.
+        #[must_use]
+        pub const fn frame_format_layout(frame_format: FrameFormat) -> Option<FormatLayout> {
+            match frame_format {
+                $(
+                    FrameFormat::$format => Some(FormatLayout {
+                        bits_per_pixel: $bits,
+                        horizontal_subsampling: $horizontal,
+                        vertical_subsampling: $vertical,
+                        arrangement: PlaneArrangement::$arrangement,
+                    }),
+                )*
+                _ => None,
+            }
+        }
+
+        impl FrameFormat {
+            /// Every format with a known [`FormatLayout`], see [`frame_format_layout`].
+            pub const UNCOMPRESSED: &'static [FrameFormat] = &[$( FrameFormat::$format, )*];
+        }
+    };
+}
+
+frame_format_layouts! {
+    Ayuv444 => 32, 1 x 1, Packed;
+    Yuyv422 => 16, 2 x 1, Packed;
+    Uyvy422 => 16, 2 x 1, Packed;
+    Yvyu422 => 16, 2 x 1, Packed;
+    Yv12 => 12, 2 x 2, Planar;
+    Nv12 => 12, 2 x 2, SemiPlanar;
+    Nv21 => 12, 2 x 2, SemiPlanar;
+    I420 => 12, 2 x 2, Planar;
+    Yvu9 => 9, 4 x 4, Planar;
+    Luma8 => 8, 1 x 1, Packed;
+    Luma16 => 16, 1 x 1, Packed;
+    Depth16 => 16, 1 x 1, Packed;
+    Rgb332 => 8, 1 x 1, Packed;
+    Rgb555 => 16, 1 x 1, Packed;
+    Rgb565 => 16, 1 x 1, Packed;
+    Rgb888 => 24, 1 x 1, Packed;
+    RgbA8888 => 32, 1 x 1, Packed;
+    ARgb8888 => 32, 1 x 1, Packed;
+    Bayer8 => 8, 1 x 1, Packed;
+    Bayer16 => 16, 1 x 1, Packed;
+}
+
+/// Conversions between [`FrameFormat`] and `FFmpeg`'s pixel formats and codec ids, for handing frames to
+/// `ffmpeg-sys-next`.
+#[cfg(feature = "interop-ffmpeg")]
+pub mod ffmpeg {
+    use super::FrameFormat;
+    use ffmpeg_sys_next::{AVCodecID, AVPixelFormat};
+
+    /// The `AVPixelFormat` of an uncompressed [`FrameFormat`]. Compressed formats have a codec id instead,
+    /// see [`to_av_codec_id`].
+    #[must_use]
+    pub fn to_av_pix_fmt(frame_format: FrameFormat) -> Option<AVPixelFormat> {
+        let pix_fmt = match frame_format {
+            FrameFormat::Yuyv422 => AVPixelFormat::AV_PIX_FMT_YUYV422,
+            FrameFormat::Uyvy422 => AVPixelFormat::AV_PIX_FMT_UYVY422,
+            FrameFormat::Yvyu422 => AVPixelFormat::AV_PIX_FMT_YVYU422,
+            FrameFormat::Nv12 => AVPixelFormat::AV_PIX_FMT_NV12,
+            FrameFormat::Nv21 => AVPixelFormat::AV_PIX_FMT_NV21,
+            FrameFormat::I420 => AVPixelFormat::AV_PIX_FMT_YUV420P,
+            FrameFormat::Luma8 => AVPixelFormat::AV_PIX_FMT_GRAY8,
+            FrameFormat::Luma16 => AVPixelFormat::AV_PIX_FMT_GRAY16LE,
+            FrameFormat::Rgb332 => AVPixelFormat::AV_PIX_FMT_RGB8,
+            FrameFormat::Rgb555 => AVPixelFormat::AV_PIX_FMT_RGB555LE,
+            FrameFormat::Rgb565 => AVPixelFormat::AV_PIX_FMT_RGB565LE,
+            FrameFormat::Rgb888 => AVPixelFormat::AV_PIX_FMT_RGB24,
+            FrameFormat::RgbA8888 => AVPixelFormat::AV_PIX_FMT_RGBA,
+            FrameFormat::ARgb8888 => AVPixelFormat::AV_PIX_FMT_ARGB,
+            _ => return None,
+        };
+        Some(pix_fmt)
+    }
+
+    /// The reverse of [`to_av_pix_fmt`].
+    #[must_use]
+    pub fn from_av_pix_fmt(pix_fmt: AVPixelFormat) -> Option<FrameFormat> {
+        let frame_format = match pix_fmt {
+            AVPixelFormat::AV_PIX_FMT_YUYV422 => FrameFormat::Yuyv422,
+            AVPixelFormat::AV_PIX_FMT_UYVY422 => FrameFormat::Uyvy422,
+            AVPixelFormat::AV_PIX_FMT_YVYU422 => FrameFormat::Yvyu422,
+            AVPixelFormat::AV_PIX_FMT_NV12 => FrameFormat::Nv12,
+            AVPixelFormat::AV_PIX_FMT_NV21 => FrameFormat::Nv21,
+            AVPixelFormat::AV_PIX_FMT_YUV420P => FrameFormat::I420,
+            AVPixelFormat::AV_PIX_FMT_GRAY8 => FrameFormat::Luma8,
+            AVPixelFormat::AV_PIX_FMT_GRAY16LE => FrameFormat::Luma16,
+            AVPixelFormat::AV_PIX_FMT_RGB8 => FrameFormat::Rgb332,
+            AVPixelFormat::AV_PIX_FMT_RGB555LE => FrameFormat::Rgb555,
+            AVPixelFormat::AV_PIX_FMT_RGB565LE => FrameFormat::Rgb565,
+            AVPixelFormat::AV_PIX_FMT_RGB24 => FrameFormat::Rgb888,
+            AVPixelFormat::AV_PIX_FMT_RGBA => FrameFormat::RgbA8888,
+            AVPixelFormat::AV_PIX_FMT_ARGB => FrameFormat::ARgb8888,
+            _ => return None,
+        };
+        Some(frame_format)
+    }
+
+    /// The `AVCodecID` of a compressed [`FrameFormat`], e.g. `AV_CODEC_ID_MJPEG` for [`FrameFormat::MJpeg`].
+    #[must_use]
+    pub fn to_av_codec_id(frame_format: FrameFormat) -> Option<AVCodecID> {
+        let codec_id = match frame_format {
+            FrameFormat::MJpeg => AVCodecID::AV_CODEC_ID_MJPEG,
+            FrameFormat::H263 => AVCodecID::AV_CODEC_ID_H263,
+            FrameFormat::H264 | FrameFormat::Avc1 => AVCodecID::AV_CODEC_ID_H264,
+            FrameFormat::H265 => AVCodecID::AV_CODEC_ID_HEVC,
+            FrameFormat::Av1 => AVCodecID::AV_CODEC_ID_AV1,
+            FrameFormat::Mpeg1 => AVCodecID::AV_CODEC_ID_MPEG1VIDEO,
+            FrameFormat::Mpeg2 => AVCodecID::AV_CODEC_ID_MPEG2VIDEO,
+            FrameFormat::Mpeg4 | FrameFormat::XVid => AVCodecID::AV_CODEC_ID_MPEG4,
+            FrameFormat::VP8 => AVCodecID::AV_CODEC_ID_VP8,
+            FrameFormat::VP9 => AVCodecID::AV_CODEC_ID_VP9,
+            _ => return None,
+        };
+        Some(codec_id)
+    }
+
+    /// The reverse of [`to_av_codec_id`]. H264 is always [`FrameFormat::H264`], as the codec id does not say
+    /// whether the stream uses start codes or length prefixes.
+    #[must_use]
+    pub fn from_av_codec_id(codec_id: AVCodecID) -> Option<FrameFormat> {
+        let frame_format = match codec_id {
+            AVCodecID::AV_CODEC_ID_MJPEG => FrameFormat::MJpeg,
+            AVCodecID::AV_CODEC_ID_H263 => FrameFormat::H263,
+            AVCodecID::AV_CODEC_ID_H264 => FrameFormat::H264,
+            AVCodecID::AV_CODEC_ID_HEVC => FrameFormat::H265,
+            AVCodecID::AV_CODEC_ID_AV1 => FrameFormat::Av1,
+            AVCodecID::AV_CODEC_ID_MPEG1VIDEO => FrameFormat::Mpeg1,
+            AVCodecID::AV_CODEC_ID_MPEG2VIDEO => FrameFormat::Mpeg2,
+            AVCodecID::AV_CODEC_ID_MPEG4 => FrameFormat::Mpeg4,
+            AVCodecID::AV_CODEC_ID_VP8 => FrameFormat::VP8,
+            AVCodecID::AV_CODEC_ID_VP9 => FrameFormat::VP9,
+            _ => return None,
+        };
+        Some(frame_format)
+    }
+}
+
 #[macro_export]
 macro_rules! define_back_and_fourth_frame_format {
-    ($fourcc_type:ty, { $( $frame_format:expr => $value:literal, )* }, $func_u8_8_to_fcc:expr, $func_fcc_to_u8_8:expr, $value_to_fcc_type:expr) => {
+    ($fourcc_type:ty, { $( $frame_format:path => $value:literal, )* }, $func_u8_8_to_fcc:expr, $func_fcc_to_u8_8:expr, $value_to_fcc_type:expr) => {
         pub struct FrameFormatIntermediate(pub $fourcc_type);
 
         impl FrameFormatIntermediate {
@@ -174,15 +576,16 @@ macro_rules! define_back_and_fourth_frame_format {
                     $(
                         $frame_format => Some(Self($value_to_fcc_type($value))),
                     )*
-                    FrameFormat::Custom(cv) => Some($func_u8_8_to_fcc(cv))
+                    FrameFormat::Custom(cv) => Some($func_u8_8_to_fcc(cv)),
+                    #[allow(unreachable_patterns)]
                     _ => None,
                 }
             }
 
             pub fn into_frame_format(fourcc: $fourcc_type) -> FrameFormat {
-                match fourcc.0 {
+                match fourcc {
                     $(
-                         $value => $frame_format,
+                        fcc if fcc == $value_to_fcc_type($value) => $frame_format,
                     )*
                     cv => FrameFormat::Custom($func_fcc_to_u8_8(cv)),
                 }
@@ -190,3 +593,110 @@ macro_rules! define_back_and_fourth_frame_format {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HD: Resolution = Resolution::new(1280, 720);
+
+    fn u8_8_to_fourcc(u8_8: [u8; 8]) -> FrameFormatIntermediate {
+        FrameFormatIntermediate([u8_8[0], u8_8[1], u8_8[2], u8_8[3]])
+    }
+
+    fn fourcc_to_u8_8(fourcc: [u8; 4]) -> [u8; 8] {
+        [fourcc[0], fourcc[1], fourcc[2], fourcc[3], 0, 0, 0, 0]
+    }
+
+    fn fourcc(value: &[u8; 4]) -> [u8; 4] {
+        *value
+    }
+
+    define_back_and_fourth_frame_format!([u8; 4], {
+        FrameFormat::Yuyv422 => b"YUYV",
+        FrameFormat::MJpeg => b"MJPG",
+    }, u8_8_to_fourcc, fourcc_to_u8_8, fourcc);
+
+    #[test]
+    fn frame_format_table_round_trips_with_a_custom_fallback() {
+        assert_eq!(FrameFormatIntermediate::into_frame_format(*b"YUYV"), FrameFormat::Yuyv422);
+        assert_eq!(FrameFormatIntermediate::from_frame_format(FrameFormat::MJpeg).map(|fcc| fcc.0), Some(*b"MJPG"));
+
+        // Unknown FourCCs survive the trip as `Custom`, and formats missing from the table have none.
+        let custom = FrameFormatIntermediate::into_frame_format(*b"Y41P");
+        assert_eq!(custom, FrameFormat::Custom(*b"Y41P\0\0\0\0"));
+        assert_eq!(FrameFormatIntermediate::from_frame_format(custom).map(|fcc| fcc.0), Some(*b"Y41P"));
+        assert!(FrameFormatIntermediate::from_frame_format(FrameFormat::Nv12).is_none());
+    }
+
+    #[test]
+    fn frame_format_layout_sizes_at_720p() {
+        let pixels = 1280 * 720;
+        assert_eq!(FrameFormat::Yuyv422.byte_size(HD), Some(pixels * 2));
+        assert_eq!(FrameFormat::Nv12.byte_size(HD), Some(pixels * 3 / 2));
+        assert_eq!(FrameFormat::I420.byte_size(HD), Some(pixels * 3 / 2));
+        assert_eq!(FrameFormat::Yvu9.byte_size(HD), Some(pixels * 9 / 8));
+        assert_eq!(FrameFormat::Luma8.byte_size(HD), Some(pixels));
+        assert_eq!(FrameFormat::Rgb888.byte_size(HD), Some(pixels * 3));
+        assert_eq!(FrameFormat::RgbA8888.byte_size(HD), Some(pixels * 4));
+        assert_eq!(FrameFormat::MJpeg.byte_size(HD), None);
+    }
+
+    #[test]
+    fn frame_format_layout_planes_at_720p() {
+        let nv12 = frame_format_layout(FrameFormat::Nv12).unwrap();
+        assert_eq!(nv12.first_plane_size(HD), 1280 * 720);
+        assert_eq!(nv12.chroma_resolution(HD), Resolution::new(640, 360));
+
+        let yuyv = frame_format_layout(FrameFormat::Yuyv422).unwrap();
+        assert_eq!(yuyv.first_plane_size(HD), 1280 * 720 * 2);
+        assert_eq!(yuyv.chroma_resolution(HD), Resolution::new(640, 720));
+    }
+
+    #[test]
+    fn frame_format_layout_sizes_round_odd_chroma_up() {
+        assert_eq!(FrameFormat::Nv12.byte_size(Resolution::new(1, 1)), Some(3));
+        assert_eq!(FrameFormat::I420.byte_size(Resolution::new(3, 3)), Some(9 + 2 * 4));
+        assert_eq!(FrameFormat::Yvu9.byte_size(Resolution::new(5, 5)), Some(25 + 2 * 4));
+        assert_eq!(FrameFormat::Yuyv422.byte_size(Resolution::new(3, 1)), Some(8));
+        assert_eq!(FrameFormat::Rgb888.byte_size(Resolution::new(3, 1)), Some(9));
+
+        let nv12 = frame_format_layout(FrameFormat::Nv12).unwrap();
+        assert_eq!(nv12.chroma_resolution(Resolution::new(5, 3)), Resolution::new(3, 2));
+    }
+
+    #[test]
+    fn every_uncompressed_format_has_a_layout() {
+        for format in FrameFormat::UNCOMPRESSED {
+            assert!(frame_format_layout(*format).is_some(), "{format}");
+        }
+    }
+
+    #[test]
+    fn jpeg_is_full_range_and_raw_yuv_is_limited() {
+        assert_eq!(FrameFormat::MJpeg.default_color_range(), ColorRange::Full);
+        let raw = [FrameFormat::Yuyv422, FrameFormat::Uyvy422, FrameFormat::Nv12, FrameFormat::Nv21, FrameFormat::I420];
+        for yuv in raw {
+            assert_eq!(yuv.default_color_range(), ColorRange::Limited, "{yuv}");
+        }
+        assert_eq!(FrameFormat::Rgb888.default_color_range(), ColorRange::Full);
+        assert_eq!(FrameFormat::Luma8.default_color_range(), ColorRange::Full);
+    }
+
+    #[cfg(feature = "interop-ffmpeg")]
+    #[test]
+    fn common_formats_map_to_their_ffmpeg_constants() {
+        use ffmpeg_sys_next::{AVCodecID, AVPixelFormat};
+
+        assert_eq!(ffmpeg::to_av_pix_fmt(FrameFormat::Yuyv422), Some(AVPixelFormat::AV_PIX_FMT_YUYV422));
+        assert_eq!(ffmpeg::to_av_pix_fmt(FrameFormat::Nv12), Some(AVPixelFormat::AV_PIX_FMT_NV12));
+        assert_eq!(ffmpeg::to_av_codec_id(FrameFormat::MJpeg), Some(AVCodecID::AV_CODEC_ID_MJPEG));
+        // MJPEG has a codec id, not a pixel format, and the other way around for raw formats.
+        assert_eq!(ffmpeg::to_av_pix_fmt(FrameFormat::MJpeg), None);
+        assert_eq!(ffmpeg::to_av_codec_id(FrameFormat::Nv12), None);
+
+        assert_eq!(ffmpeg::from_av_pix_fmt(AVPixelFormat::AV_PIX_FMT_YUYV422), Some(FrameFormat::Yuyv422));
+        assert_eq!(ffmpeg::from_av_pix_fmt(AVPixelFormat::AV_PIX_FMT_NV12), Some(FrameFormat::Nv12));
+        assert_eq!(ffmpeg::from_av_codec_id(AVCodecID::AV_CODEC_ID_MJPEG), Some(FrameFormat::MJpeg));
+    }
+}
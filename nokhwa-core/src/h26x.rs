@@ -0,0 +1,159 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Helpers for inspecting H264/H265 frames without decoding them, for passing the bitstream straight
+//! through to a recorder or network stream.
+
+use crate::frame_format::FrameFormat;
+
+const H264_NAL_IDR_SLICE: u8 = 5;
+// Random access point (BLA, IDR and CRA) pictures, the ones a decoder can start from.
+const H265_NAL_IRAP: std::ops::RangeInclusive<u8> = 16..=21;
+
+/// Splits an Annex B byte stream (as used by [`FrameFormat::H264`] and [`FrameFormat::H265`]) into NAL units,
+/// with their start codes removed.
+#[must_use]
+pub fn annex_b_nal_units(data: &[u8]) -> Vec<&[u8]> {
+    let mut units = Vec::new();
+    let mut start = None;
+    let mut position = 0;
+
+    while position + 3 <= data.len() {
+        if data[position..position + 3] == [0, 0, 1] {
+            if let Some(start) = start {
+                units.push(trim_trailing_zeros(&data[start..position]));
+            }
+            position += 3;
+            start = Some(position);
+        } else {
+            position += 1;
+        }
+    }
+    if let Some(start) = start {
+        units.push(trim_trailing_zeros(&data[start..]));
+    }
+
+    units.retain(|unit| !unit.is_empty());
+    units
+}
+
+/// Splits a stream of NAL units each prefixed by their 4 byte big endian length (as used by [`FrameFormat::Avc1`])
+/// into NAL units. Returns `None` if a length runs past the end of the buffer.
+#[must_use]
+pub fn length_prefixed_nal_units(data: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut units = Vec::new();
+    let mut position = 0;
+
+    while position < data.len() {
+        let length: [u8; 4] = data.get(position..position + 4)?.try_into().ok()?;
+        let length = u32::from_be_bytes(length) as usize;
+        position += 4;
+        units.push(data.get(position..position + length)?);
+        position += length;
+    }
+
+    Some(units)
+}
+
+/// Returns whether the H264/H265 frame in `data` holds a keyframe (a picture decoding can start from).
+///
+/// Returns `None` if `frame_format` is not H264, AVC1 or H265, or the frame holds no picture data to tell from,
+/// e.g. a lone parameter set.
+#[must_use]
+pub fn is_keyframe(frame_format: FrameFormat, data: &[u8]) -> Option<bool> {
+    match frame_format {
+        FrameFormat::H264 => h264_is_keyframe(&annex_b_nal_units(data)),
+        FrameFormat::Avc1 => h264_is_keyframe(&length_prefixed_nal_units(data)?),
+        FrameFormat::H265 => h265_is_keyframe(&annex_b_nal_units(data)),
+        _ => None,
+    }
+}
+
+fn h264_is_keyframe(units: &[&[u8]]) -> Option<bool> {
+    let mut has_slice = false;
+    for nal_type in units.iter().filter_map(|unit| unit.first()).map(|header| header & 0x1F) {
+        if nal_type == H264_NAL_IDR_SLICE {
+            return Some(true);
+        }
+        has_slice |= (1..H264_NAL_IDR_SLICE).contains(&nal_type);
+    }
+    has_slice.then_some(false)
+}
+
+fn h265_is_keyframe(units: &[&[u8]]) -> Option<bool> {
+    let mut has_slice = false;
+    for nal_type in units.iter().filter_map(|unit| unit.first()).map(|header| (header >> 1) & 0x3F) {
+        if H265_NAL_IRAP.contains(&nal_type) {
+            return Some(true);
+        }
+        // 0 to 31 are the VCL (picture data) NAL types.
+        has_slice |= nal_type < 32;
+    }
+    has_slice.then_some(false)
+}
+
+// Zero bytes before a start code belong to it (as a 4 byte start code or trailing padding), not the NAL unit.
+fn trim_trailing_zeros(unit: &[u8]) -> &[u8] {
+    let end = unit.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1);
+    &unit[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_buffer::FrameBuffer;
+    use crate::types::Resolution;
+
+    // SPS and PPS, then a slice NAL unit with the header byte `slice`.
+    fn h264_access_unit(slice: u8) -> Vec<u8> {
+        [
+            &[0, 0, 0, 1, 0x67, 0x42, 0x00, 0x1F][..],
+            &[0, 0, 0, 1, 0x68, 0xCE, 0x3C, 0x80],
+            &[0, 0, 1, slice, 0x88, 0x84, 0x00],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn h264_keyframes_are_told_apart_by_their_slice_type() {
+        let idr = h264_access_unit(0x65);
+        assert_eq!(
+            annex_b_nal_units(&idr),
+            [&[0x67, 0x42, 0x00, 0x1F][..], &[0x68, 0xCE, 0x3C, 0x80], &[0x65, 0x88, 0x84]]
+        );
+        assert_eq!(is_keyframe(FrameFormat::H264, &idr), Some(true));
+        assert_eq!(is_keyframe(FrameFormat::H264, &h264_access_unit(0x41)), Some(false));
+        // Parameter sets alone hold no picture.
+        assert_eq!(is_keyframe(FrameFormat::H264, &idr[..16]), None);
+
+        let resolution = Resolution::new(2, 2);
+        assert_eq!(FrameBuffer::new(resolution, &idr, FrameFormat::H264).is_keyframe(), Some(true));
+        assert_eq!(FrameBuffer::new(resolution, &idr, FrameFormat::MJpeg).is_keyframe(), None);
+    }
+
+    #[test]
+    fn avc1_and_h265_keyframes() {
+        let avc1 = |slice: u8| [&[0, 0, 0, 3, 0x68, 0xCE, 0x3C][..], &[0, 0, 0, 2, slice, 0x88]].concat();
+        assert_eq!(is_keyframe(FrameFormat::Avc1, &avc1(0x65)), Some(true));
+        assert_eq!(is_keyframe(FrameFormat::Avc1, &avc1(0x41)), Some(false));
+        assert_eq!(is_keyframe(FrameFormat::Avc1, &avc1(0x65)[..9]), None);
+
+        // IDR_W_RADL (19) and TRAIL_R (1) slices, after a VPS (32).
+        let h265 = |nal_type: u8| [0, 0, 0, 1, 32 << 1, 0x01, 0x0C, 0, 0, 1, nal_type << 1, 0x01, 0xAF];
+        assert_eq!(is_keyframe(FrameFormat::H265, &h265(19)), Some(true));
+        assert_eq!(is_keyframe(FrameFormat::H265, &h265(1)), Some(false));
+    }
+}
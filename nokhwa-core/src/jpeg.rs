@@ -0,0 +1,368 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Helpers for inspecting MJPEG frames without decoding them.
+
+use crate::{frame_buffer::FrameBuffer, frame_format::FrameFormat};
+use std::time::Duration;
+
+const MARKER_SOI: u8 = 0xD8;
+const MARKER_EOI: u8 = 0xD9;
+const MARKER_SOS: u8 = 0xDA;
+const MARKER_APP1: u8 = 0xE1;
+
+// Anything shorter can't hold the quantisation table, frame header and scan every frame needs.
+const MIN_MJPEG_LENGTH: usize = 64;
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+const TAG_EXIF_IFD_POINTER: u16 = 0x8769;
+const TAG_EXPOSURE_TIME: u16 = 0x829A;
+const TAG_ISO_SPEED_RATINGS: u16 = 0x8827;
+const TAG_WHITE_BALANCE: u16 = 0xA403;
+
+const TYPE_SHORT: u16 = 3;
+const TYPE_LONG: u16 = 4;
+const TYPE_RATIONAL: u16 = 5;
+
+/// The white balance mode recorded in an EXIF segment.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ExifWhiteBalance {
+    Auto,
+    Manual,
+}
+
+/// Capture information read from the EXIF (APP1) segment of an MJPEG frame.
+///
+/// Each field is `None` if the camera did not record it.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ExifData {
+    pub exposure_time: Option<Duration>,
+    pub iso: Option<u32>,
+    pub white_balance: Option<ExifWhiteBalance>,
+}
+
+/// Quickly checks that `buf` looks like a complete JPEG frame: long enough, starting with SOI and ending with EOI.
+///
+/// This does not decode anything, so it is cheap enough to run on every frame to skip the ones a USB glitch cut short.
+/// Zero padding after EOI, which some cameras add to fill their transfer, is ignored.
+#[must_use]
+pub fn is_valid_mjpeg(buf: &[u8]) -> bool {
+    let end = buf.iter().rposition(|byte| *byte != 0).map_or(0, |last| last + 1);
+    let trimmed = &buf[..end];
+
+    trimmed.len() >= MIN_MJPEG_LENGTH
+        && trimmed.starts_with(&[0xFF, MARKER_SOI])
+        && trimmed.ends_with(&[0xFF, MARKER_EOI])
+}
+
+/// The length of the JPEG at the start of `buf`, up to and including its EOI marker.
+///
+/// The length is found by walking the marker segments and skipping over the entropy coded data, so a thumbnail
+/// embedded in an EXIF segment, which carries its own SOI and EOI, is not mistaken for the end of the image. This
+/// makes it suitable for splitting a concatenation of JPEGs, such as a raw MJPEG recording.
+///
+/// Returns `None` if `buf` does not start with SOI, or ends before the JPEG's EOI.
+#[must_use]
+pub fn jpeg_length(buf: &[u8]) -> Option<usize> {
+    if buf.get(0..2)? != [0xFF, MARKER_SOI] {
+        return None;
+    }
+
+    let mut position = 2;
+    loop {
+        if *buf.get(position)? != 0xFF {
+            return None;
+        }
+        // A marker may be preceded by any number of 0xFF fill bytes.
+        while *buf.get(position + 1)? == 0xFF {
+            position += 1;
+        }
+
+        match buf[position + 1] {
+            MARKER_EOI => return Some(position + 2),
+            // TEM and restart markers stand alone, without a length.
+            0x01 | 0xD0..=0xD7 => position += 2,
+            marker => {
+                let length = usize::from(u16::from_be_bytes([*buf.get(position + 2)?, *buf.get(position + 3)?]));
+                position += 2 + length;
+                if marker == MARKER_SOS {
+                    position = skip_entropy_coded_data(buf, position)?;
+                }
+            }
+        }
+    }
+}
+
+// Returns the position of the first marker after the entropy coded data starting at `position`, stepping over
+// stuffed zero bytes and restart markers, which may appear inside it.
+fn skip_entropy_coded_data(buf: &[u8], mut position: usize) -> Option<usize> {
+    loop {
+        position += buf.get(position..)?.iter().position(|byte| *byte == 0xFF)?;
+        match *buf.get(position + 1)? {
+            0x00 | 0xD0..=0xD7 => position += 2,
+            // A fill byte, the marker follows it.
+            0xFF => position += 1,
+            _ => return Some(position),
+        }
+    }
+}
+
+/// Reads the EXIF capture information out of an MJPEG [`FrameBuffer`].
+///
+/// Returns `None` if the buffer is not [`FrameFormat::MJpeg`], or carries no readable EXIF segment.
+#[must_use]
+pub fn mjpeg_exif(buf: &FrameBuffer) -> Option<ExifData> {
+    if buf.source_frame_format() != FrameFormat::MJpeg {
+        return None;
+    }
+
+    let tiff = find_exif_segment(buf.buffer())?;
+    parse_tiff(tiff)
+}
+
+// Walks the JPEG markers up to the start of scan, returning the TIFF data of the first EXIF segment.
+fn find_exif_segment(data: &[u8]) -> Option<&[u8]> {
+    if data.get(0..2)? != [0xFF, MARKER_SOI] {
+        return None;
+    }
+
+    let mut position = 2;
+    while position + 4 <= data.len() {
+        if data[position] != 0xFF {
+            return None;
+        }
+        let marker = data[position + 1];
+        if marker == MARKER_SOS || marker == MARKER_EOI {
+            return None;
+        }
+
+        let length = usize::from(u16::from_be_bytes([data[position + 2], data[position + 3]]));
+        let segment = data.get(position + 4..position + 2 + length)?;
+        if marker == MARKER_APP1 && segment.starts_with(EXIF_HEADER) {
+            return Some(&segment[EXIF_HEADER.len()..]);
+        }
+
+        position += 2 + length;
+    }
+
+    None
+}
+
+struct TiffReader<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl TiffReader<'_> {
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let bytes = [*self.data.get(offset)?, *self.data.get(offset + 1)?];
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self.data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    // Reads an integer entry value, which is stored inline in the entry's value field.
+    fn integer_value(&self, entry: usize) -> Option<u32> {
+        match self.u16_at(entry + 2)? {
+            TYPE_SHORT => self.u16_at(entry + 8).map(u32::from),
+            TYPE_LONG => self.u32_at(entry + 8),
+            _ => None,
+        }
+    }
+
+    // Reads a rational entry value, which is stored at the offset in the entry's value field.
+    fn rational_value(&self, entry: usize) -> Option<(u32, u32)> {
+        if self.u16_at(entry + 2)? != TYPE_RATIONAL {
+            return None;
+        }
+        let offset = self.u32_at(entry + 8)? as usize;
+        Some((self.u32_at(offset)?, self.u32_at(offset + 4)?))
+    }
+}
+
+fn parse_tiff(tiff: &[u8]) -> Option<ExifData> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let reader = TiffReader {
+        data: tiff,
+        little_endian,
+    };
+    if reader.u16_at(2)? != 42 {
+        return None;
+    }
+
+    let mut exif = ExifData::default();
+    let ifd0 = reader.u32_at(4)? as usize;
+    let exif_ifd = read_ifd(&reader, ifd0, &mut exif);
+    if let Some(exif_ifd) = exif_ifd {
+        read_ifd(&reader, exif_ifd as usize, &mut exif);
+    }
+
+    Some(exif)
+}
+
+// Fills in any known tags found in the IFD at `offset`, returning the EXIF sub-IFD pointer if present.
+fn read_ifd(reader: &TiffReader<'_>, offset: usize, exif: &mut ExifData) -> Option<u32> {
+    let count = usize::from(reader.u16_at(offset)?);
+    let mut exif_ifd = None;
+
+    for index in 0..count {
+        let entry = offset + 2 + index * 12;
+        let Some(tag) = reader.u16_at(entry) else {
+            break;
+        };
+
+        match tag {
+            TAG_EXIF_IFD_POINTER => exif_ifd = reader.u32_at(entry + 8),
+            TAG_EXPOSURE_TIME => {
+                exif.exposure_time = reader
+                    .rational_value(entry)
+                    .filter(|(_, denominator)| *denominator != 0)
+                    .map(|(numerator, denominator)| {
+                        Duration::from_nanos(
+                            u64::from(numerator) * 1_000_000_000 / u64::from(denominator),
+                        )
+                    });
+            }
+            TAG_ISO_SPEED_RATINGS => exif.iso = reader.integer_value(entry),
+            TAG_WHITE_BALANCE => {
+                exif.white_balance = match reader.integer_value(entry) {
+                    Some(0) => Some(ExifWhiteBalance::Auto),
+                    Some(1) => Some(ExifWhiteBalance::Manual),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    exif_ifd
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::types::Resolution;
+
+    // Big endian TIFF data whose EXIF sub-IFD records a 1/250s exposure at ISO 400 with manual white balance.
+    fn tiff() -> Vec<u8> {
+        let mut tiff = b"MM\0\x2A".to_vec();
+        tiff.extend_from_slice(&8_u32.to_be_bytes());
+        // IFD0 at 8, pointing to the EXIF IFD at 26.
+        tiff.extend_from_slice(&1_u16.to_be_bytes());
+        tiff.extend_from_slice(&[0x87, 0x69, 0, 4, 0, 0, 0, 1, 0, 0, 0, 26]);
+        tiff.extend_from_slice(&0_u32.to_be_bytes());
+        // EXIF IFD at 26, its rational value at 68.
+        tiff.extend_from_slice(&3_u16.to_be_bytes());
+        tiff.extend_from_slice(&[0x82, 0x9A, 0, 5, 0, 0, 0, 1, 0, 0, 0, 68]);
+        tiff.extend_from_slice(&[0x88, 0x27, 0, 3, 0, 0, 0, 1, 0x01, 0x90, 0, 0]);
+        tiff.extend_from_slice(&[0xA4, 0x03, 0, 3, 0, 0, 0, 1, 0, 1, 0, 0]);
+        tiff.extend_from_slice(&0_u32.to_be_bytes());
+        tiff.extend_from_slice(&1_u32.to_be_bytes());
+        tiff.extend_from_slice(&250_u32.to_be_bytes());
+        tiff
+    }
+
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xFF, marker];
+        segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    // A JPEG frame with a JFIF header, then `app1` if given, then a scan.
+    fn jpeg(app1: Option<&[u8]>) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, MARKER_SOI];
+        jpeg.extend(segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        if let Some(app1) = app1 {
+            jpeg.extend(segment(MARKER_APP1, app1));
+        }
+        jpeg.extend(segment(0xDB, &[0; 65]));
+        jpeg.extend(segment(MARKER_SOS, &[1, 1, 0, 0, 0x3F, 0]));
+        jpeg.extend_from_slice(&[0x12, 0xFF, 0x00, 0x34, 0xFF, MARKER_EOI]);
+        jpeg
+    }
+
+    fn exif_payload(thumbnail: &[u8]) -> Vec<u8> {
+        let mut payload = EXIF_HEADER.to_vec();
+        payload.extend(tiff());
+        payload.extend_from_slice(thumbnail);
+        payload
+    }
+
+    fn frame(data: &[u8]) -> FrameBuffer {
+        FrameBuffer::new(Resolution::new(16, 16), data, FrameFormat::MJpeg)
+    }
+
+    const RECORDED: ExifData = ExifData {
+        exposure_time: Some(Duration::from_millis(4)),
+        iso: Some(400),
+        white_balance: Some(ExifWhiteBalance::Manual),
+    };
+
+    #[test]
+    fn mjpeg_exif_reads_the_capture_settings() {
+        assert_eq!(mjpeg_exif(&frame(&jpeg(Some(&exif_payload(&[]))))), Some(RECORDED));
+    }
+
+    #[test]
+    fn mjpeg_exif_reads_past_an_embedded_thumbnail() {
+        // The thumbnail is a whole JPEG of its own, with an EXIF segment that must not be picked up instead.
+        let mut thumbnail_exif = EXIF_HEADER.to_vec();
+        thumbnail_exif.extend_from_slice(b"II\x2A\0\x08\0\0\0\0\0");
+        let thumbnail = jpeg(Some(&thumbnail_exif));
+        let data = jpeg(Some(&exif_payload(&thumbnail)));
+
+        assert_eq!(mjpeg_exif(&frame(&data)), Some(RECORDED));
+        assert_eq!(jpeg_length(&data), Some(data.len()));
+    }
+
+    #[test]
+    fn mjpeg_exif_is_none_without_an_exif_segment() {
+        assert_eq!(mjpeg_exif(&frame(&jpeg(None))), None);
+        assert_eq!(mjpeg_exif(&frame(&jpeg(Some(b"http://ns.adobe.com/xap/1.0/\0")))), None);
+
+        let data = jpeg(Some(&exif_payload(&[])));
+        let yuyv = FrameBuffer::new(Resolution::new(16, 16), &data, FrameFormat::Yuyv422);
+        assert_eq!(mjpeg_exif(&yuyv), None);
+    }
+
+    /// Encodes packed RGB888 pixels into a baseline JPEG, for tests that need a real MJPEG frame.
+    #[cfg(feature = "mjpeg")]
+    pub(crate) fn encode_rgb(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+        let mut compress = mozjpeg::Compress::new(mozjpeg::ColorSpace::JCS_RGB);
+        compress.set_size(width, height);
+        compress.set_quality(100.0);
+        let mut started = compress.start_compress(Vec::new()).unwrap();
+        started.write_scanlines(rgb).unwrap();
+        started.finish().unwrap()
+    }
+}
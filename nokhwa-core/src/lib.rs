@@ -22,12 +22,18 @@
  */
 
 //! Core type definitions for `nokhwa`
+pub mod aligned;
 pub mod camera;
+pub mod capture_thread;
+pub mod conversions;
 pub mod decoder;
 pub mod error;
 pub mod format_request;
 pub mod frame_buffer;
 pub mod frame_format;
+pub mod h26x;
+pub mod jpeg;
+pub mod pixel_format;
 pub mod properties;
 pub mod query;
 pub mod ranges;
@@ -35,4 +41,4 @@ pub mod traits;
 pub mod types;
 pub mod utils;
 pub mod stream;
-mod platform;
+pub mod platform;
@@ -0,0 +1,419 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! [`Decoder`] implementations that turn a [`FrameBuffer`] into common pixel formats.
+
+use crate::{
+    decoder::{Decoder, StaticDecoder},
+    error::NokhwaError,
+    frame_buffer::FrameBuffer,
+    frame_format::FrameFormat,
+};
+use image::{ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+/// A Zero-Sized struct that decodes a [`FrameBuffer`] into 8-bit Luma with an opaque Alpha channel.
+///
+/// Colour sources are decoded to RGB first, then averaged into luma ([`LumaWeights::Average`]). This is fast, but
+/// makes green look as dark as blue; use [`WeightedLumaAFormat`] for perceptually correct grayscale.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct LumaAFormat;
+
+impl Decoder for LumaAFormat {
+    const ALLOWED_FORMATS: &'static [FrameFormat] = LUMA_SOURCE_FORMATS;
+
+    type OutputPixels = LumaA<u8>;
+    type PixelContainer = Vec<u8>;
+
+    fn decode(
+        &mut self,
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        Self::decode_static(buffer)
+    }
+
+    fn decode_buffer(&mut self, buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        Self::decode_static_to_buffer(buffer, output)
+    }
+}
+
+impl StaticDecoder for LumaAFormat {
+    fn decode_static(
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        decode_luma_alpha_image::<Self>(buffer, LumaWeights::Average)
+    }
+
+    fn decode_static_to_buffer(buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        decode_luma_alpha::<Self>(buffer, output, LumaWeights::Average)
+    }
+}
+
+/// How [`WeightedLumaAFormat`] weighs the red, green and blue channels into luma.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum LumaWeights {
+    /// `(R + G + B) / 3`, what [`LumaAFormat`] uses.
+    Average,
+    /// ITU-R BT.601 (`0.299R + 0.587G + 0.114B`), for standard definition sources and most computer vision code.
+    #[default]
+    Rec601,
+    /// ITU-R BT.709 (`0.2126R + 0.7152G + 0.0722B`), for HD sources.
+    Rec709,
+}
+
+impl LumaWeights {
+    /// Weighs one RGB pixel into luma.
+    #[must_use]
+    #[inline]
+    pub fn luma(self, red: u8, green: u8, blue: u8) -> u8 {
+        let (red, green, blue) = (u32::from(red), u32::from(green), u32::from(blue));
+        // fixed point, the weights of each set add up to 256
+        let luma = match self {
+            LumaWeights::Average => return ((red + green + blue) / 3) as u8,
+            LumaWeights::Rec601 => 77 * red + 150 * green + 29 * blue,
+            LumaWeights::Rec709 => 54 * red + 183 * green + 19 * blue,
+        };
+        ((luma + 128) >> 8) as u8
+    }
+}
+
+/// Decodes a [`FrameBuffer`] into 8-bit Luma with an opaque Alpha channel like [`LumaAFormat`], but weighs the
+/// colour channels by how bright they look ([`LumaWeights::Rec601`] by default) instead of averaging them.
+///
+/// [`StaticDecoder`] always uses [`LumaWeights::Rec601`].
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct WeightedLumaAFormat {
+    weights: LumaWeights,
+}
+
+impl WeightedLumaAFormat {
+    #[must_use]
+    pub fn new(weights: LumaWeights) -> Self {
+        Self { weights }
+    }
+
+    #[must_use]
+    pub fn weights(&self) -> LumaWeights {
+        self.weights
+    }
+}
+
+impl Decoder for WeightedLumaAFormat {
+    const ALLOWED_FORMATS: &'static [FrameFormat] = LUMA_SOURCE_FORMATS;
+
+    type OutputPixels = LumaA<u8>;
+    type PixelContainer = Vec<u8>;
+
+    fn decode(
+        &mut self,
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        decode_luma_alpha_image::<Self>(buffer, self.weights)
+    }
+
+    fn decode_buffer(&mut self, buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        decode_luma_alpha::<Self>(buffer, output, self.weights)
+    }
+}
+
+impl StaticDecoder for WeightedLumaAFormat {
+    fn decode_static(
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        decode_luma_alpha_image::<Self>(buffer, LumaWeights::Rec601)
+    }
+
+    fn decode_static_to_buffer(buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        decode_luma_alpha::<Self>(buffer, output, LumaWeights::Rec601)
+    }
+}
+
+#[cfg(feature = "mjpeg")]
+const LUMA_SOURCE_FORMATS: &[FrameFormat] = &[
+    FrameFormat::MJpeg,
+    FrameFormat::Yuyv422,
+    FrameFormat::Uyvy422,
+    FrameFormat::Yvyu422,
+    FrameFormat::Nv12,
+    FrameFormat::Luma8,
+];
+#[cfg(not(feature = "mjpeg"))]
+const LUMA_SOURCE_FORMATS: &[FrameFormat] = &[
+    FrameFormat::Yuyv422,
+    FrameFormat::Uyvy422,
+    FrameFormat::Yvyu422,
+    FrameFormat::Nv12,
+    FrameFormat::Luma8,
+];
+
+fn decode_luma_alpha_image<D>(
+    buffer: &FrameBuffer,
+    weights: LumaWeights,
+) -> Result<ImageBuffer<LumaA<u8>, Vec<u8>>, NokhwaError>
+where
+    D: Decoder<OutputPixels = LumaA<u8>>,
+{
+    let resolution = buffer.resolution();
+    let mut output = vec![0; D::predicted_size_of_frame(buffer).unwrap_or_default()];
+    decode_luma_alpha::<D>(buffer, &mut output, weights)?;
+
+    ImageBuffer::from_raw(resolution.width(), resolution.height(), output).ok_or(
+        NokhwaError::ProcessFrameError {
+            src: buffer.source_frame_format(),
+            destination: "LumaA".to_string(),
+            error: "Failed to create buffer".to_string(),
+        },
+    )
+}
+
+fn decode_luma_alpha<D>(buffer: &FrameBuffer, output: &mut [u8], weights: LumaWeights) -> Result<(), NokhwaError>
+where
+    D: Decoder<OutputPixels = LumaA<u8>>,
+{
+    if let ControlFlow::Break(why) = D::check_format(buffer) {
+        return Err(why);
+    }
+
+    let needed = D::predicted_size_of_frame(buffer).unwrap_or_default();
+    if output.len() < needed {
+        return Err(NokhwaError::BufferTooSmall { needed, got: output.len() });
+    }
+
+    match buffer.source_frame_format() {
+        // Decoded through RGB so the frame's color range is honoured. The JPEG carries its own dimensions, so
+        // they are checked against the buffer's resolution like the RGB path does.
+        #[cfg(feature = "mjpeg")]
+        FrameFormat::MJpeg => {
+            let rgb = buffer.to_rgb()?;
+            let resolution = buffer.resolution();
+            let expected = resolution.width() as usize * resolution.height() as usize * 3;
+            if rgb.len() != expected {
+                return Err(NokhwaError::ProcessFrameError {
+                    src: FrameFormat::MJpeg,
+                    destination: "LumaA".to_string(),
+                    error: format!("Bad buffer length: expected {expected} bytes, got {}", rgb.len()),
+                });
+            }
+            rgb_to_luma_alpha(&rgb, output, weights);
+        }
+        FrameFormat::Yuyv422 | FrameFormat::Uyvy422 | FrameFormat::Yvyu422 | FrameFormat::Nv12 => {
+            rgb_to_luma_alpha(&buffer.to_rgb()?, output, weights);
+        }
+        FrameFormat::Luma8 => {
+            for (pxv, d) in buffer.buffer().iter().zip(output.chunks_exact_mut(2)) {
+                d[0] = *pxv;
+                d[1] = 255;
+            }
+        }
+        other => {
+            return Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: "LumaA".to_string(),
+                error: "Unsupported source format".to_string(),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// A pixel type a camera can deliver as-is, for [`PassthroughFormat`].
+pub trait PassthroughPixel: Pixel<Subpixel = u8> {
+    /// The [`FrameFormat`]s whose bytes are already laid out as this pixel type.
+    const FRAME_FORMATS: &'static [FrameFormat];
+    /// Name of this layout for error messages.
+    const NAME: &'static str;
+}
+
+impl PassthroughPixel for Rgb<u8> {
+    const FRAME_FORMATS: &'static [FrameFormat] = &[FrameFormat::Rgb888];
+    const NAME: &'static str = "RGB";
+}
+
+impl PassthroughPixel for Rgba<u8> {
+    const FRAME_FORMATS: &'static [FrameFormat] = &[FrameFormat::RgbA8888];
+    const NAME: &'static str = "RGBA";
+}
+
+impl PassthroughPixel for Luma<u8> {
+    const FRAME_FORMATS: &'static [FrameFormat] = &[FrameFormat::Luma8];
+    const NAME: &'static str = "Luma";
+}
+
+/// A Zero-Sized struct that "decodes" a [`FrameBuffer`] already in the pixel layout `P` by copying its bytes as-is,
+/// e.g. [`FrameFormat::Rgb888`] into [`Rgb<u8>`]. Any other source is an error rather than a conversion.
+///
+/// This gives sources that need no conversion the same decode API as the others, at the cost of a single copy.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PassthroughFormat<P>(PhantomData<P>);
+
+impl<P: PassthroughPixel> Decoder for PassthroughFormat<P> {
+    const ALLOWED_FORMATS: &'static [FrameFormat] = P::FRAME_FORMATS;
+
+    type OutputPixels = P;
+    type PixelContainer = Vec<u8>;
+
+    fn decode(
+        &mut self,
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        Self::decode_static(buffer)
+    }
+
+    fn decode_buffer(&mut self, buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        Self::decode_static_to_buffer(buffer, output)
+    }
+}
+
+impl<P: PassthroughPixel> StaticDecoder for PassthroughFormat<P> {
+    fn decode_static(
+        buffer: &FrameBuffer,
+    ) -> Result<ImageBuffer<Self::OutputPixels, Self::PixelContainer>, NokhwaError> {
+        if let ControlFlow::Break(why) = Self::check_format(buffer) {
+            return Err(why);
+        }
+
+        let resolution = buffer.resolution();
+        ImageBuffer::from_raw(resolution.width(), resolution.height(), buffer.buffer().to_vec()).ok_or(
+            NokhwaError::ProcessFrameError {
+                src: buffer.source_frame_format(),
+                destination: P::NAME.to_string(),
+                error: "Failed to create buffer".to_string(),
+            },
+        )
+    }
+
+    fn decode_static_to_buffer(buffer: &FrameBuffer, output: &mut [u8]) -> Result<(), NokhwaError> {
+        if let ControlFlow::Break(why) = Self::check_format(buffer) {
+            return Err(why);
+        }
+
+        let source = buffer.buffer();
+        let got = output.len();
+        let Some(output) = output.get_mut(..source.len()) else {
+            return Err(NokhwaError::BufferTooSmall {
+                needed: source.len(),
+                got,
+            });
+        };
+        output.copy_from_slice(source);
+
+        Ok(())
+    }
+}
+
+fn rgb_to_luma_alpha(rgb: &[u8], output: &mut [u8], weights: LumaWeights) {
+    for (px, d) in rgb.chunks_exact(3).zip(output.chunks_exact_mut(2)) {
+        d[0] = weights.luma(px[0], px[1], px[2]);
+        d[1] = 255;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Resolution;
+
+    #[test]
+    fn output_pixel_sizes_follow_the_pixel_type() {
+        assert_eq!(PassthroughFormat::<Rgb<u8>>::channels(), 3);
+        assert_eq!(PassthroughFormat::<Rgba<u8>>::channels(), 4);
+        assert_eq!(LumaAFormat::channels(), 2);
+        assert_eq!(PassthroughFormat::<Rgb<u8>>::subpixel_size(), 1);
+        assert_eq!(PassthroughFormat::<Rgba<u8>>::subpixel_size(), 1);
+
+        let rgba = FrameBuffer::new(Resolution::new(4, 2), &[0; 32], FrameFormat::RgbA8888);
+        assert_eq!(PassthroughFormat::<Rgba<u8>>::predicted_size_of_frame(&rgba), Some(32));
+        assert_eq!(PassthroughFormat::<Rgb<u8>>::predicted_size_of_frame(&rgba), None);
+    }
+
+    #[test]
+    fn luma_alpha_copies_gray_frames_and_adds_opaque_alpha() {
+        let buffer = FrameBuffer::new(Resolution::new(3, 1), &[0, 10, 200], FrameFormat::Luma8);
+
+        let image = LumaAFormat::decode_static(&buffer).unwrap();
+        assert_eq!(image.dimensions(), (3, 1));
+        assert_eq!(image.into_raw(), [0, 255, 10, 255, 200, 255]);
+    }
+
+    #[cfg(feature = "mjpeg")]
+    #[test]
+    fn luma_alpha_decodes_mjpeg_frames() {
+        let jpeg = crate::jpeg::tests::encode_rgb(16, 8, &[90; 16 * 8 * 3]);
+        let buffer = FrameBuffer::new(Resolution::new(16, 8), &jpeg, FrameFormat::MJpeg);
+
+        let image = LumaAFormat::decode_static(&buffer).unwrap();
+        assert_eq!(image.dimensions(), (16, 8));
+        for pixel in image.pixels() {
+            assert!(pixel[0].abs_diff(90) <= 2, "{pixel:?}");
+            assert_eq!(pixel[1], 255);
+        }
+
+        // The JPEG's own dimensions must match the resolution the frame claims.
+        let mislabelled = FrameBuffer::new(Resolution::new(8, 8), &jpeg, FrameFormat::MJpeg);
+        assert!(matches!(
+            LumaAFormat::decode_static(&mislabelled),
+            Err(NokhwaError::ProcessFrameError { src: FrameFormat::MJpeg, .. })
+        ));
+    }
+
+    #[test]
+    fn weighted_luma_keeps_green_brighter_than_averaged_luma() {
+        assert_eq!(LumaWeights::Average.luma(0, 255, 0), 85);
+        assert_eq!(LumaWeights::Rec601.luma(0, 255, 0), 149);
+        assert_eq!(LumaWeights::Rec709.luma(0, 255, 0), 182);
+        for weights in [LumaWeights::Average, LumaWeights::Rec601, LumaWeights::Rec709] {
+            assert_eq!(weights.luma(255, 255, 255), 255);
+        }
+
+        // A green YUYV pair, decoded through RGB by both decoders.
+        let green = FrameBuffer::new(Resolution::new(2, 1), &[145, 54, 145, 34], FrameFormat::Yuyv422);
+        let rgb = green.to_rgb().unwrap();
+        let luma = |weights: LumaWeights| weights.luma(rgb[0], rgb[1], rgb[2]);
+
+        let averaged = LumaAFormat::decode_static(&green).unwrap();
+        let weighted = WeightedLumaAFormat::decode_static(&green).unwrap();
+        let rec709 = WeightedLumaAFormat::new(LumaWeights::Rec709).decode(&green).unwrap();
+        assert_eq!(averaged.into_raw(), [luma(LumaWeights::Average), 255, luma(LumaWeights::Average), 255]);
+        assert_eq!(weighted.get_pixel(0, 0)[0], luma(LumaWeights::Rec601));
+        assert_eq!(rec709.get_pixel(1, 0)[0], luma(LumaWeights::Rec709));
+        assert!(luma(LumaWeights::Average) < 100 && luma(LumaWeights::Rec601) > 140);
+    }
+
+    #[test]
+    fn passthrough_copies_matching_frames_and_rejects_the_rest() {
+        let rgb = (0..12).collect::<Vec<u8>>();
+        let buffer = FrameBuffer::new(Resolution::new(2, 2), &rgb, FrameFormat::Rgb888);
+
+        assert_eq!(PassthroughFormat::<Rgb<u8>>::decode_static(&buffer).unwrap().into_raw(), rgb);
+        let mut output = [0; 12];
+        PassthroughFormat::<Rgb<u8>>::decode_static_to_buffer(&buffer, &mut output).unwrap();
+        assert_eq!(output, *rgb);
+        assert!(matches!(
+            PassthroughFormat::<Rgb<u8>>::decode_static_to_buffer(&buffer, &mut [0; 11]),
+            Err(NokhwaError::BufferTooSmall { needed: 12, got: 11 })
+        ));
+
+        // Other layouts are not converted, even when the byte count happens to fit.
+        let yuyv = FrameBuffer::new(Resolution::new(2, 3), &rgb, FrameFormat::Yuyv422);
+        assert!(matches!(
+            PassthroughFormat::<Rgb<u8>>::decode_static(&yuyv),
+            Err(NokhwaError::ConversionError(_))
+        ));
+        assert!(PassthroughFormat::<Rgba<u8>>::decode_static(&buffer).is_err());
+    }
+}
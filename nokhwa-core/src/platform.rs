@@ -1,8 +1,20 @@
-use crate::camera::{AsyncCamera, Camera};
-use crate::error::NokhwaResult;
+#[cfg(feature = "async")]
+use crate::camera::{AsyncCamera, AsyncSetting, AsyncStream};
+use crate::camera::Camera;
+use crate::error::{NokhwaError, NokhwaResult};
+use crate::format_request::RequestedFormat;
+use crate::frame_buffer::FrameBuffer;
+#[cfg(feature = "async")]
+use crate::stream::FrameStream;
 use crate::types::{CameraIndex, CameraInformation};
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
 
-#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+/// How long [`PlatformTrait::query_with_thumbnails`] waits for each device's frame.
+const THUMBNAIL_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Backends {
     Video4Linux2,
     WebWASM,
@@ -16,13 +28,252 @@ pub trait PlatformTrait {
     type Camera: Camera;
 
 
+    /// Blocks until the user has answered the platform's camera permission prompt.
+    /// # Errors
+    /// Errors if permission is denied.
     fn block_on_permission(&mut self) -> NokhwaResult<()>;
 
     fn check_permission_given(&mut self) -> bool;
 
+    /// Lists the devices available on this platform.
+    /// # Errors
+    /// Errors if the platform's device enumeration fails.
     fn query(&mut self) -> NokhwaResult<Vec<CameraInformation>>;
 
+    /// Opens the device at `index`.
+    /// # Errors
+    /// Errors if the device does not exist or cannot be opened.
     fn open(&mut self, index: &CameraIndex) -> NokhwaResult<Self::Camera>;
+
+    /// Opens the device at `index` with the given [`OpenOptions`]. Platforms that support
+    /// [`OpenOptions::read_only`] should override this; by default only a normal open is accepted.
+    /// # Errors
+    /// Errors if the platform does not support the requested options, or the device cannot be opened.
+    fn open_with(&mut self, index: &CameraIndex, options: OpenOptions) -> NokhwaResult<Self::Camera> {
+        if options.read_only {
+            return Err(NokhwaError::NotImplementedError(format!(
+                "{:?} cannot open cameras read-only",
+                Self::PLATFORM
+            )));
+        }
+
+        self.open(index)
+    }
+
+    /// Lists the devices like [`query`](PlatformTrait::query), grabbing one raw frame from each in a format
+    /// chosen by `format` for a device picker to show as a preview.
+    ///
+    /// Each device is opened, streamed just long enough for one frame, set back to the format it had (if the backend
+    /// reports it, see [`Setting::camera_format`](crate::camera::Setting::camera_format)), and closed again. A device
+    /// that is busy, cannot satisfy `format`, or sends nothing within a couple of seconds gets `None` instead of
+    /// failing the whole query.
+    /// # Errors
+    /// Errors if the platform's device enumeration fails.
+    fn query_with_thumbnails(
+        &mut self,
+        format: RequestedFormat<'_>,
+    ) -> NokhwaResult<Vec<(CameraInformation, Option<FrameBuffer>)>> {
+        let devices = self.query()?;
+
+        Ok(devices
+            .into_iter()
+            .map(|device| {
+                let thumbnail = self
+                    .open(device.index())
+                    .and_then(|mut camera| grab_thumbnail(&mut camera, format))
+                    .ok();
+                (device, thumbnail)
+            })
+            .collect())
+    }
+}
+
+// Opens a stream on `camera` in the format chosen by `format` just long enough to take one frame, then puts the
+// camera back in the format it had.
+fn grab_thumbnail<C: Camera>(camera: &mut C, format: RequestedFormat<'_>) -> NokhwaResult<FrameBuffer> {
+    let previous = camera.camera_format();
+    camera.set_requested_format(format)?;
+
+    let frame = camera.open_stream().and_then(|stream| {
+        let frame = stream.poll_frame_timeout(THUMBNAIL_TIMEOUT);
+        drop(stream);
+        camera.close_stream()?;
+        frame
+    });
+
+    if let Some(previous) = previous {
+        camera.set_format(previous)?;
+    }
+    frame
+}
+
+pub(crate) fn format_not_fulfilled() -> NokhwaError {
+    NokhwaError::GetPropertyError {
+        property: "CameraFormat".to_string(),
+        error: "no format fulfills the request".to_string(),
+    }
+}
+
+/// Options for opening a camera with [`PlatformTrait::open_with`].
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct OpenOptions {
+    /// Open the camera only to read its formats and controls, without taking the exclusive access
+    /// streaming needs, so other applications can keep using it. See [`Capture::is_read_only`](crate::camera::Capture::is_read_only).
+    pub read_only: bool,
+}
+
+impl OpenOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+}
+
+/// The object-safe part of [`PlatformTrait`] needed to list and open devices, so platforms of different types can
+/// be used together with [`query_all`] and [`open_first_available`]. Every [`PlatformTrait`] implements this.
+pub trait PlatformQuery {
+    fn backend(&self) -> Backends;
+
+    /// See [`PlatformTrait::query`].
+    /// # Errors
+    /// Errors if the platform's device enumeration fails.
+    fn query_devices(&mut self) -> NokhwaResult<Vec<CameraInformation>>;
+
+    /// See [`PlatformTrait::open`].
+    /// # Errors
+    /// Errors if the device does not exist or cannot be opened.
+    fn open_device(&mut self, index: &CameraIndex) -> NokhwaResult<Box<dyn Camera>>;
+}
+
+impl<T> PlatformQuery for T
+where
+    T: PlatformTrait,
+    T::Camera: 'static,
+{
+    fn backend(&self) -> Backends {
+        T::PLATFORM
+    }
+
+    fn query_devices(&mut self) -> NokhwaResult<Vec<CameraInformation>> {
+        PlatformTrait::query(self)
+    }
+
+    fn open_device(&mut self, index: &CameraIndex) -> NokhwaResult<Box<dyn Camera>> {
+        let camera = PlatformTrait::open(self, index)?;
+        Ok(Box::new(camera))
+    }
+}
+
+/// Queries every platform in `platforms` and merges the results, tagging each device with the backend that found it
+/// (see [`CameraInformation::backend`]).
+///
+/// Devices are deduplicated by their unique id (the `misc` field, or the name if a backend leaves it empty).
+/// When several backends see the same device, the first platform in `platforms` wins, so list them in order of preference.
+///
+/// A platform that errors or panics while querying (e.g. because its system library is missing) is skipped, so one
+/// broken backend does not hide the devices the others found.
+/// # Errors
+/// If every platform fails to query, this returns a [`NokhwaError::GeneralError`] listing why each one was
+/// unavailable.
+pub fn query_all(platforms: &mut [&mut dyn PlatformQuery]) -> NokhwaResult<Vec<CameraInformation>> {
+    let mut seen = HashSet::new();
+    let mut devices = Vec::new();
+    let mut failures = Vec::new();
+
+    for platform in platforms.iter_mut() {
+        let backend = platform.backend();
+        let found = match panic::catch_unwind(AssertUnwindSafe(|| platform.query_devices())) {
+            Ok(Ok(found)) => found,
+            Ok(Err(why)) => {
+                failures.push(NokhwaError::backend_unavailable(backend, why).to_string());
+                continue;
+            }
+            Err(_) => {
+                failures.push(NokhwaError::backend_unavailable(backend, "panicked while querying").to_string());
+                continue;
+            }
+        };
+
+        for mut device in found {
+            let unique_id = if device.misc().is_empty() {
+                device.human_name()
+            } else {
+                device.misc()
+            };
+            if seen.insert(unique_id) {
+                device.set_backend(Some(backend));
+                devices.push(device);
+            }
+        }
+    }
+
+    if !failures.is_empty() && failures.len() == platforms.len() {
+        return Err(NokhwaError::GeneralError(format!(
+            "every backend failed to query: {}",
+            failures.join(", ")
+        )));
+    }
+
+    Ok(devices)
+}
+
+/// Tries each backend constructor in order of preference, returning the first that succeeds along with its [`Backends`].
+///
+/// A backend that errors or panics while starting (e.g. because its system library is missing) is skipped,
+/// so the next one gets a chance instead of taking the whole application down.
+/// # Errors
+/// If every backend fails, this returns a [`NokhwaError::GeneralError`] listing why each one was unavailable.
+pub fn resolve_first_available<T, F>(
+    candidates: impl IntoIterator<Item = (Backends, F)>,
+) -> NokhwaResult<(Backends, T)>
+where
+    F: FnOnce() -> NokhwaResult<T>,
+{
+    let mut failures = Vec::new();
+
+    for (backend, constructor) in candidates {
+        match panic::catch_unwind(AssertUnwindSafe(constructor)) {
+            Ok(Ok(value)) => return Ok((backend, value)),
+            Ok(Err(why)) => failures.push(NokhwaError::backend_unavailable(backend, why).to_string()),
+            Err(_) => failures.push(NokhwaError::backend_unavailable(backend, "panicked while starting").to_string()),
+        }
+    }
+
+    if failures.is_empty() {
+        return Err(NokhwaError::GeneralError(
+            "no backends are compiled in".to_string(),
+        ));
+    }
+
+    Err(NokhwaError::GeneralError(format!(
+        "no backend available: {}",
+        failures.join(", ")
+    )))
+}
+
+/// Opens the camera at `index` on the first platform in `platforms` that can, returning it along with the
+/// [`Backends`] that opened it. List the platforms in order of preference.
+///
+/// A platform that errors or panics while opening (e.g. because its system library is missing) is skipped, see
+/// [`resolve_first_available`].
+/// # Errors
+/// If no platform can open the camera, this returns a [`NokhwaError::GeneralError`] listing why each one was
+/// unavailable.
+pub fn open_first_available(
+    platforms: &mut [&mut dyn PlatformQuery],
+    index: &CameraIndex,
+) -> NokhwaResult<(Backends, Box<dyn Camera>)> {
+    resolve_first_available(
+        platforms
+            .iter_mut()
+            .map(|platform| (platform.backend(), move || platform.open_device(index))),
+    )
 }
 
 #[cfg(feature = "async")]
@@ -36,4 +287,145 @@ pub trait AsyncPlatformTrait {
     async fn query_async(&mut self) -> NokhwaResult<Vec<CameraInformation>>;
 
     async fn open_async (&mut self, index: &CameraIndex) -> NokhwaResult<Self::AsyncCamera>;
-}
\ No newline at end of file
+
+    /// Opens the camera at `index`, sets the format that best fulfills `format`, and starts streaming, yielding the
+    /// frames as a [`futures::Stream`].
+    ///
+    /// The returned [`FrameStream`] owns the camera, and closes the stream when dropped.
+    ///
+    /// # Errors
+    /// Errors if the camera fails to open, reports no formats ([`NokhwaError::no_formats_reported`]), no format
+    /// fulfills `format`, or the stream fails to open.
+    // Like the rest of this trait, this makes no `Send` promise about its future: it awaits the platform's own
+    // `async fn`s, so it could not promise more than they do.
+    #[allow(async_fn_in_trait)]
+    async fn open_async_stream(
+        &mut self,
+        index: &CameraIndex,
+        format: RequestedFormat<'_>,
+    ) -> NokhwaResult<FrameStream<Self::AsyncCamera>> {
+        let mut camera = self.open_async(index).await?;
+        let formats = camera.enumerate_formats_async().await?;
+        if formats.is_empty() {
+            return Err(NokhwaError::no_formats_reported());
+        }
+        let camera_format = format.fulfill(&formats).ok_or_else(format_not_fulfilled)?;
+        camera.set_format_async(camera_format).await?;
+
+        let stream = camera.open_stream_async().await?;
+        Ok(FrameStream::new(camera, stream))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A platform that lists fixed devices, or fails to.
+    struct MockPlatform {
+        backend: Backends,
+        devices: NokhwaResult<Vec<CameraInformation>>,
+    }
+
+    impl MockPlatform {
+        fn new(backend: Backends, ids: &[&str]) -> Self {
+            let devices = ids
+                .iter()
+                .enumerate()
+                .map(|(index, id)| {
+                    CameraInformation::new(
+                        format!("Camera {id}"),
+                        String::new(),
+                        (*id).to_string(),
+                        CameraIndex::Index(index as u32),
+                    )
+                })
+                .collect();
+            MockPlatform { backend, devices: Ok(devices) }
+        }
+
+        fn failing(backend: Backends) -> Self {
+            MockPlatform {
+                backend,
+                devices: Err(NokhwaError::GeneralError("no devices for you".to_string())),
+            }
+        }
+    }
+
+    impl PlatformQuery for MockPlatform {
+        fn backend(&self) -> Backends {
+            self.backend
+        }
+
+        fn query_devices(&mut self) -> NokhwaResult<Vec<CameraInformation>> {
+            self.devices.clone()
+        }
+
+        // Stands in for a backend whose system library is missing: it either errors or panics.
+        fn open_device(&mut self, _: &CameraIndex) -> NokhwaResult<Box<dyn Camera>> {
+            if self.backend == Backends::Custom("panics") {
+                panic!("libcamera.so not found");
+            }
+            Err(NokhwaError::GeneralError("no such library".to_string()))
+        }
+    }
+
+    #[test]
+    fn query_all_dedups_and_tags_devices() {
+        let mut first = MockPlatform::new(Backends::Video4Linux2, &["a", "b"]);
+        let mut second = MockPlatform::new(Backends::Custom("mock"), &["b", "c"]);
+
+        let devices = query_all(&mut [&mut first, &mut second]).unwrap();
+        let tagged = devices
+            .iter()
+            .map(|device| (device.misc(), device.backend()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tagged,
+            [
+                ("a".to_string(), Some(Backends::Video4Linux2)),
+                ("b".to_string(), Some(Backends::Video4Linux2)),
+                ("c".to_string(), Some(Backends::Custom("mock"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_all_skips_failing_backends() {
+        let mut broken = MockPlatform::failing(Backends::Video4Linux2);
+        let mut working = MockPlatform::new(Backends::Custom("mock"), &["a"]);
+
+        let devices = query_all(&mut [&mut broken, &mut working]).unwrap();
+        assert_eq!(devices.len(), 1);
+
+        let mut broken_again = MockPlatform::failing(Backends::Custom("mock"));
+        assert!(query_all(&mut [&mut broken, &mut broken_again]).is_err());
+    }
+
+    #[test]
+    fn resolve_first_available_falls_through_unavailable_backends() {
+        let candidates: Vec<(Backends, Box<dyn FnOnce() -> NokhwaResult<u32>>)> = vec![
+            (
+                Backends::Video4Linux2,
+                Box::new(|| Err(NokhwaError::backend_unavailable(Backends::Video4Linux2, "no /dev/video*"))),
+            ),
+            (Backends::Custom("panics"), Box::new(|| panic!("libcamera.so not found"))),
+            (Backends::Custom("works"), Box::new(|| Ok(7))),
+        ];
+
+        assert_eq!(resolve_first_available(candidates).unwrap(), (Backends::Custom("works"), 7));
+    }
+
+    #[test]
+    fn open_first_available_reports_every_unavailable_backend() {
+        let mut missing = MockPlatform::new(Backends::Video4Linux2, &[]);
+        let mut panicking = MockPlatform::new(Backends::Custom("panics"), &[]);
+
+        let Err(NokhwaError::GeneralError(why)) =
+            open_first_available(&mut [&mut missing, &mut panicking], &CameraIndex::Index(0))
+        else {
+            panic!("expected every backend to be unavailable");
+        };
+        assert!(why.contains("backend Video4Linux2 unavailable: "), "{why}");
+        assert!(why.contains("backend Custom(\"panics\") unavailable: panicked while starting"), "{why}");
+    }
+}
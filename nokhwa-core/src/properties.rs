@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::ops::{ControlFlow};
+use std::time::Duration;
 use crate::error::{NokhwaError, NokhwaResult};
 use crate::ranges::{Range, ValidatableRange};
 
@@ -14,6 +15,10 @@ pub enum ControlId {
     FocusAbsolute,
     FocusRelative,
     FocusStatus,
+    /// The point auto focus should focus on, as an `[x, y]` [`ControlValue::Array`] of floats normalized to
+    /// `0.0..=1.0` from the top left of the frame (`focusPointOfInterest` on `AVFoundation`).
+    /// See [`Setting::set_focus_point`](crate::camera::Setting::set_focus_point).
+    FocusPointOfInterest,
 
     ExposureMode,
     ExposureBias,
@@ -24,11 +29,23 @@ pub enum ControlId {
     ExposureApertureAbsolute,
     ExposureApertureRelative,
 
+    /// Overall sensor gain, for devices that expose a single gain control (V4L2's `V4L2_CID_GAIN`).
+    Gain,
+    /// Gain applied in the analog domain, before digitization (V4L2's `V4L2_CID_ANALOGUE_GAIN`).
+    /// Independent of [`ControlId::ExposureIsoSensitivity`] on devices that expose both.
+    AnalogGain,
+    /// Gain applied to the digitized samples (V4L2's `V4L2_CID_DIGITAL_GAIN`).
+    DigitalGain,
+
     WhiteBalanceMode,
     WhiteBalanceTemperature,
 
     ZoomMode,
+    ZoomAbsolute,
     LightingMode,
+    /// Whether HDR / wide dynamic range capture is on, as a [`ControlValue::Boolean`] (V4L2's
+    /// `V4L2_CID_WIDE_DYNAMIC_RANGE`). Devices without it leave it out of their [`Properties`].
+    HdrMode,
     PlatformSpecific(PlatformSpecificControlId)
 }
 
@@ -38,6 +55,41 @@ impl Display for ControlId {
     }
 }
 
+impl ControlId {
+    /// A human readable name for this control, suitable for a settings panel.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let label = match self {
+            ControlId::FocusMode => "Focus Mode",
+            ControlId::FocusAutoType => "Auto Focus Type",
+            ControlId::FocusAutoRange => "Auto Focus Range",
+            ControlId::FocusAbsolute => "Focus",
+            ControlId::FocusRelative => "Focus (Relative)",
+            ControlId::FocusStatus => "Focus Status",
+            ControlId::FocusPointOfInterest => "Focus Point",
+            ControlId::ExposureMode => "Exposure Mode",
+            ControlId::ExposureBias => "Exposure Bias",
+            ControlId::ExposureTime => "Exposure Time",
+            ControlId::ExposureAutoPriority => "Exposure Auto Priority",
+            ControlId::ExposureIsoMode => "ISO Mode",
+            ControlId::ExposureIsoSensitivity => "ISO",
+            ControlId::ExposureApertureAbsolute => "Aperture",
+            ControlId::ExposureApertureRelative => "Aperture (Relative)",
+            ControlId::Gain => "Gain",
+            ControlId::AnalogGain => "Analog Gain",
+            ControlId::DigitalGain => "Digital Gain",
+            ControlId::WhiteBalanceMode => "White Balance Mode",
+            ControlId::WhiteBalanceTemperature => "White Balance Temperature",
+            ControlId::ZoomMode => "Zoom Mode",
+            ControlId::ZoomAbsolute => "Zoom",
+            ControlId::LightingMode => "Lighting Mode",
+            ControlId::HdrMode => "HDR",
+            ControlId::PlatformSpecific(id) => return format!("Platform Control {id:#x}"),
+        };
+        label.to_string()
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Properties {
     controls: HashMap<ControlId, ControlBody>,
@@ -63,6 +115,7 @@ impl Properties {
         if let Some(control) = self.controls.get_mut(control_id) {
             // FIXME: Remove this clone one day!
             control.set_value(value.clone())?;
+            return Ok(());
         }
         Err(NokhwaError::SetPropertyError {
             property: control_id.to_string(),
@@ -70,6 +123,95 @@ impl Properties {
             error: "Not Found/Not Supported".to_string(),
         })
     }
+
+    /// Lists every control with everything a settings panel needs to draw it.
+    ///
+    /// The list is sorted by [`ControlId`], which keeps related controls (focus, exposure, white balance, ...)
+    /// together, with platform specific controls last.
+    #[must_use]
+    pub fn as_ui_list(&self) -> Vec<ControlDescriptorUi> {
+        let mut list = self
+            .controls
+            .iter()
+            .map(|(id, body)| ControlDescriptorUi {
+                id: *id,
+                label: id.label(),
+                control_type: *body.control_type(),
+                descriptor: body.descriptor().clone(),
+                value: body.value().clone(),
+                default_value: body.default_value().clone(),
+                read_only: body.flags().contains(&ControlFlags::ReadOnly),
+                inactive: body.flags().contains(&ControlFlags::Inactive)
+                    || body.flags().contains(&ControlFlags::Disabled),
+            })
+            .collect::<Vec<_>>();
+        list.sort_by_key(|control| control.id);
+        list
+    }
+
+    /// Takes an immutable copy of the current value of every control that has one.
+    #[must_use]
+    pub fn snapshot(&self) -> PropertiesSnapshot {
+        PropertiesSnapshot {
+            values: self
+                .controls
+                .iter()
+                .filter_map(|(id, body)| body.value().clone().map(|value| (*id, value)))
+                .collect(),
+        }
+    }
+}
+
+/// A single control, flattened for display. See [`Properties::as_ui_list`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlDescriptorUi {
+    pub id: ControlId,
+    pub label: String,
+    pub control_type: ControlType,
+    pub descriptor: ControlValueDescriptor,
+    pub value: Option<ControlValue>,
+    pub default_value: Option<ControlValue>,
+    /// The control can be read, but not set.
+    pub read_only: bool,
+    /// The control currently has no effect, e.g. manual exposure while auto exposure is on.
+    pub inactive: bool,
+}
+
+/// An immutable copy of the control values of a [`Properties`], taken with [`Properties::snapshot`].
+///
+/// Snapshots own their values, so they can be freely sent between threads while the camera keeps changing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PropertiesSnapshot {
+    values: HashMap<ControlId, ControlValue>,
+}
+
+impl PropertiesSnapshot {
+    #[must_use]
+    pub fn value(&self, control_id: &ControlId) -> Option<&ControlValue> {
+        self.values.get(control_id)
+    }
+
+    #[must_use]
+    pub fn values(&self) -> &HashMap<ControlId, ControlValue> {
+        &self.values
+    }
+
+    /// Returns the controls whose value in `other` differs from this snapshot, along with the value in `other`.
+    ///
+    /// Applying the result to a camera in this snapshot's state brings it to `other`'s state. Controls that
+    /// only exist in this snapshot are not included, as there is no value to write for them.
+    /// The result is sorted by [`ControlId`].
+    #[must_use]
+    pub fn diff(&self, other: &PropertiesSnapshot) -> Vec<(ControlId, ControlValue)> {
+        let mut changed = other
+            .values
+            .iter()
+            .filter(|(id, value)| self.values.get(id) != Some(value))
+            .map(|(id, value)| (*id, value.clone()))
+            .collect::<Vec<_>>();
+        changed.sort_by_key(|(id, _)| *id);
+        changed
+    }
 }
 
 
@@ -80,6 +222,7 @@ pub struct ControlBody {
     descriptor: ControlValueDescriptor,
     value: Option<ControlValue>,
     default_value: Option<ControlValue>,
+    unit: Option<ControlUnit>,
 }
 
 impl ControlBody {
@@ -90,9 +233,17 @@ impl ControlBody {
             descriptor: control_value_descriptor,
             value,
             default_value,
+            unit: None,
         }
     }
 
+    /// Attaches the [`ControlUnit`] that this control's values are measured in.
+    #[must_use]
+    pub fn with_unit(mut self, unit: ControlUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
     pub fn control_type(&self) -> &ControlType {
         &self.control_type
     }
@@ -113,6 +264,11 @@ impl ControlBody {
         &self.default_value
     }
 
+    #[must_use]
+    pub fn unit(&self) -> Option<ControlUnit> {
+        self.unit
+    }
+
     pub fn add_flag(&mut self, flag: ControlFlags) {
         self.flags.insert(flag);
     }
@@ -123,10 +279,21 @@ impl ControlBody {
 
     pub fn set_value(&mut self, value: ControlValue) -> NokhwaResult<Option<ControlValue>> {
         if let ControlFlow::Break(()) =  self.descriptor.validate(&value) {
+            let error = match (&self.descriptor, &value) {
+                (ControlValueDescriptor::Integer(range), ControlValue::Integer(int)) if !range.is_on_step(*int) => {
+                    // off-step values always have both a step and a minimum
+                    format!(
+                        "Value is not a multiple of the step {} from the minimum {}",
+                        range.step().unwrap_or_default(),
+                        range.minimum().unwrap_or_default()
+                    )
+                }
+                _ => "Failed to validate control value".to_string(),
+            };
             return Err(NokhwaError::SetPropertyError {
                 property: "Control Body".to_string(),
                 value: value.to_string(),
-                error: "Failed to validate control value".to_string(),
+                error,
             })
         }
 
@@ -152,6 +319,15 @@ pub enum ControlType {
     String,
 }
 
+/// The unit a control's numeric values are measured in, if the platform reports one.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ControlUnit {
+    /// Each step of the value is this [`Duration`], e.g. 100µs for V4L2's `V4L2_CID_EXPOSURE_ABSOLUTE`.
+    Time(Duration),
+    Kelvin,
+    Iso,
+}
+
 #[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 pub enum ControlFlags {
     Disabled,
@@ -186,6 +362,34 @@ pub enum ControlValueDescriptor {
 }
 
 impl ControlValueDescriptor {
+    /// Builds a [`ControlValueDescriptor::Menu`] from a platform's `(index, label)` menu items, e.g. from V4L2's
+    /// `VIDIOC_QUERYMENU`. Each label maps to exactly its index, so an item is picked with
+    /// `ControlValue::KeyValue(label, ControlValuePrimitive::Integer(index))`.
+    #[must_use]
+    pub fn menu_from_items(items: impl IntoIterator<Item = (i64, String)>) -> Self {
+        ControlValueDescriptor::Menu(
+            items
+                .into_iter()
+                .map(|(index, label)| (label, ControlValuePrimitiveDescriptor::Integer(Range::exact(index))))
+                .collect(),
+        )
+    }
+
+    /// Looks up the label of the menu item at `index`, for showing a control's raw integer value by name.
+    ///
+    /// Returns `None` if this is not a [`ControlValueDescriptor::Menu`] or no item has that index.
+    #[must_use]
+    pub fn menu_label(&self, index: i64) -> Option<&str> {
+        let ControlValueDescriptor::Menu(menu) = self else {
+            return None;
+        };
+
+        menu.iter().find_map(|(label, descriptor)| match descriptor {
+            ControlValuePrimitiveDescriptor::Integer(range) if range.preferred() == index => Some(label.as_str()),
+            _ => None,
+        })
+    }
+
     pub fn validate(&self, value: &ControlValue) -> ControlFlow<()> {
         match self {
             ControlValueDescriptor::Null => {
@@ -195,7 +399,9 @@ impl ControlValueDescriptor {
             }
             ControlValueDescriptor::Integer(int_range) => {
                 if let ControlValue::Integer(i) = value {
-                    int_range.validate(i)?;
+                    if int_range.validate(i).is_ok() {
+                        return ControlFlow::Continue(())
+                    }
                 }
             }
             ControlValueDescriptor::BitMask => {
@@ -205,7 +411,9 @@ impl ControlValueDescriptor {
             }
             ControlValueDescriptor::Float(float_range) => {
                 if let ControlValue::Float(i) = value {
-                    float_range.validate(i)?;
+                    if float_range.validate(i).is_ok() {
+                        return ControlFlow::Continue(())
+                    }
                 }
             }
             ControlValueDescriptor::String => {
@@ -219,12 +427,14 @@ impl ControlValueDescriptor {
                 }
             }
             ControlValueDescriptor::Array(arr) => {
-                if arr.is_valid_value(value) {
-                    return ControlFlow::Continue(())
+                if let ControlValue::Array(values) = value {
+                    if values.iter().all(|v| arr.is_valid_value(v.as_ref())) {
+                        return ControlFlow::Continue(())
+                    }
                 }
             }
             ControlValueDescriptor::MultiChoice(choices) => {
-                if let &ControlValue::Array(values) = value {
+                if let ControlValue::Array(values) = value {
                     for v in values {
                         let mut contains = false;
                         for choice in choices {
@@ -237,6 +447,7 @@ impl ControlValueDescriptor {
                             return ControlFlow::Break(())
                         }
                     }
+                    return ControlFlow::Continue(())
                 }
             }
             ControlValueDescriptor::Enum(choices) => {
@@ -255,6 +466,7 @@ impl ControlValueDescriptor {
                             }
                         }
                     }
+                    return ControlFlow::Continue(())
                 }
             }
             ControlValueDescriptor::Menu(menu) => {
@@ -412,3 +624,131 @@ impl From<ControlValuePrimitive> for ControlValue {
         }
     }
 }
+
+/// Defines a `ControlIdIntermediate` newtype over a platform's control ID type, converting to and from
+/// [`ControlId`] through a table of `ControlId::Variant => platform_value` pairs.
+///
+/// Platform controls missing from the table convert to [`ControlId::PlatformSpecific`] with
+/// `$func_to_platform_specific`, and back with `$func_from_platform_specific`, so every control the device has can
+/// still be reached.
+#[macro_export]
+macro_rules! define_back_and_fourth_control {
+    ($control_type:ty, { $( $control_id:path => $value:expr, )* }, $func_to_platform_specific:expr, $func_from_platform_specific:expr) => {
+        pub struct ControlIdIntermediate(pub $control_type);
+
+        impl ControlIdIntermediate {
+            pub fn from_control_id(control_id: ControlId) -> Option<Self> {
+                match control_id {
+                    $(
+                        $control_id => Some(Self($value)),
+                    )*
+                    ControlId::PlatformSpecific(id) => $func_from_platform_specific(id).map(Self),
+                    #[allow(unreachable_patterns)]
+                    _ => None,
+                }
+            }
+
+            pub fn into_control_id(value: $control_type) -> ControlId {
+                match value {
+                    $(
+                        v if v == $value => $control_id,
+                    )*
+                    v => ControlId::PlatformSpecific($func_to_platform_specific(v)),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_platform_specific(id: u32) -> PlatformSpecificControlId {
+        PlatformSpecificControlId::from(id)
+    }
+
+    fn from_platform_specific(id: PlatformSpecificControlId) -> Option<u32> {
+        u32::try_from(id).ok()
+    }
+
+    define_back_and_fourth_control!(u32, {
+        ControlId::Gain => 1,
+        ControlId::HdrMode => 2,
+    }, to_platform_specific, from_platform_specific);
+
+    #[test]
+    fn control_table_round_trips() {
+        assert_eq!(ControlIdIntermediate::from_control_id(ControlId::Gain).map(|id| id.0), Some(1));
+        assert_eq!(ControlIdIntermediate::into_control_id(2), ControlId::HdrMode);
+        assert!(ControlIdIntermediate::from_control_id(ControlId::ZoomAbsolute).is_none());
+    }
+
+    #[test]
+    fn unmapped_controls_are_platform_specific() {
+        assert_eq!(ControlIdIntermediate::into_control_id(7), ControlId::PlatformSpecific(7));
+        assert_eq!(
+            ControlIdIntermediate::from_control_id(ControlId::PlatformSpecific(7)).map(|id| id.0),
+            Some(7)
+        );
+        assert!(ControlIdIntermediate::from_control_id(ControlId::PlatformSpecific(u64::MAX)).is_none());
+    }
+
+    #[test]
+    fn hdr_mode_is_a_boolean_control() {
+        // As V4L2 describes V4L2_CID_WIDE_DYNAMIC_RANGE.
+        let hdr = ControlBody::new(
+            ControlType::BinaryMenu,
+            HashSet::new(),
+            ControlValueDescriptor::Boolean,
+            Some(ControlValue::Boolean(false)),
+            Some(ControlValue::Boolean(false)),
+        );
+        let mut properties = Properties::new(HashMap::from([(ControlId::HdrMode, hdr)]));
+        assert_eq!(ControlId::HdrMode.label(), "HDR");
+
+        properties.set_control_value(&ControlId::HdrMode, ControlValue::Boolean(true)).unwrap();
+        assert_eq!(
+            properties.control_value(&ControlId::HdrMode).unwrap().value(),
+            &Some(ControlValue::Boolean(true))
+        );
+        assert!(properties.set_control_value(&ControlId::HdrMode, ControlValue::Integer(1)).is_err());
+        assert_eq!(
+            properties.control_value(&ControlId::HdrMode).unwrap().value(),
+            &Some(ControlValue::Boolean(true))
+        );
+
+        // Devices without HDR leave it out.
+        assert!(Properties::empty().set_control_value(&ControlId::HdrMode, ControlValue::Boolean(true)).is_err());
+    }
+
+    fn integer_control(value: i64) -> ControlBody {
+        ControlBody::new(
+            ControlType::Integer,
+            HashSet::new(),
+            ControlValueDescriptor::Integer(Range::new(value, Some(-100), Some(100), Some(1))),
+            Some(ControlValue::Integer(value)),
+            Some(ControlValue::Integer(value)),
+        )
+    }
+
+    #[test]
+    fn snapshot_diffs_list_only_changed_controls() {
+        // V4L2_CID_BRIGHTNESS and V4L2_CID_CONTRAST
+        let brightness = ControlId::PlatformSpecific(0x0098_0900);
+        let contrast = ControlId::PlatformSpecific(0x0098_0901);
+        let mut properties = Properties::new(HashMap::from([
+            (brightness, integer_control(0)),
+            (contrast, integer_control(0)),
+        ]));
+
+        let before = properties.snapshot();
+        properties.set_control_value(&brightness, ControlValue::Integer(40)).unwrap();
+        properties.set_control_value(&contrast, ControlValue::Integer(0)).unwrap();
+        let after = properties.snapshot();
+
+        assert_eq!(before.diff(&after), [(brightness, ControlValue::Integer(40))]);
+        assert_eq!(after.diff(&before), [(brightness, ControlValue::Integer(0))]);
+        assert!(after.diff(&after).is_empty());
+    }
+}
@@ -22,6 +22,7 @@ pub trait ValidatableRange {
 ///
 /// Inclusive by default.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Range<T> {
     minimum: Option<T>,
     lower_inclusive: bool,
@@ -62,11 +63,12 @@ impl<T> Range<T> where T: Copy {
         }
     }
 
+    /// Create a [`Range`] that only accepts `preferred`.
     pub fn exact(preferred: T) -> Self {
         Self {
-            minimum: None,
+            minimum: Some(preferred),
             lower_inclusive: true,
-            maximum: None,
+            maximum: Some(preferred),
             upper_inclusive: true,
             preferred,
             step: None,
@@ -91,26 +93,111 @@ impl<T> Range<T> where T: Copy {
     pub fn set_preferred(&mut self, preferred: T) {
         self.preferred = preferred;
     }
+    /// The lowest value in the range, or `None` if it is unbounded below. Whether the value itself is allowed is
+    /// [`lower_inclusive`](Range::lower_inclusive).
+    #[must_use]
     pub fn minimum(&self) -> Option<T> {
         self.minimum
     }
+    #[must_use]
     pub fn lower_inclusive(&self) -> bool {
         self.lower_inclusive
     }
+    /// The highest value in the range, or `None` if it is unbounded above. Whether the value itself is allowed is
+    /// [`upper_inclusive`](Range::upper_inclusive).
+    #[must_use]
     pub fn maximum(&self) -> Option<T> {
         self.maximum
     }
+    #[must_use]
     pub fn upper_inclusive(&self) -> bool {
         self.upper_inclusive
     }
+    /// The value to use when there is no other preference: the control's default for a
+    /// [`ControlValueDescriptor`](crate::properties::ControlValueDescriptor), or the target of a format request.
+    #[must_use]
     pub fn preferred(&self) -> T {
         self.preferred
     }
+    /// The increment values in the range move in, counted from the minimum, or `None` if any value is allowed.
+    #[must_use]
     pub fn step(&self) -> Option<T> {
         self.step
     }
 }
 
+impl Range<i64> {
+    /// Whether `value` is a whole number of `step`s away from the minimum, e.g. a V4L2 brightness control that only
+    /// moves in increments of 4. Ranges without a step or a minimum accept every value.
+    #[must_use]
+    pub fn is_on_step(&self, value: i64) -> bool {
+        match (self.step.filter(|step| *step > 0), self.minimum) {
+            (Some(step), Some(min)) => (i128::from(value) - i128::from(min)) % i128::from(step) == 0,
+            _ => true,
+        }
+    }
+
+    /// Clamps `value` into this range, then rounds it to the nearest `step` counted from the minimum.
+    ///
+    /// Exclusive bounds are treated as the next integer inside them.
+    #[must_use]
+    pub fn clamp_value(&self, value: i64) -> i64 {
+        let lower = self
+            .minimum
+            .map(|min| if self.lower_inclusive { min } else { min.saturating_add(1) });
+        let upper = self
+            .maximum
+            .map(|max| if self.upper_inclusive { max } else { max.saturating_sub(1) });
+
+        let mut clamped = value;
+        if let Some(lower) = lower {
+            clamped = clamped.max(lower);
+        }
+        if let Some(upper) = upper {
+            clamped = clamped.min(upper);
+        }
+
+        if let (Some(step), Some(min)) = (self.step.filter(|step| *step > 0), self.minimum) {
+            let steps = (clamped.saturating_sub(min).saturating_add(step / 2)) / step;
+            clamped = min.saturating_add(steps.saturating_mul(step));
+            // rounding up may have stepped past the top (or an exclusive bottom), step back inside
+            if upper.is_some_and(|upper| clamped > upper) {
+                clamped -= step;
+            }
+            if lower.is_some_and(|lower| clamped < lower) {
+                clamped += step;
+            }
+        }
+
+        clamped
+    }
+}
+
+impl Range<f64> {
+    /// Clamps `value` into this range, then rounds it to the nearest `step` counted from the minimum.
+    ///
+    /// Exclusive bounds clamp to the bound itself, so the result may still fail validation against them.
+    #[must_use]
+    pub fn clamp_value(&self, value: f64) -> f64 {
+        let mut clamped = value;
+        if let Some(min) = self.minimum {
+            clamped = clamped.max(min);
+        }
+        if let Some(max) = self.maximum {
+            clamped = clamped.min(max);
+        }
+
+        if let (Some(step), Some(min)) = (self.step.filter(|step| *step > 0.0), self.minimum) {
+            clamped = min + ((clamped - min) / step).round() * step;
+            if self.maximum.is_some_and(|max| clamped > max) {
+                clamped -= step;
+            }
+        }
+
+        clamped
+    }
+}
+
 impl<T> ValidatableRange for Range<T>
 where
     T: SimpleRangeItem,
@@ -153,13 +240,18 @@ where
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let lower_inclusive_char = bool_to_inclusive_char(self.lower_inclusive, false);
         let upper_inclusive_char = bool_to_inclusive_char(self.upper_inclusive, true);
+        let minimum = bound_to_string(self.minimum.as_ref());
+        let maximum = bound_to_string(self.maximum.as_ref());
         let default = &self.preferred;
 
         write!(
             f,
-            "Range: {lower_inclusive_char}{:?}, {:?}{upper_inclusive_char}, Preferred: {default:?}",
-            self.minimum, self.maximum
-        )
+            "Range: {lower_inclusive_char}{minimum}, {maximum}{upper_inclusive_char}, "
+        )?;
+        if let Some(step) = &self.step {
+            write!(f, "Step: {step:?}, ")?;
+        }
+        write!(f, "Preferred: {default:?}")
     }
 }
 
@@ -199,7 +291,7 @@ where
         if self.available.contains(value) {
             return Ok(());
         }
-        Err(RangeValidationFailure::default())
+        Err(RangeValidationFailure)
     }
 }
 
@@ -298,7 +390,7 @@ where
         if self.appendable_options.contains(value) {
             return Ok(());
         }
-        Err(RangeValidationFailure::default())
+        Err(RangeValidationFailure)
     }
 }
 
@@ -382,6 +474,17 @@ where
     }
 }
 
+// An unbounded end of a `Range` is shown as `..`.
+fn bound_to_string<T>(bound: Option<&T>) -> String
+where
+    T: Debug,
+{
+    match bound {
+        Some(v) => format!("{v:?}"),
+        None => String::from(".."),
+    }
+}
+
 fn num_range_validate<T>(
     minimum: Option<T>,
     maximum: Option<T>,
@@ -394,19 +497,6 @@ fn num_range_validate<T>(
 where
     T: SimpleRangeItem,
 {
-
-    if let (Some(step), Some(min)) = (step, minimum) {
-        let prepared_value: T = value - min;
-        // We can check the step if we subtract the value from the minimum value
-        // then see if the remainder of prepared value and step is zero.
-        // e.g. 4, 12, value is 7, step is 3
-        // 7 - 4 = 3
-        // 3 % 3 = 0 Valid!
-        if prepared_value % step != T::ZERO {
-            return Err(RangeValidationFailure::default());
-        }
-    }
-
     if value == default {
         return Ok(());
     }
@@ -417,8 +507,8 @@ where
         } else {
             min < value
         };
-        if test {
-            return Err(RangeValidationFailure::default());
+        if !test {
+            return Err(RangeValidationFailure);
         }
     }
 
@@ -428,8 +518,21 @@ where
         } else {
             max > value
         };
-        if test {
-            return Err(RangeValidationFailure::default());
+        if !test {
+            return Err(RangeValidationFailure);
+        }
+    }
+
+    // This is checked after the bounds so that `value - min` cannot underflow.
+    if let (Some(step), Some(min)) = (step, minimum) {
+        let prepared_value: T = value - min;
+        // We can check the step if we subtract the value from the minimum value
+        // then see if the remainder of prepared value and step is zero.
+        // e.g. 4, 12, value is 7, step is 3
+        // 7 - 4 = 3
+        // 3 % 3 = 0 Valid!
+        if prepared_value % step != T::ZERO {
+            return Err(RangeValidationFailure);
         }
     }
 
@@ -456,4 +559,52 @@ impl SimpleRangeItem for f32 {
 
 impl SimpleRangeItem for f64 {
     const ZERO: Self = 0_f64;
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::properties::ControlValueDescriptor;
+
+    #[test]
+    fn control_ranges_expose_their_bounds() {
+        let brightness = ControlValueDescriptor::Integer(Range::new(128, Some(0), Some(255), Some(1)));
+        let ControlValueDescriptor::Integer(range) = brightness else {
+            unreachable!()
+        };
+
+        assert_eq!(range.minimum(), Some(0));
+        assert_eq!(range.maximum(), Some(255));
+        assert_eq!(range.preferred(), 128);
+        assert_eq!(range.step(), Some(1));
+    }
+
+    #[test]
+    fn ranges_validate_bounds_and_steps() {
+        let range = Range::new(10, Some(4), Some(16), Some(3));
+        assert!(range.validate(&4).is_ok());
+        assert!(range.validate(&13).is_ok());
+        assert!(range.validate(&16).is_ok());
+        assert!(range.validate(&11).is_err());
+        assert!(range.validate(&1).is_err());
+        assert!(range.validate(&19).is_err());
+        // The preferred value is always accepted, even off step.
+        assert!(range.validate(&10).is_ok());
+
+        let exclusive = Range::with_inclusive(5, Some(0), false, Some(10), false, None);
+        assert!(exclusive.validate(&0).is_err());
+        assert!(exclusive.validate(&10).is_err());
+        assert!(exclusive.validate(&9).is_ok());
+    }
+
+    #[test]
+    fn ranges_display_their_bounds() {
+        assert_eq!(
+            Range::new(128, Some(0), Some(255), Some(1)).to_string(),
+            "Range: [0, 255], Step: 1, Preferred: 128"
+        );
+        assert_eq!(
+            Range::with_inclusive(5, Some(0), false, None, true, None).to_string(),
+            "Range: (0, ..], Preferred: 5"
+        );
+    }
+}
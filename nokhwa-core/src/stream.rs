@@ -1,30 +1,427 @@
+#[cfg(feature = "async")]
+use crate::camera::Capture;
+use crate::capture_thread::{set_current_thread_affinity, set_current_thread_priority, ThreadPriority};
+use crate::decoder::Decoder;
 use crate::error::{NokhwaError, NokhwaResult};
 use crate::frame_buffer::FrameBuffer;
-use flume::{Receiver, TryRecvError};
-use std::sync::Arc;
+use crate::types::CameraFormat;
+use flume::{Receiver, RecvTimeoutError, Sender, TryRecvError, TrySendError};
+use image::{ImageBuffer, Pixel, Primitive};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-pub trait StreamInnerTrait {
+// How often `Stream::wait_for_first_frame` checks for a frame.
+const FIRST_FRAME_POLL_INTERVAL: Duration = Duration::from_millis(5);
+// How many of the latest frames `Stream::measured_fps` averages over.
+const FPS_WINDOW: usize = 30;
+// How often the thread behind `Stream::on_decoded_frame` checks whether it was stopped.
+const CALLBACK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How a backend moves frames from the device into memory.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum IoMethod {
+    /// Driver-allocated buffers mapped into this process. Supported everywhere.
+    #[default]
+    Mmap,
+    /// Buffers allocated by the application and handed to the driver (V4L2 `V4L2_MEMORY_USERPTR`).
+    UserPtr,
+    /// Driver buffers exported as dma-buf file descriptors, to share frames zero-copy with a GPU or encoder.
+    /// See [`FrameBuffer::dmabuf_fd`].
+    DmaBuf,
+    /// Frames copied out of the driver with `read()` (V4L2 `V4L2_CAP_READWRITE`), for devices that cannot stream.
+    /// Costs a copy per frame. See [`DeviceCapabilities::preferred_io_method`](crate::types::DeviceCapabilities::preferred_io_method).
+    Read,
+}
+
+/// Options for opening a [`Stream`] with [`Capture::open_stream_with`](crate::camera::Capture::open_stream_with).
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct StreamConfig {
+    pub io_method: IoMethod,
+    /// If set, opening fails with [`NokhwaError::ReadFrameError`] unless the first frame arrives within this time,
+    /// rather than leaving the first [`Stream::poll_frame`] blocked on a device that never delivers.
+    pub first_frame_timeout: Option<Duration>,
+    /// Priority for the backend's capture thread. `None` leaves it as the OS started it.
+    pub thread_priority: Option<ThreadPriority>,
+    /// CPUs the backend's capture thread may run on, bit `n` being CPU `n`. `None` leaves it unpinned.
+    pub cpu_affinity: Option<u64>,
+}
+
+impl StreamConfig {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_io_method(mut self, io_method: IoMethod) -> Self {
+        self.io_method = io_method;
+        self
+    }
+
+    #[must_use]
+    pub fn with_first_frame_timeout(mut self, timeout: Duration) -> Self {
+        self.first_frame_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn with_thread_priority(mut self, priority: ThreadPriority) -> Self {
+        self.thread_priority = Some(priority);
+        self
+    }
+
+    #[must_use]
+    pub fn with_cpu_affinity(mut self, cpu_mask: u64) -> Self {
+        self.cpu_affinity = Some(cpu_mask);
+        self
+    }
+
+    /// Applies [`thread_priority`](StreamConfig::thread_priority) and [`cpu_affinity`](StreamConfig::cpu_affinity)
+    /// to the calling thread. Backends call this first thing on their capture thread.
+    ///
+    /// These are best-effort: raising priority commonly needs privileges, so backends should log or ignore the
+    /// error rather than fail the stream.
+    /// # Errors
+    /// Errors if the OS refuses either setting, see [`set_current_thread_priority`] and
+    /// [`set_current_thread_affinity`].
+    pub fn apply_to_current_thread(&self) -> NokhwaResult<()> {
+        if let Some(priority) = self.thread_priority {
+            set_current_thread_priority(priority)?;
+        }
+        if let Some(cpu_mask) = self.cpu_affinity {
+            set_current_thread_affinity(cpu_mask)?;
+        }
+        Ok(())
+    }
+}
+
+/// The backend half of a [`Stream`].
+///
+/// Implementations must own everything they use, moving or [`Arc`]-sharing the device state in rather than
+/// borrowing it from the camera, so that a [`Stream`] can outlive the call that opened it.
+pub trait StreamInnerTrait: 'static {
     fn receiver(&self) -> Arc<Receiver<FrameBuffer>>;
     fn stop(&mut self) -> NokhwaResult<()>;
+
+    /// The [`IoMethod`] this stream was opened with.
+    fn io_method(&self) -> IoMethod {
+        IoMethod::Mmap
+    }
+
+    /// The format the device was negotiated to when this stream opened, if the backend knows it.
+    ///
+    /// When this returns `Some`, the [`Stream`] checks every frame against it, and returns
+    /// [`NokhwaError::FormatChanged`] instead of a frame the driver renegotiated behind its back.
+    fn format(&self) -> Option<CameraFormat> {
+        None
+    }
+
+    /// Returns the backend's side of [`Stream::stats`]: the frames it dropped and failed to capture. Backends that
+    /// keep a [`StreamStatsRecorder`] should return its [`StreamStatsRecorder::stats`]; the default reports nothing.
+    ///
+    /// The [`Stream`] counts delivered frames and their interval itself as it hands them out, so those are the
+    /// same for every backend.
+    fn stats(&self) -> StreamStats {
+        StreamStats::default()
+    }
+
+    /// Suspends frame delivery while keeping the negotiated format and buffers.
+    /// Backends that can stop the device itself (e.g. V4L2's `VIDIOC_STREAMOFF`) should override this;
+    /// by default the [`Stream`] just discards frames until [`resume`](StreamInnerTrait::resume).
+    ///
+    /// # Errors
+    /// Errors if the device could not be suspended.
+    fn pause(&mut self) -> NokhwaResult<()> {
+        Ok(())
+    }
+
+    /// Undoes [`pause`](StreamInnerTrait::pause).
+    ///
+    /// # Errors
+    /// Errors if the device could not be restarted.
+    fn resume(&mut self) -> NokhwaResult<()> {
+        Ok(())
+    }
+}
+
+/// Delivery statistics of a [`Stream`], see [`Stream::stats`].
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Frames handed out to the application.
+    pub delivered: u64,
+    /// Frames thrown away because the consumer did not keep up.
+    pub dropped: u64,
+    /// Frames the backend failed to capture, or that did not match the stream's format.
+    pub errors: u64,
+    /// Time between the two most recently delivered frames being handed out.
+    pub last_frame_interval: Duration,
+}
+
+/// Thread-safe counters a backend's capture loop updates to produce [`StreamStats`].
+///
+/// Share it between the capture thread and the [`StreamInnerTrait`] implementation with an [`Arc`].
+#[derive(Debug, Default)]
+pub struct StreamStatsRecorder {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    errors: AtomicU64,
+    // (time of last delivered frame, interval before it)
+    timing: Mutex<(Option<Instant>, Duration)>,
 }
 
+impl StreamStatsRecorder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_delivered(&self) {
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+
+        let now = Instant::now();
+        if let Ok(mut timing) = self.timing.lock() {
+            if let Some(last) = timing.0 {
+                timing.1 = now.duration_since(last);
+            }
+            timing.0 = Some(now);
+        }
+    }
+
+    pub fn record_dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sends `frame` without blocking, counting it as delivered, or as dropped if the channel is full.
+    ///
+    /// Frames without a [`timestamp`](FrameBuffer::timestamp) are stamped with the current time.
+    ///
+    /// Returns `false` if the receiving [`Stream`] is gone and the capture loop should stop.
+    pub fn send_frame(&self, sender: &Sender<FrameBuffer>, frame: FrameBuffer) -> bool {
+        let frame = match frame.timestamp() {
+            Some(_) => frame,
+            None => frame.with_timestamp(Instant::now()),
+        };
+
+        match sender.try_send(frame) {
+            Ok(()) => {
+                self.record_delivered();
+                true
+            }
+            Err(TrySendError::Full(_)) => {
+                self.record_dropped();
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> StreamStats {
+        let last_frame_interval = self
+            .timing
+            .lock()
+            .map(|timing| timing.1)
+            .unwrap_or_default();
+
+        StreamStats {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            last_frame_interval,
+        }
+    }
+}
+
+/// A running capture from a camera, opened with [`Capture::open_stream`](crate::camera::Capture::open_stream).
+///
+/// A `Stream` does not borrow the camera it came from, so it can be returned from a function or stored in a struct
+/// next to the camera.
+///
+/// Frames are handed out as the backend delivered them and are never decoded, so compressed frames can be recorded
+/// without a decoder, see [`FrameBuffer::requires_decode`].
 pub struct Stream {
     inner: Box<dyn StreamInnerTrait>,
+    paused: bool,
+    // Fingerprint of the last frame handed out, while duplicate detection is on.
+    last_fingerprint: Option<Mutex<Option<u64>>>,
+    // Timestamps of the latest frames handed out, while frame rate measurement is on.
+    frame_times: Option<Mutex<VecDeque<Instant>>>,
+    // Frames handed out and rejected on the receiving side, whatever the backend counts.
+    received: StreamStatsRecorder,
 }
 
 impl Stream {
     pub fn new(inner: Box<dyn StreamInnerTrait>) -> Self {
         Self {
             inner,
+            paused: false,
+            last_fingerprint: None,
+            frame_times: None,
+            received: StreamStatsRecorder::new(),
+        }
+    }
+
+    /// Turns on flagging of repeated frames: each frame is compared to the one before it by a fingerprint of
+    /// sampled luma, and flagged with [`FrameBuffer::is_duplicate_of_previous`] if they match.
+    ///
+    /// Cameras repeat their last frame when they cannot keep up, e.g. on a saturated USB bus. Sampling keeps this
+    /// cheap, but means two frames that differ only between the sampled pixels are also flagged.
+    pub fn set_duplicate_detection(&mut self, enabled: bool) {
+        self.last_fingerprint = enabled.then(|| Mutex::new(None));
+    }
+
+    #[must_use]
+    pub fn duplicate_detection(&self) -> bool {
+        self.last_fingerprint.is_some()
+    }
+
+    /// Turns on measuring the frame rate this stream actually delivers, see [`measured_fps`](Stream::measured_fps).
+    pub fn set_fps_measurement(&mut self, enabled: bool) {
+        self.frame_times = enabled.then(|| Mutex::new(VecDeque::with_capacity(FPS_WINDOW)));
+    }
+
+    #[must_use]
+    pub fn fps_measurement(&self) -> bool {
+        self.frame_times.is_some()
+    }
+
+    /// The frame rate this stream is actually delivering, averaged over the intervals between the timestamps of the
+    /// last 30 frames handed out. This is often lower than the negotiated [`CameraFormat::frame_rate`], e.g. when the
+    /// USB bus is contended or long exposures slow the sensor down.
+    ///
+    /// Frames the application polls too slowly to receive are not counted, so this measures the rate frames reach
+    /// the application. Returns `0.0` until two frames have been received with
+    /// [measurement](Stream::set_fps_measurement) on, or if it is off.
+    #[must_use]
+    pub fn measured_fps(&self) -> f32 {
+        let Some(frame_times) = &self.frame_times else {
+            return 0.0;
+        };
+        let Ok(frame_times) = frame_times.lock() else {
+            return 0.0;
+        };
+
+        match (frame_times.front(), frame_times.back()) {
+            (Some(first), Some(last)) if last > first => {
+                (frame_times.len() - 1) as f32 / last.duration_since(*first).as_secs_f32()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Stops delivering frames without tearing down the stream, so [`resume`](Stream::resume) is instant.
+    ///
+    /// A paused stream behaves as if the camera had stopped sending frames: [`try_poll_frame`](Stream::try_poll_frame)
+    /// returns `Ok(None)`, [`poll_frame_timeout`](Stream::poll_frame_timeout) waits out its timeout, and
+    /// [`poll_frame`](Stream::poll_frame) blocks until the stream is resumed, so do not call it on a paused stream
+    /// from the thread that would resume it. Frames already queued, and any the backend keeps sending while paused,
+    /// are discarded.
+    ///
+    /// # Errors
+    /// Errors if the backend fails to suspend the device.
+    pub fn pause(&mut self) -> NokhwaResult<()> {
+        if self.paused {
+            return Ok(());
+        }
+
+        self.inner.pause()?;
+        self.paused = true;
+        self.inner.receiver().drain();
+        Ok(())
+    }
+
+    /// Resumes frame delivery after [`pause`](Stream::pause). Frames captured while paused are discarded.
+    ///
+    /// # Errors
+    /// Errors if the backend fails to restart the device.
+    pub fn resume(&mut self) -> NokhwaResult<()> {
+        if !self.paused {
+            return Ok(());
+        }
+
+        self.inner.resume()?;
+        self.inner.receiver().drain();
+        self.paused = false;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn io_method(&self) -> IoMethod {
+        self.inner.io_method()
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Runs the checks and tagging every received frame goes through before it is returned.
+    fn process_frame(&self, frame: FrameBuffer) -> NokhwaResult<FrameBuffer> {
+        let frame = self.check_format(frame).inspect_err(|_| self.received.record_error())?;
+        self.received.record_delivered();
+        self.record_frame_time(&frame);
+
+        let Some(last_fingerprint) = &self.last_fingerprint else {
+            return Ok(frame);
+        };
+        let fingerprint = frame.fingerprint();
+        let previous = last_fingerprint
+            .lock()
+            .map(|mut last| last.replace(fingerprint))
+            .unwrap_or_default();
+        Ok(frame.with_duplicate_of_previous(previous == Some(fingerprint)))
+    }
+
+    fn record_frame_time(&self, frame: &FrameBuffer) {
+        let Some(frame_times) = &self.frame_times else {
+            return;
+        };
+        if let Ok(mut frame_times) = frame_times.lock() {
+            if frame_times.len() == FPS_WINDOW {
+                frame_times.pop_front();
+            }
+            frame_times.push_back(frame.timestamp().unwrap_or_else(Instant::now));
         }
     }
 
-    // pub unsafe fn erase_lifetime(self) -> Stream<'static> {
-    //     Self {
-    //         inner: self.inner,
-    //         phantom_data: Default::default(),
-    //     }
-    // }
+    // Checks a received frame against the format the backend negotiated, see `StreamInnerTrait::format`.
+    fn check_format(&self, frame: FrameBuffer) -> NokhwaResult<FrameBuffer> {
+        let Some(format) = self.inner.format() else {
+            return Ok(frame);
+        };
+
+        if frame.resolution() != format.resolution() || frame.source_frame_format() != format.format() {
+            return Err(NokhwaError::FormatChanged {
+                expected: format!("{} {}", format.resolution(), format.format()),
+                got: format!("{} {}", frame.resolution(), frame.source_frame_format()),
+            });
+        }
+
+        // The backend may stamp frames with the format it asked for, so also check uncompressed frames hold
+        // at least as many bytes as that format needs. Drivers may pad the buffer past the image, so longer
+        // frames are accepted.
+        if let Some(expected_len) = format.format().byte_size(format.resolution()) {
+            if frame.buffer().len() < expected_len {
+                return Err(NokhwaError::FormatChanged {
+                    expected: format!(
+                        "{} {} ({expected_len} bytes)",
+                        format.resolution(),
+                        format.format()
+                    ),
+                    got: format!("{} bytes", frame.buffer().len()),
+                });
+            }
+        }
+
+        Ok(frame)
+    }
 
     pub fn check_disconnected(&self) -> NokhwaResult<()> {
         if self.inner.receiver().is_disconnected() {
@@ -35,19 +432,162 @@ impl Stream {
         Ok(())
     }
 
+    /// Waits until the backend has delivered a frame, without taking it, so the next poll returns it.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects, or no frame arrives within `timeout`.
+    pub fn wait_for_first_frame(&self, timeout: Duration) -> NokhwaResult<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            self.check_disconnected()?;
+            if !self.inner.receiver().is_empty() {
+                return Ok(());
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(NokhwaError::ReadFrameError(format!(
+                    "no frame arrived within {timeout:?} of opening the stream"
+                )));
+            }
+            std::thread::sleep(remaining.min(FIRST_FRAME_POLL_INTERVAL));
+        }
+    }
+
+    /// Waits for the next frame. On a [paused](Stream::pause) stream this blocks until it is resumed.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects, or the frame does not match the stream's format.
     pub fn poll_frame(&self) -> NokhwaResult<FrameBuffer> {
         self.check_disconnected()?;
 
-        self.inner
-            .receiver()
-            .recv()
-            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))
+        loop {
+            let frame = self
+                .inner
+                .receiver()
+                .recv()
+                .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+            // Backends that cannot suspend the device keep sending while paused.
+            if !self.paused {
+                return self.process_frame(frame);
+            }
+        }
+    }
+
+    /// Waits for the next frame, giving up after `timeout` has passed.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects, or no frame arrives within `timeout`, which is always the case while it is
+    /// [paused](Stream::pause).
+    pub fn poll_frame_timeout(&self, timeout: Duration) -> NokhwaResult<FrameBuffer> {
+        self.recv_frame_timeout(timeout)?.ok_or_else(|| {
+            NokhwaError::ReadFrameError(format!("timed out after {timeout:?} waiting for a frame"))
+        })
+    }
+
+    // Like `poll_frame_timeout`, but returns `Ok(None)` if `timeout` elapses, so callers can tell a timeout from
+    // other errors.
+    fn recv_frame_timeout(&self, timeout: Duration) -> NokhwaResult<Option<FrameBuffer>> {
+        self.check_disconnected()?;
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match self.inner.receiver().recv_deadline(deadline) {
+                Ok(_) if self.paused => {}
+                Ok(frame) => return self.process_frame(frame).map(Some),
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(NokhwaError::ReadFrameError("stream is disconnected!".to_string()))
+                }
+            }
+        }
+    }
+
+    /// Collects exactly `count` frames in the order they were delivered.
+    ///
+    /// `timeout` bounds the whole collection, not each individual frame.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects or `timeout` elapses before `count` frames have arrived.
+    /// Frames collected up to that point are discarded.
+    pub fn collect_frames(&self, count: usize, timeout: Duration) -> NokhwaResult<Vec<FrameBuffer>> {
+        let deadline = Instant::now() + timeout;
+        let mut frames = Vec::with_capacity(count);
+
+        while frames.len() < count {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let frame = self.poll_frame_timeout(remaining).map_err(|why| match why {
+                NokhwaError::ReadFrameError(msg) => NokhwaError::ReadFrameError(format!(
+                    "collected {} of {count} frames: {msg}",
+                    frames.len()
+                )),
+                other => other,
+            })?;
+            frames.push(frame);
+        }
+
+        Ok(frames)
+    }
+
+    /// Collects every frame delivered over the next `duration`, then stops the stream.
+    ///
+    /// If the stream fails before `duration` is up (e.g. the camera disconnects), the frames collected so far are
+    /// kept alongside the error in the returned [`CaptureOutcome`].
+    pub fn capture_for(self, duration: Duration) -> CaptureOutcome {
+        let deadline = Instant::now() + duration;
+        let mut frames = Vec::new();
+
+        let error = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break None;
+            }
+
+            match self.recv_frame_timeout(remaining) {
+                Ok(Some(frame)) => frames.push(frame),
+                // Running out the clock while waiting is how a timed capture ends.
+                Ok(None) => break None,
+                Err(why) => break Some(why),
+            }
+        };
+
+        self.finish_capture(frames, error)
+    }
+
+    /// Collects the next `count` frames, then stops the stream.
+    ///
+    /// If the stream fails before `count` frames have arrived (e.g. the camera disconnects), the frames collected so
+    /// far are kept alongside the error in the returned [`CaptureOutcome`].
+    pub fn capture_n(self, count: usize) -> CaptureOutcome {
+        let mut frames = Vec::with_capacity(count);
+
+        let mut error = None;
+        while frames.len() < count {
+            match self.poll_frame() {
+                Ok(frame) => frames.push(frame),
+                Err(why) => {
+                    error = Some(why);
+                    break;
+                }
+            }
+        }
+
+        self.finish_capture(frames, error)
+    }
+
+    fn finish_capture(self, frames: Vec<FrameBuffer>, error: Option<NokhwaError>) -> CaptureOutcome {
+        let stopped = self.stop_stream();
+        CaptureOutcome {
+            frames,
+            error: error.or(stopped.err()),
+        }
     }
 
     pub fn try_poll_frame(&self) -> NokhwaResult<Option<FrameBuffer>> {
         self.check_disconnected()?;
 
-        if self.inner.receiver().is_empty() {
+        if self.paused || self.inner.receiver().is_empty() {
             return Ok(None);
         }
 
@@ -56,7 +596,7 @@ impl Stream {
             .try_recv();
 
         match possible_frame {
-            Ok(f) => Ok(Some(f)),
+            Ok(f) => self.process_frame(f).map(Some),
             Err(why) => {
                 match why {
                     TryRecvError::Empty => Ok(None),
@@ -69,22 +609,454 @@ impl Stream {
 
     }
 
+    /// Like [`poll_frame`](Stream::poll_frame), for async code. On a [paused](Stream::pause) stream this waits until
+    /// it is resumed.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects, or the frame does not match the stream's format.
     #[cfg(feature = "async")]
     pub async fn await_frame(&self) -> NokhwaResult<FrameBuffer> {
         use futures::TryFutureExt;
 
         self.check_disconnected()?;
 
-        self.inner
-            .receiver()
-            .recv_async()
-            .map_err(|why| NokhwaError::ReadFrameError(why.to_string())).await
+        loop {
+            let frame = self.inner
+                .receiver()
+                .recv_async()
+                .map_err(|why| NokhwaError::ReadFrameError(why.to_string())).await?;
+            if !self.paused {
+                return self.process_frame(frame);
+            }
+        }
+    }
+
+    /// Returns how many frames this stream has delivered, dropped and failed to capture so far.
+    ///
+    /// Delivered frames and their interval are counted here as frames are handed out, so they work with every
+    /// backend. Dropped frames and capture errors come from the backend (see [`StreamInnerTrait::stats`]), plus
+    /// frames this stream rejected for not matching its format.
+    #[must_use]
+    pub fn stats(&self) -> StreamStats {
+        let backend = self.inner.stats();
+        let received = self.received.stats();
+
+        StreamStats {
+            delivered: received.delivered,
+            dropped: backend.dropped,
+            errors: backend.errors + received.errors,
+            last_frame_interval: received.last_frame_interval,
+        }
     }
 
     pub fn stop_stream(mut self) -> NokhwaResult<()> {
         self.inner.stop()?;
         Ok(())
     }
+
+    /// Hands this stream to a background thread that decodes each frame with `decoder` and passes the image to
+    /// `on_frame`. The image is decoded into a scratch buffer that is reused for every frame, so clone it to keep it
+    /// past the callback.
+    ///
+    /// Frames that fail to decode are skipped and their error is passed to `on_error`, which is also called once if
+    /// the backend disconnects. Duplicate detection and format checks are not applied to these frames; a frame whose
+    /// size does not match its format fails to decode instead.
+    ///
+    /// The stream runs until the returned [`DecodedFrameCallback`] is stopped or dropped.
+    ///
+    /// # Errors
+    /// Errors if the decoding thread could not be spawned.
+    pub fn on_decoded_frame<D>(
+        self,
+        mut decoder: D,
+        mut on_frame: impl FnMut(&ImageBuffer<D::OutputPixels, Vec<<D::OutputPixels as Pixel>::Subpixel>>)
+            + Send
+            + 'static,
+        mut on_error: impl FnMut(NokhwaError) + Send + 'static,
+    ) -> NokhwaResult<DecodedFrameCallback>
+    where
+        D: Decoder + Send + 'static,
+    {
+        let receiver = self.inner.receiver();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let running = running.clone();
+            std::thread::Builder::new()
+                .name("nokhwa-decoded-frame".to_string())
+                .spawn(move || {
+                    let mut scratch = Vec::new();
+                    while running.load(Ordering::Acquire) {
+                        let frame = match receiver.recv_timeout(CALLBACK_POLL_INTERVAL) {
+                            Ok(frame) => frame,
+                            Err(RecvTimeoutError::Timeout) => continue,
+                            Err(RecvTimeoutError::Disconnected) => {
+                                on_error(NokhwaError::ReadFrameError("stream is disconnected!".to_string()));
+                                break;
+                            }
+                        };
+
+                        let resolution = frame.resolution();
+                        scratch.resize(
+                            resolution.width() as usize * resolution.height() as usize * D::channels(),
+                            <<D::OutputPixels as Pixel>::Subpixel as Primitive>::DEFAULT_MIN_VALUE,
+                        );
+                        if let Err(why) = decoder.decode_buffer(&frame, &mut scratch) {
+                            on_error(why);
+                            continue;
+                        }
+
+                        // `scratch` is sized for the resolution above, so this always succeeds.
+                        if let Some(image) =
+                            ImageBuffer::from_raw(resolution.width(), resolution.height(), std::mem::take(&mut scratch))
+                        {
+                            on_frame(&image);
+                            scratch = image.into_raw();
+                        }
+                    }
+                })
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?
+        };
+
+        Ok(DecodedFrameCallback {
+            stream: Some(self),
+            running,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// The frames collected by [`Stream::capture_for`] or [`Stream::capture_n`].
+#[derive(Clone, Debug)]
+pub struct CaptureOutcome {
+    /// Every frame collected, in the order they were delivered.
+    pub frames: Vec<FrameBuffer>,
+    /// Why the capture ended early, or why the stream failed to stop afterwards.
+    pub error: Option<NokhwaError>,
+}
+
+impl CaptureOutcome {
+    /// Whether the whole budget was captured and the stream stopped cleanly.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Returns the frames, or the error if the capture ended early, discarding the frames collected up to then.
+    ///
+    /// # Errors
+    /// Errors if the capture ended early or the stream failed to stop.
+    pub fn into_result(self) -> NokhwaResult<Vec<FrameBuffer>> {
+        match self.error {
+            Some(why) => Err(why),
+            None => Ok(self.frames),
+        }
+    }
+}
+
+/// A [`Stream`] whose frames are decoded and passed to a callback on a background thread, returned by
+/// [`Stream::on_decoded_frame`].
+///
+/// Dropping this stops the thread and the stream, ignoring any error; use [`stop`](DecodedFrameCallback::stop) to
+/// see them.
+pub struct DecodedFrameCallback {
+    stream: Option<Stream>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DecodedFrameCallback {
+    /// Returns the delivery statistics of the underlying stream, see [`Stream::stats`].
+    #[must_use]
+    pub fn stats(&self) -> StreamStats {
+        self.stream.as_ref().map(Stream::stats).unwrap_or_default()
+    }
+
+    /// Stops calling the callbacks, waiting for one in progress to return, then stops the stream.
+    ///
+    /// # Errors
+    /// Errors if a callback panicked, or the stream fails to stop.
+    pub fn stop(mut self) -> NokhwaResult<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> NokhwaResult<()> {
+        self.running.store(false, Ordering::Release);
+        let joined = match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .map_err(|_| NokhwaError::StreamShutdownError("a frame callback panicked".to_string())),
+            None => Ok(()),
+        };
+
+        if let Some(stream) = self.stream.take() {
+            stream.stop_stream()?;
+        }
+        joined
+    }
+}
+
+impl Drop for DecodedFrameCallback {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// The frames of a camera's [`Stream`] as a [`futures::Stream`], returned by
+/// [`AsyncPlatformTrait::open_async_stream`](crate::platform::AsyncPlatformTrait::open_async_stream).
+///
+/// This owns the camera, and closes its stream when dropped. The stream ends if the backend disconnects.
+#[cfg(feature = "async")]
+pub struct FrameStream<C: Capture> {
+    frames: flume::r#async::RecvStream<'static, FrameBuffer>,
+    // Only taken on drop, so it is released before the camera closes the stream.
+    stream: Option<Stream>,
+    camera: C,
+}
+
+#[cfg(feature = "async")]
+impl<C: Capture> FrameStream<C> {
+    /// Wraps a `stream` opened on `camera`.
+    #[must_use]
+    pub fn new(camera: C, stream: Stream) -> Self {
+        Self {
+            frames: Receiver::clone(&stream.inner.receiver()).into_stream(),
+            stream: Some(stream),
+            camera,
+        }
+    }
+
+    /// The camera being streamed from, e.g. to read its controls.
+    #[must_use]
+    pub fn camera(&self) -> &C {
+        &self.camera
+    }
+
+    /// The camera being streamed from, e.g. to change its controls while streaming.
+    pub fn camera_mut(&mut self) -> &mut C {
+        &mut self.camera
+    }
+
+    /// See [`Stream::stats`].
+    #[must_use]
+    pub fn stats(&self) -> StreamStats {
+        self.stream.as_ref().map(Stream::stats).unwrap_or_default()
+    }
+}
+
+// Nothing is structurally pinned: the camera is only ever reached through `&mut Self`.
+#[cfg(feature = "async")]
+impl<C: Capture> Unpin for FrameStream<C> {}
+
+#[cfg(feature = "async")]
+impl<C: Capture> futures::Stream for FrameStream<C> {
+    type Item = NokhwaResult<FrameBuffer>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use futures::StreamExt;
+
+        let this = &mut *self;
+        let Some(stream) = &this.stream else {
+            return std::task::Poll::Ready(None);
+        };
+
+        this.frames
+            .poll_next_unpin(cx)
+            .map(|frame| frame.map(|frame| stream.process_frame(frame)))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: Capture> Drop for FrameStream<C> {
+    fn drop(&mut self) {
+        drop(self.stream.take());
+        // swallow errors, the camera is dropped either way
+        let _ = self.camera.close_stream();
+    }
+}
+
+/// Captures from several [`Stream`]s at once, handing out one frame per stream captured at about the same time,
+/// e.g. for stereo or 360° rigs.
+///
+/// Frames are matched by their [`timestamp`](FrameBuffer::timestamp), which V4L2 and `AVFoundation` set from the
+/// time the driver captured the frame (see [`FrameBuffer::with_raw_timestamp`]). Other backends stamp frames as they
+/// are sent, and frames without a timestamp are stamped when the group receives them, so those are only matched to
+/// within the delay of getting them off the device.
+pub struct SyncGroup {
+    streams: Vec<Stream>,
+    // The oldest frame received from each stream and not handed out yet, and how many frames were discarded.
+    pending: Mutex<(Vec<Option<FrameBuffer>>, u64)>,
+}
+
+impl SyncGroup {
+    #[must_use]
+    pub fn new(streams: Vec<Stream>) -> Self {
+        let pending = streams.iter().map(|_| None).collect();
+        Self {
+            streams,
+            pending: Mutex::new((pending, 0)),
+        }
+    }
+
+    #[must_use]
+    pub fn streams(&self) -> &[Stream] {
+        &self.streams
+    }
+
+    #[must_use]
+    pub fn into_streams(self) -> Vec<Stream> {
+        self.streams
+    }
+
+    /// How many frames were discarded so far because no frame of another stream was within tolerance of them.
+    #[must_use]
+    pub fn dropped_frames(&self) -> u64 {
+        self.pending.lock().map(|pending| pending.1).unwrap_or_default()
+    }
+
+    /// Waits for one frame from every stream, in the order the streams were added, all captured within `tolerance`
+    /// of each other.
+    ///
+    /// Frames too old to match the newest frame of the other streams are discarded, so a stream running behind
+    /// catches up. Frames received but not yet returned are kept for the next call.
+    ///
+    /// # Errors
+    /// Errors if the group is empty, or polling any stream fails.
+    pub fn next_synchronized(&self, tolerance: Duration) -> NokhwaResult<Vec<FrameBuffer>> {
+        if self.streams.is_empty() {
+            return Err(NokhwaError::ReadFrameError("sync group has no streams".to_string()));
+        }
+
+        let mut pending = self
+            .pending
+            .lock()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+        let (heads, dropped) = &mut *pending;
+
+        loop {
+            for (stream, head) in self.streams.iter().zip(heads.iter_mut()) {
+                if head.is_none() {
+                    let frame = stream.poll_frame()?;
+                    *head = Some(match frame.timestamp() {
+                        Some(_) => frame,
+                        None => frame.with_timestamp(Instant::now()),
+                    });
+                }
+            }
+
+            let timestamps = heads
+                .iter()
+                .filter_map(|head| head.as_ref().and_then(FrameBuffer::timestamp))
+                .collect::<Vec<_>>();
+            let (Some(oldest), Some(newest)) = (timestamps.iter().min(), timestamps.iter().max()) else {
+                continue;
+            };
+            if newest.duration_since(*oldest) <= tolerance {
+                return Ok(heads.iter_mut().filter_map(Option::take).collect());
+            }
+
+            // discard every frame too old to be matched with the newest one, and wait for their successors
+            let newest = *newest;
+            for head in heads.iter_mut() {
+                if head
+                    .as_ref()
+                    .and_then(FrameBuffer::timestamp)
+                    .is_some_and(|timestamp| newest.duration_since(timestamp) > tolerance)
+                {
+                    *head = None;
+                    *dropped += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the most recent frames of a [`Stream`] for pre-trigger recording, e.g. a dashcam keeping the last few
+/// seconds to save when something happens.
+///
+/// Memory is bounded by a frame count, and optionally a byte budget: the oldest frames are evicted to stay within both.
+#[derive(Clone, Debug, Default)]
+pub struct RingRecorder {
+    frames: VecDeque<FrameBuffer>,
+    max_frames: usize,
+    max_bytes: Option<usize>,
+    bytes: usize,
+}
+
+impl RingRecorder {
+    /// Creates a recorder keeping at most `max_frames` frames.
+    #[must_use]
+    pub fn new(max_frames: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(max_frames),
+            max_frames,
+            ..Self::default()
+        }
+    }
+
+    /// Also limits the retained frames to `max_bytes` in total. A single frame larger than this is not kept.
+    #[must_use]
+    pub fn with_byte_budget(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self.evict();
+        self
+    }
+
+    /// Adds `frame` as the newest frame, evicting the oldest ones to stay within the limits.
+    pub fn push(&mut self, frame: FrameBuffer) {
+        self.bytes += frame.buffer().len();
+        self.frames.push_back(frame);
+        self.evict();
+    }
+
+    /// Moves every frame `stream` has ready into the recorder without blocking, returning how many were added.
+    ///
+    /// # Errors
+    /// Errors if the stream disconnects, or delivers a frame that does not match its format.
+    pub fn record(&mut self, stream: &Stream) -> NokhwaResult<usize> {
+        let mut recorded = 0;
+        while let Some(frame) = stream.try_poll_frame()? {
+            self.push(frame);
+            recorded += 1;
+        }
+        Ok(recorded)
+    }
+
+    /// Takes the retained window, oldest frame first, leaving the recorder empty to start the next one.
+    pub fn trigger(&mut self) -> Vec<FrameBuffer> {
+        self.bytes = 0;
+        self.frames.drain(..).collect()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Total size of the retained frames, in bytes.
+    #[must_use]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    fn evict(&mut self) {
+        while self.frames.len() > self.max_frames
+            || self.max_bytes.is_some_and(|max_bytes| self.bytes > max_bytes)
+        {
+            let Some(oldest) = self.frames.pop_front() else {
+                break;
+            };
+            self.bytes -= oldest.buffer().len();
+        }
+    }
 }
 
 impl Drop for Stream {
@@ -92,3 +1064,286 @@ impl Drop for Stream {
         let _ = self.inner.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame_format::FrameFormat;
+    use crate::types::{FrameRate, Resolution};
+
+    const RESOLUTION: Resolution = Resolution::new(2, 2);
+
+    // A backend whose frames are sent by the test, checked against a 2x2 Luma8 format.
+    struct MockStream {
+        receiver: Arc<Receiver<FrameBuffer>>,
+        backend_stats: StreamStats,
+    }
+
+    fn mock_stream(backend_stats: StreamStats) -> (Sender<FrameBuffer>, Stream) {
+        let (sender, receiver) = flume::unbounded();
+        let inner = MockStream {
+            receiver: Arc::new(receiver),
+            backend_stats,
+        };
+        (sender, Stream::new(Box::new(inner)))
+    }
+
+    fn frame(len: usize) -> FrameBuffer {
+        FrameBuffer::new(RESOLUTION, &vec![0; len], FrameFormat::Luma8)
+    }
+
+    impl StreamInnerTrait for MockStream {
+        fn receiver(&self) -> Arc<Receiver<FrameBuffer>> {
+            self.receiver.clone()
+        }
+
+        fn stop(&mut self) -> NokhwaResult<()> {
+            Ok(())
+        }
+
+        fn format(&self) -> Option<CameraFormat> {
+            Some(CameraFormat::new(RESOLUTION, FrameFormat::Luma8, FrameRate::frame_rate(30)))
+        }
+
+        fn stats(&self) -> StreamStats {
+            self.backend_stats
+        }
+    }
+
+    #[test]
+    fn stats_count_frames_handed_out_for_any_backend() {
+        let backend_stats = StreamStats {
+            dropped: 5,
+            errors: 1,
+            ..StreamStats::default()
+        };
+        let (sender, stream) = mock_stream(backend_stats);
+        for _ in 0..3 {
+            sender.send(frame(4)).unwrap();
+        }
+        sender.send(frame(3)).unwrap();
+
+        stream.poll_frame().unwrap();
+        stream.poll_frame().unwrap();
+        let stats = stream.stats();
+        assert_eq!((stats.delivered, stats.dropped, stats.errors), (2, 5, 1));
+
+        stream.poll_frame().unwrap();
+        assert!(stream.poll_frame().is_err());
+        let stats = stream.stats();
+        assert_eq!((stats.delivered, stats.dropped, stats.errors), (3, 5, 2));
+    }
+
+    #[test]
+    fn frames_not_matching_the_negotiated_format_are_rejected() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        sender.send(frame(3)).unwrap();
+        sender.send(FrameBuffer::new(Resolution::new(1, 4), &[0; 4], FrameFormat::Luma8)).unwrap();
+        // Drivers may pad the buffer past the image.
+        sender.send(frame(8)).unwrap();
+
+        assert!(matches!(stream.poll_frame(), Err(NokhwaError::FormatChanged { .. })));
+        assert!(matches!(stream.poll_frame(), Err(NokhwaError::FormatChanged { .. })));
+        assert_eq!(stream.poll_frame().unwrap().buffer().len(), 8);
+    }
+
+    #[test]
+    fn collect_frames_returns_frames_in_delivery_order() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        // Padding lengths tell the frames apart.
+        for len in 4..10 {
+            sender.send(frame(len)).unwrap();
+        }
+
+        let frames = stream.collect_frames(5, Duration::from_secs(1)).unwrap();
+        let lengths = frames.iter().map(|x| x.buffer().len()).collect::<Vec<_>>();
+        assert_eq!(lengths, [4, 5, 6, 7, 8]);
+        assert_eq!(stream.poll_frame().unwrap().buffer().len(), 9);
+    }
+
+    #[test]
+    fn collect_frames_times_out_when_too_few_frames_arrive() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        sender.send(frame(4)).unwrap();
+        sender.send(frame(4)).unwrap();
+
+        let started = Instant::now();
+        let error = stream.collect_frames(5, Duration::from_millis(50)).unwrap_err();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(matches!(&error, NokhwaError::ReadFrameError(msg) if msg.starts_with("collected 2 of 5 frames")));
+    }
+
+    #[test]
+    fn capture_for_keeps_frames_until_the_deadline() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        for _ in 0..3 {
+            sender.send(frame(4)).unwrap();
+        }
+
+        let started = Instant::now();
+        let outcome = stream.capture_for(Duration::from_millis(50));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(outcome.frames.len(), 3);
+        assert!(outcome.is_complete());
+    }
+
+    #[test]
+    fn capture_for_keeps_frames_collected_before_an_error() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        sender.send(frame(4)).unwrap();
+        sender.send(frame(3)).unwrap();
+
+        let outcome = stream.capture_for(Duration::from_secs(5));
+        assert_eq!(outcome.frames.len(), 1);
+        assert!(matches!(outcome.error, Some(NokhwaError::FormatChanged { .. })));
+        assert!(!outcome.is_complete());
+    }
+
+    #[test]
+    fn paused_streams_deliver_nothing_until_resumed() {
+        let (sender, mut stream) = mock_stream(StreamStats::default());
+        sender.send(frame(4)).unwrap();
+
+        stream.pause().unwrap();
+        // The mock keeps sending while paused, like a backend that cannot suspend the device.
+        sender.send(frame(4)).unwrap();
+        assert!(stream.try_poll_frame().unwrap().is_none());
+        assert!(matches!(stream.recv_frame_timeout(Duration::from_millis(10)), Ok(None)));
+        assert!(stream.poll_frame_timeout(Duration::from_millis(10)).is_err());
+
+        stream.resume().unwrap();
+        sender.send(frame(4)).unwrap();
+        assert!(stream.poll_frame().is_ok());
+        assert!(stream.try_poll_frame().unwrap().is_none());
+        assert_eq!(stream.stats().delivered, 1);
+    }
+
+    #[test]
+    fn repeated_frames_are_flagged_only_with_duplicate_detection_on() {
+        let (sender, mut stream) = mock_stream(StreamStats::default());
+        let send = |luma: &[u8]| {
+            for &value in luma {
+                sender.send(FrameBuffer::new(RESOLUTION, &[value; 4], FrameFormat::Luma8)).unwrap();
+            }
+        };
+        let flags = |stream: &Stream, count| {
+            (0..count)
+                .map(|_| stream.poll_frame().unwrap().is_duplicate_of_previous())
+                .collect::<Vec<_>>()
+        };
+
+        send(&[10, 10]);
+        assert_eq!(flags(&stream, 2), [false, false]);
+
+        stream.set_duplicate_detection(true);
+        send(&[10, 10, 200, 10, 10]);
+        assert_eq!(flags(&stream, 5), [false, true, false, false, true]);
+    }
+
+    #[test]
+    fn sync_groups_match_frames_within_tolerance_and_discard_the_rest() {
+        let start = Instant::now();
+        let at = |millis| frame(4).with_timestamp(start + Duration::from_millis(millis));
+        let (left_sender, left) = mock_stream(StreamStats::default());
+        let (right_sender, right) = mock_stream(StreamStats::default());
+        for millis in [40, 73] {
+            left_sender.send(at(millis)).unwrap();
+        }
+        // The right camera sent one frame before the left one started, which nothing can match.
+        for millis in [0, 42, 75] {
+            right_sender.send(at(millis)).unwrap();
+        }
+
+        let group = SyncGroup::new(vec![left, right]);
+        let captured_at = |frames: Vec<FrameBuffer>| {
+            frames
+                .iter()
+                .map(|frame| frame.timestamp().unwrap().duration_since(start).as_millis())
+                .collect::<Vec<_>>()
+        };
+        let tolerance = Duration::from_millis(5);
+
+        assert_eq!(captured_at(group.next_synchronized(tolerance).unwrap()), [40, 42]);
+        assert_eq!(captured_at(group.next_synchronized(tolerance).unwrap()), [73, 75]);
+        assert_eq!(group.dropped_frames(), 1);
+    }
+
+    #[test]
+    fn ring_recorders_keep_the_newest_window() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        for index in 0..30 {
+            sender.send(FrameBuffer::new(RESOLUTION, &[index; 4], FrameFormat::Luma8)).unwrap();
+        }
+
+        let mut recorder = RingRecorder::new(10);
+        assert_eq!(recorder.record(&stream).unwrap(), 30);
+        assert_eq!((recorder.len(), recorder.bytes()), (10, 40));
+
+        let window = recorder.trigger();
+        assert_eq!(window.iter().map(|frame| frame.buffer()[0]).collect::<Vec<_>>(), (20..30).collect::<Vec<_>>());
+        assert!(recorder.is_empty());
+        assert_eq!(recorder.bytes(), 0);
+    }
+
+    #[test]
+    fn measured_fps_follows_the_timestamps_of_the_last_frames() {
+        let (sender, mut stream) = mock_stream(StreamStats::default());
+        let start = Instant::now();
+        let send_every = |interval: u64, range: std::ops::Range<u64>| {
+            for index in range {
+                sender.send(frame(4).with_timestamp(start + Duration::from_millis(index * interval))).unwrap();
+            }
+        };
+        let poll_all = |stream: &Stream| while stream.try_poll_frame().unwrap().is_some() {};
+
+        send_every(40, 0..2);
+        poll_all(&stream);
+        assert!(stream.measured_fps().abs() < f32::EPSILON);
+
+        stream.set_fps_measurement(true);
+        send_every(40, 0..1);
+        poll_all(&stream);
+        assert!(stream.measured_fps().abs() < f32::EPSILON);
+
+        send_every(40, 1..10);
+        poll_all(&stream);
+        assert!((stream.measured_fps() - 25.0).abs() < 0.01);
+
+        // Only the last 30 frames count, so the 25 fps start drops out of the window.
+        send_every(10, 40..80);
+        poll_all(&stream);
+        assert!((stream.measured_fps() - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn decoded_frame_callbacks_get_every_frame_and_every_error() {
+        let (sender, stream) = mock_stream(StreamStats::default());
+        for len in [4, 4, 3, 4] {
+            sender.send(frame(len)).unwrap();
+        }
+        drop(sender);
+
+        let (events, received) = flume::unbounded();
+        let errors = events.clone();
+        let callback = stream
+            .on_decoded_frame(
+                crate::pixel_format::LumaAFormat,
+                move |image| {
+                    let _ = events.send(Ok(image.dimensions()));
+                },
+                move |why| {
+                    let _ = errors.send(Err(why));
+                },
+            )
+            .unwrap();
+
+        let events = (0..5)
+            .map(|_| received.recv_timeout(Duration::from_secs(1)).unwrap())
+            .collect::<Vec<_>>();
+        assert!(events[..2].iter().all(|event| matches!(event, Ok((2, 2)))));
+        assert!(matches!(&events[2], Err(NokhwaError::StructureError { error, .. }) if error.ends_with("got 3")));
+        assert!(matches!(events[3], Ok((2, 2))));
+        assert!(matches!(&events[4], Err(NokhwaError::ReadFrameError(why)) if why == "stream is disconnected!"));
+        callback.stop().unwrap();
+    }
+}
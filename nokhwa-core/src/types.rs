@@ -1,5 +1,5 @@
 use crate::utils::Distance;
-use crate::{error::NokhwaError, frame_format::FrameFormat};
+use crate::{error::{NokhwaError, NokhwaResult}, frame_format::{FrameFormat, ScanType}};
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 use std::{
@@ -12,8 +12,10 @@ use std::{
 use std::num::NonZeroI32;
 use std::ops::{Div, Rem};
 use num_rational::Rational32;
-use crate::ranges::{SimpleRangeItem};
-use num_traits::FromPrimitive;
+use crate::ranges::{Range, SimpleRangeItem, ValidatableRange};
+use crate::platform::Backends;
+use crate::stream::IoMethod;
+use num_traits::{FromPrimitive, Signed};
 
 /// Describes the index of the camera.
 /// - Index: A numbered index
@@ -28,16 +30,27 @@ pub enum CameraIndex {
 impl CameraIndex {
     /// Turns this value into a number. If it is a string, it will attempt to parse it as a `u32`.
     /// # Errors
-    /// Fails if the value is not a number.
+    /// Fails with [`NokhwaError::NonNumericIndex`] if the string is not made of digits (e.g. a device path, or
+    /// empty), or with [`NokhwaError::ConversionError`] if it is a number too large for a `u32`.
     pub fn as_index(&self) -> Result<u32, NokhwaError> {
         match self {
             CameraIndex::Index(i) => Ok(*i),
-            CameraIndex::String(s) => s
-                .parse::<u32>()
-                .map_err(|why| NokhwaError::GeneralError(why.to_string())),
+            CameraIndex::String(s) => {
+                if s.is_empty() || !s.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err(NokhwaError::NonNumericIndex(s.clone()));
+                }
+                s.parse::<u32>()
+                    .map_err(|why| NokhwaError::ConversionError(format!("camera index {s}: {why}")))
+            }
         }
     }
 
+    /// Like [`as_index`](CameraIndex::as_index), but returns `default` for indices that are not a number.
+    #[must_use]
+    pub fn numeric_or(&self, default: u32) -> u32 {
+        self.as_index().unwrap_or(default)
+    }
+
     /// Turns this value into a `String`. If it is a number, it will be automatically converted.
     #[must_use]
     pub fn as_string(&self) -> String {
@@ -145,8 +158,90 @@ impl Resolution {
     pub fn aspect_ratio(&self) -> f64 {
         f64::from(self.width_x) / f64::from(self.height_y)
     }
+
+    /// The largest centered region of this resolution with the aspect ratio `target_aspect` (width / height), as
+    /// its top left corner and size, e.g. `((420, 0), 1080x1080)` to crop 1920x1080 to 1:1.
+    /// Pass these to [`FrameBuffer::crop`](crate::frame_buffer::FrameBuffer::crop).
+    ///
+    /// A `target_aspect` that is not a positive finite number gives the whole frame.
+    #[must_use]
+    pub fn crop_to_aspect(&self, target_aspect: f64) -> ((u32, u32), Resolution) {
+        if !target_aspect.is_finite() || target_aspect <= 0.0 {
+            return ((0, 0), *self);
+        }
+
+        let size = if self.aspect_ratio() > target_aspect {
+            let width = (f64::from(self.height_y) * target_aspect).round() as u32;
+            Resolution::new(width.clamp(1, self.width_x), self.height_y)
+        } else {
+            let height = (f64::from(self.width_x) / target_aspect).round() as u32;
+            Resolution::new(self.width_x, height.clamp(1, self.height_y))
+        };
+        let origin = ((self.width_x - size.width_x) / 2, (self.height_y - size.height_y) / 2);
+        (origin, size)
+    }
+
+    /// The size to display a frame of this resolution at so it is not stretched, given the width to height ratio of
+    /// its pixels, e.g. 853x480 for a 720x480 frame with 32:27 pixels.
+    ///
+    /// The height is kept and the width scaled. A `pixel_aspect` that is not positive gives this resolution.
+    #[must_use]
+    pub fn display_resolution(&self, pixel_aspect: Rational32) -> Resolution {
+        let (numerator, denominator) = (*pixel_aspect.numer(), *pixel_aspect.denom());
+        if numerator <= 0 || denominator <= 0 {
+            return *self;
+        }
+
+        let (numerator, denominator) = (u64::from(numerator.unsigned_abs()), u64::from(denominator.unsigned_abs()));
+        let width = (u64::from(self.width_x) * numerator + denominator / 2) / denominator;
+        Resolution::new(u32::try_from(width).unwrap_or(u32::MAX).max(1), self.height_y)
+    }
+
+    /// The number of pixels in millions, e.g. `2.0736` for 1920x1080.
+    #[must_use]
+    pub fn megapixels(&self) -> f64 {
+        f64::from(self.width_x) * f64::from(self.height_y) / 1_000_000.0
+    }
+
+    /// The name this resolution is commonly known by, e.g. `"1080p"` for 1920x1080 or `"VGA"` for 640x480.
+    ///
+    /// Returns `None` if this is not one of the standard resolutions.
+    #[must_use]
+    pub fn common_name(&self) -> Option<&'static str> {
+        COMMON_RESOLUTIONS
+            .iter()
+            .find(|(resolution, _)| resolution == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// Whether this is one of the standard resolutions named by [`common_name`](Self::common_name).
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        self.common_name().is_some()
+    }
 }
 
+const COMMON_RESOLUTIONS: &[(Resolution, &str)] = &[
+    (Resolution::new(160, 120), "QQVGA"),
+    (Resolution::new(176, 144), "QCIF"),
+    (Resolution::new(320, 240), "QVGA"),
+    (Resolution::new(352, 288), "CIF"),
+    (Resolution::new(640, 360), "360p"),
+    (Resolution::new(640, 480), "VGA"),
+    (Resolution::new(800, 600), "SVGA"),
+    (Resolution::new(854, 480), "480p"),
+    (Resolution::new(1024, 768), "XGA"),
+    (Resolution::new(1280, 720), "720p"),
+    (Resolution::new(1280, 960), "960p"),
+    (Resolution::new(1280, 1024), "SXGA"),
+    (Resolution::new(1600, 1200), "UXGA"),
+    (Resolution::new(1920, 1080), "1080p"),
+    (Resolution::new(2560, 1440), "1440p"),
+    (Resolution::new(3840, 2160), "4K"),
+    (Resolution::new(4096, 2160), "DCI 4K"),
+    (Resolution::new(7680, 4320), "8K"),
+];
+
 impl Display for Resolution {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}x{}", self.x(), self.y())
@@ -177,7 +272,7 @@ impl Distance<u32> for Resolution {
         let y1 = self.y();
         let y2 = other.y();
 
-        (x2 - x1).pow(2) + (y2 - y1).pow(2)
+        x1.abs_diff(x2).pow(2) + y1.abs_diff(y2).pow(2)
     }
 }
 
@@ -220,8 +315,8 @@ impl SimpleRangeItem for Resolution {
 /// Note that while constructing negative is allowed, the absolute value
 /// will be passed to the driver.
 ///
-/// # Panics
-/// If denominator is 0, any attempt to use the [`FrameRate`] will **panic.**
+/// Every way of constructing a [`FrameRate`] guarantees a non-zero denominator: [`FrameRate::new`] takes a
+/// [`NonZeroI32`], while [`FrameRate::try_new`] and [`TryFrom<Rational32>`] reject zero.
 #[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct FrameRate {
@@ -229,12 +324,44 @@ pub struct FrameRate {
 }
 
 impl FrameRate {
+    /// Creates a [`FrameRate`] of `numerator / denominator`, reduced to lowest terms with a positive denominator,
+    /// like [`Rational32::new`].
     pub const fn new(numerator: i32, denominator: NonZeroI32) -> Self {
+        let denominator = denominator.get();
+        // `Rational32::new` is not `const`, so the greatest common divisor is found here.
+        let (mut gcd, mut remainder) = (numerator.unsigned_abs(), denominator.unsigned_abs());
+        while remainder != 0 {
+            (gcd, remainder) = (remainder, gcd % remainder);
+        }
+        // The divisor only wraps to `i32::MIN` if both values are multiples of it, which still divides them exactly.
+        let gcd = gcd.cast_signed();
+        let (numerator, denominator) = (numerator / gcd, denominator / gcd);
+        let (numerator, denominator) = if denominator < 0 {
+            (-numerator, -denominator)
+        } else {
+            (numerator, denominator)
+        };
+
         Self {
-            rational: Rational32::new_raw(numerator, denominator.get()),
+            rational: Rational32::new_raw(numerator, denominator),
         }
     }
 
+    /// Creates a [`FrameRate`] of `numerator / denominator`, reduced like [`FrameRate::new`].
+    ///
+    /// # Errors
+    /// Errors if `denominator` is zero.
+    pub fn try_new(numerator: i32, denominator: i32) -> NokhwaResult<Self> {
+        let Some(denominator) = NonZeroI32::new(denominator) else {
+            return Err(NokhwaError::StructureError {
+                structure: "FrameRate".to_string(),
+                error: format!("{numerator}/0 has a zero denominator"),
+            });
+        };
+
+        Ok(Self::new(numerator, denominator))
+    }
+
     pub const fn frame_rate(fps: i32) -> Self {
         Self {
             rational: Rational32::new_raw(fps, 1),
@@ -277,7 +404,10 @@ impl Div for FrameRate {
     type Output = FrameRate;
 
     fn div(self, rhs: Self) -> Self::Output {
-        self.rational.div(rhs.rational).into()
+        // `Rational32` arithmetic panics rather than produce a zero denominator.
+        FrameRate {
+            rational: self.rational.div(rhs.rational),
+        }
     }
 }
 
@@ -285,7 +415,9 @@ impl Sub for FrameRate {
     type Output = FrameRate;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        self.rational.sub(rhs.rational).into()
+        FrameRate {
+            rational: self.rational.sub(rhs.rational),
+        }
     }
 }
 
@@ -293,7 +425,9 @@ impl Rem for FrameRate {
     type Output = FrameRate;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        self.rational.rem(rhs.rational).into()
+        FrameRate {
+            rational: self.rational.rem(rhs.rational),
+        }
     }
 }
 
@@ -301,11 +435,81 @@ impl SimpleRangeItem for FrameRate {
     const ZERO: Self = FrameRate::frame_rate(0);
 }
 
-impl From<Rational32> for FrameRate {
-    fn from(value: Rational32) -> Self {
-        FrameRate {
-            rational: value,
-        }
+impl TryFrom<Rational32> for FrameRate {
+    type Error = NokhwaError;
+
+    /// Converts `value`, reduced to lowest terms. `Rational32::new_raw` can build a ratio with a zero
+    /// denominator, which is rejected.
+    fn try_from(value: Rational32) -> Result<Self, Self::Error> {
+        FrameRate::try_new(*value.numer(), *value.denom())
+    }
+}
+
+/// The frame rates a camera supports for one [`Resolution`] and [`FrameFormat`].
+///
+/// Drivers report either a list of `discrete` rates, a continuous `stepwise` range, or both.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameRateSupport {
+    pub discrete: Vec<FrameRate>,
+    pub stepwise: Option<Range<FrameRate>>,
+}
+
+impl FrameRateSupport {
+    /// Returns `true` if `frame_rate` is one of the discrete rates or lies within the stepwise range.
+    #[must_use]
+    pub fn supports(&self, frame_rate: FrameRate) -> bool {
+        self.discrete.contains(&frame_rate)
+            || self
+                .stepwise
+                .is_some_and(|range| range.validate(&frame_rate).is_ok())
+    }
+
+    /// Widens the stepwise range to also cover `range`, for drivers that report more than one. Rates in a gap
+    /// between disjoint ranges are then reported as supported too, which drivers handle by snapping to the
+    /// nearest rate they have. The step is kept only if both ranges share it.
+    pub fn add_stepwise(&mut self, range: Range<FrameRate>) {
+        let Some(current) = self.stepwise else {
+            self.stepwise = Some(range);
+            return;
+        };
+
+        let minimum = current.minimum().zip(range.minimum()).map(|(a, b)| a.min(b));
+        let maximum = current.maximum().zip(range.maximum()).map(|(a, b)| a.max(b));
+        let step = current.step().filter(|step| range.step() == Some(*step));
+        self.stepwise = Some(Range::new(current.preferred(), minimum, maximum, step));
+    }
+
+    /// Snaps `target` to the nearest supported frame rate, which may be any rate inside the stepwise range
+    /// rather than only the discrete ones. Returns `None` if nothing is supported.
+    #[must_use]
+    pub fn closest(&self, target: FrameRate) -> Option<FrameRate> {
+        let distance = |rate: &FrameRate| (rate.as_raw() - target.as_raw()).abs();
+
+        let stepwise = self.stepwise.map(|range| {
+            let mut snapped = *target.as_raw();
+            if let Some(min) = range.minimum() {
+                snapped = snapped.max(*min.as_raw());
+            }
+            if let Some(max) = range.maximum() {
+                snapped = snapped.min(*max.as_raw());
+            }
+            if let (Some(step), Some(min)) = (range.step(), range.minimum()) {
+                if *step.numerator() > 0 {
+                    let steps = ((snapped - min.as_raw()) / step.as_raw()).round();
+                    snapped = min.as_raw() + steps * step.as_raw();
+                    if range.maximum().is_some_and(|max| snapped > *max.as_raw()) {
+                        snapped -= step.as_raw();
+                    }
+                }
+            }
+            FrameRate { rational: snapped }
+        });
+
+        self.discrete
+            .iter()
+            .copied()
+            .chain(stepwise)
+            .min_by_key(distance)
     }
 }
 
@@ -317,6 +521,8 @@ pub struct CameraFormat {
     resolution: Resolution,
     format: FrameFormat,
     frame_rate: FrameRate,
+    pixel_aspect_ratio: Option<Rational32>,
+    scan_type: ScanType,
 }
 
 impl CameraFormat {
@@ -327,6 +533,8 @@ impl CameraFormat {
             resolution,
             format,
             frame_rate,
+            pixel_aspect_ratio: None,
+            scan_type: ScanType::Progressive,
         }
     }
 
@@ -340,6 +548,8 @@ impl CameraFormat {
             },
             format,
             frame_rate: fps,
+            pixel_aspect_ratio: None,
+            scan_type: ScanType::Progressive,
         }
     }
 
@@ -387,6 +597,118 @@ impl CameraFormat {
     pub fn set_format(&mut self, format: FrameFormat) {
         self.format = format;
     }
+
+    /// Get the width to height ratio of the pixels, if the backend reports it. Most cameras have square (1:1)
+    /// pixels, but some sensors and capture cards deliver anamorphic ones, e.g. 32:27 for 720x480 shown as 16:9.
+    #[must_use]
+    pub fn pixel_aspect_ratio(&self) -> Option<Rational32> {
+        self.pixel_aspect_ratio
+    }
+
+    /// Set the [`CameraFormat`]'s pixel aspect ratio.
+    pub fn set_pixel_aspect_ratio(&mut self, pixel_aspect_ratio: Option<Rational32>) {
+        self.pixel_aspect_ratio = pixel_aspect_ratio;
+    }
+
+    /// Get whether frames in this format are progressive or interlaced, as the backend reports it.
+    #[must_use]
+    pub fn scan_type(&self) -> ScanType {
+        self.scan_type
+    }
+
+    /// Set the [`CameraFormat`]'s scan type.
+    pub fn set_scan_type(&mut self, scan_type: ScanType) {
+        self.scan_type = scan_type;
+    }
+
+    /// The size to display frames in this format at, see [`Resolution::display_resolution`]. Formats without a
+    /// [pixel aspect ratio](CameraFormat::pixel_aspect_ratio) are assumed to have square pixels.
+    #[must_use]
+    pub fn display_resolution(&self) -> Resolution {
+        self.pixel_aspect_ratio
+            .map_or(self.resolution, |pixel_aspect| self.resolution.display_resolution(pixel_aspect))
+    }
+
+    /// Whether a camera on a `bus_speed` connection can deliver this format, e.g. to warn that 4K YUYV needs USB 3.
+    ///
+    /// Compressed formats, and buses of unknown speed, always fit; for uncompressed formats this compares
+    /// [`FrameFormat::byte_size`] times the frame rate against [`BusSpeed::max_bandwidth`].
+    #[must_use]
+    pub fn fits_within_bandwidth(&self, bus_speed: BusSpeed) -> bool {
+        let (Some(frame_size), Some(bandwidth), Some(fps)) = (
+            self.format.byte_size(self.resolution),
+            bus_speed.max_bandwidth(),
+            self.frame_rate.approximate_float(),
+        ) else {
+            return true;
+        };
+
+        frame_size as f64 * f64::from(fps.abs()) <= bandwidth as f64
+    }
+}
+
+/// The speed of the bus (in practice, USB) a camera is connected through, see [`CameraInformation::bus_speed`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum BusSpeed {
+    /// USB 1.1, 12 Mbit/s.
+    Full,
+    /// USB 2.0, 480 Mbit/s.
+    High,
+    /// USB 3.x Gen 1, 5 Gbit/s.
+    Super,
+    /// USB 3.x Gen 2 or faster, 10 Gbit/s and up.
+    SuperPlus,
+    Unknown,
+}
+
+impl BusSpeed {
+    /// The most bytes per second a camera can stream over this bus: the isochronous payload limit,
+    /// well below the raw signalling rate. `None` for [`BusSpeed::Unknown`].
+    #[must_use]
+    pub fn max_bandwidth(self) -> Option<u64> {
+        match self {
+            // 1023 bytes per 1ms frame.
+            BusSpeed::Full => Some(1_023_000),
+            // 3 transactions of 1024 bytes per 125µs microframe.
+            BusSpeed::High => Some(24_576_000),
+            // 3 bursts of 16 packets of 1024 bytes per 125µs service interval.
+            BusSpeed::Super => Some(393_216_000),
+            BusSpeed::SuperPlus => Some(786_432_000),
+            BusSpeed::Unknown => None,
+        }
+    }
+}
+
+/// What kinds of I/O a device supports, see [`CameraInformation::capabilities`].
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DeviceCapabilities {
+    /// The device captures video (V4L2 `V4L2_CAP_VIDEO_CAPTURE`).
+    pub video_capture: bool,
+    /// Frames can be streamed through driver buffers (V4L2 `V4L2_CAP_STREAMING`).
+    pub streaming: bool,
+    /// Frames can be read with `read()` (V4L2 `V4L2_CAP_READWRITE`).
+    pub read_write: bool,
+}
+
+impl DeviceCapabilities {
+    /// The [`IoMethod`] to capture with: [`IoMethod::Mmap`] if the device can stream, falling back to
+    /// [`IoMethod::Read`] if it can only be read from. `None` if it cannot capture video at all.
+    #[must_use]
+    pub fn preferred_io_method(self) -> Option<IoMethod> {
+        if !self.video_capture {
+            return None;
+        }
+
+        if self.streaming {
+            Some(IoMethod::Mmap)
+        } else if self.read_write {
+            Some(IoMethod::Read)
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for CameraFormat {
@@ -395,6 +717,8 @@ impl Default for CameraFormat {
             resolution: Resolution::new(640, 480),
             format: FrameFormat::MJpeg,
             frame_rate: FrameRate::default(),
+            pixel_aspect_ratio: None,
+            scan_type: ScanType::Progressive,
         }
     }
 }
@@ -412,13 +736,23 @@ impl Display for CameraFormat {
 /// Information about a Camera e.g. its name.
 /// `description` amd `misc` may contain information that may differ from backend to backend. Refer to each backend for details.
 /// `index` is a camera's index given to it by (usually) the OS usually in the order it is known to the system.
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd)]
+///
+/// [`CameraInformation`]s sort by `index` first.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct CameraInformation {
     human_name: String,
     description: String,
     misc: String,
     index: CameraIndex,
+    bus_speed: Option<BusSpeed>,
+    capabilities: Option<DeviceCapabilities>,
+    driver_version: Option<String>,
+    firmware_version: Option<String>,
+    reported_virtual: Option<bool>,
+    // Only meaningful within the process that queried the device.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    backend: Option<Backends>,
 }
 
 impl CameraInformation {
@@ -435,6 +769,12 @@ impl CameraInformation {
             description,
             misc,
             index,
+            bus_speed: None,
+            capabilities: None,
+            driver_version: None,
+            firmware_version: None,
+            reported_virtual: None,
+            backend: None,
         }
     }
 
@@ -500,6 +840,75 @@ impl CameraInformation {
         self.index = index;
     }
 
+    /// Get the speed of the bus the device is connected through, if the backend could tell.
+    #[must_use]
+    pub fn bus_speed(&self) -> Option<BusSpeed> {
+        self.bus_speed
+    }
+
+    /// Set the device info's bus speed.
+    pub fn set_bus_speed(&mut self, bus_speed: Option<BusSpeed>) {
+        self.bus_speed = bus_speed;
+    }
+
+    /// Get the kinds of I/O the device supports, if the backend reports them.
+    #[must_use]
+    pub fn capabilities(&self) -> Option<DeviceCapabilities> {
+        self.capabilities
+    }
+
+    /// Set the device info's capabilities.
+    pub fn set_capabilities(&mut self, capabilities: Option<DeviceCapabilities>) {
+        self.capabilities = capabilities;
+    }
+
+    /// Get the version of the driver handling the device, e.g. `"6.8.0"` for a V4L2 driver (usually the kernel
+    /// version), for bug reports and device specific workarounds.
+    #[must_use]
+    pub fn driver_version(&self) -> Option<&str> {
+        self.driver_version.as_deref()
+    }
+
+    /// Set the device info's driver version.
+    pub fn set_driver_version(&mut self, driver_version: Option<String>) {
+        self.driver_version = driver_version;
+    }
+
+    /// Get the firmware revision the device reports, e.g. a USB camera's `bcdDevice` as `"1.00"`.
+    #[must_use]
+    pub fn firmware_version(&self) -> Option<&str> {
+        self.firmware_version.as_deref()
+    }
+
+    /// Set the device info's firmware version.
+    pub fn set_firmware_version(&mut self, firmware_version: Option<String>) {
+        self.firmware_version = firmware_version;
+    }
+
+    /// Whether the backend knows this to be a virtual camera from the driver itself, e.g. V4L2's `v4l2loopback`.
+    /// `None` if the backend cannot tell, in which case [`CameraInformationExt::is_virtual`] guesses from the name.
+    #[must_use]
+    pub fn reported_virtual(&self) -> Option<bool> {
+        self.reported_virtual
+    }
+
+    /// Set whether the driver reports the device as virtual.
+    pub fn set_reported_virtual(&mut self, reported_virtual: Option<bool>) {
+        self.reported_virtual = reported_virtual;
+    }
+
+    /// The backend that found this device, as tagged by [`query_all`](crate::platform::query_all).
+    /// `None` for devices listed by a single platform's [`query`](crate::platform::PlatformTrait::query).
+    #[must_use]
+    pub fn backend(&self) -> Option<Backends> {
+        self.backend
+    }
+
+    /// Set the backend that found this device.
+    pub fn set_backend(&mut self, backend: Option<Backends>) {
+        self.backend = backend;
+    }
+
     // /// Gets the device info's index as an `u32`.
     // /// # Errors
     // /// If the index is not parsable as a `u32`, this will error.
@@ -520,6 +929,85 @@ impl CameraInformation {
     // }
 }
 
+impl PartialOrd for CameraInformation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CameraInformation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index
+            .cmp(&other.index)
+            .then_with(|| self.human_name.cmp(&other.human_name))
+            .then_with(|| self.description.cmp(&other.description))
+            .then_with(|| self.misc.cmp(&other.misc))
+            .then_with(|| self.bus_speed.cmp(&other.bus_speed))
+            .then_with(|| self.capabilities.cmp(&other.capabilities))
+            .then_with(|| self.driver_version.cmp(&other.driver_version))
+            .then_with(|| self.firmware_version.cmp(&other.firmware_version))
+            .then_with(|| self.reported_virtual.cmp(&other.reported_virtual))
+            .then_with(|| self.backend.cmp(&other.backend))
+    }
+}
+
+// Lowercase name fragments of well known virtual camera drivers.
+const VIRTUAL_CAMERA_NAMES: &[&str] = &[
+    "virtual",
+    "loopback",
+    "dummy video device",
+    "snap camera",
+    "manycam",
+    "xsplit",
+    "splitcam",
+    "nvidia broadcast",
+];
+
+/// Helpers for filtering the results of a camera query.
+pub trait CameraInformationExt {
+    /// Whether this is a virtual camera (e.g. OBS Virtual Camera, v4l2loopback) rather than a physical device.
+    ///
+    /// This uses what the driver [reports](CameraInformation::reported_virtual) where the backend knows it, and
+    /// otherwise falls back to a name based heuristic, which can be fooled by unusual device names.
+    fn is_virtual(&self) -> bool;
+}
+
+impl CameraInformationExt for CameraInformation {
+    fn is_virtual(&self) -> bool {
+        if let Some(reported_virtual) = self.reported_virtual {
+            return reported_virtual;
+        }
+
+        let name = self.human_name.to_lowercase();
+        let description = self.description.to_lowercase();
+        VIRTUAL_CAMERA_NAMES
+            .iter()
+            .any(|fragment| name.contains(fragment) || description.contains(fragment))
+    }
+}
+
+/// Removes the cameras that [`CameraInformationExt::is_virtual`] flags, keeping the order of the rest.
+#[must_use]
+pub fn filter_real_cameras(cameras: Vec<CameraInformation>) -> Vec<CameraInformation> {
+    cameras
+        .into_iter()
+        .filter(|camera| !camera.is_virtual())
+        .collect()
+}
+
+/// Sorts `formats` into a stable order for device pickers: by [`FrameFormat::display_order`], then largest
+/// resolution first, then highest frame rate first. [`FrameFormat::Custom`] formats are ordered by their bytes.
+pub fn sort_camera_formats_for_display(formats: &mut [CameraFormat]) {
+    formats.sort_by(|a, b| {
+        a.format()
+            .display_order()
+            .cmp(&b.format().display_order())
+            .then_with(|| a.format().cmp(&b.format()))
+            .then_with(|| b.resolution().cmp(&a.resolution()))
+            .then_with(|| b.frame_rate().cmp(&a.frame_rate()))
+    });
+}
+
 impl Display for CameraInformation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -587,3 +1075,151 @@ impl Display for CameraInformation {
 //         write!(f, "{self:?}")
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolutions_know_their_common_names() {
+        let full_hd = Resolution::new(1920, 1080);
+        assert!((full_hd.megapixels() - 2.0736).abs() < 1e-9);
+        assert!(Resolution::new(0, 1080).megapixels().abs() < f64::EPSILON);
+
+        assert_eq!(full_hd.common_name(), Some("1080p"));
+        assert_eq!(Resolution::new(640, 480).common_name(), Some("VGA"));
+        assert_eq!(Resolution::new(3840, 2160).common_name(), Some("4K"));
+        assert!(full_hd.is_standard());
+
+        // Rotated and odd sizes have no name.
+        assert_eq!(Resolution::new(1080, 1920).common_name(), None);
+        assert!(!Resolution::new(1000, 600).is_standard());
+    }
+
+    #[test]
+    fn virtual_cameras_are_filtered_out() {
+        let camera = |name: &str, index| {
+            CameraInformation::new(name.to_string(), String::new(), String::new(), CameraIndex::Index(index))
+        };
+        let obs = camera("OBS Virtual Camera", 0);
+        let webcam = camera("HD Pro Webcam C920", 1);
+        assert!(obs.is_virtual());
+        assert!(!webcam.is_virtual());
+
+        // What the driver reports wins over the name.
+        let mut reported = camera("Integrated Camera", 2);
+        reported.set_reported_virtual(Some(true));
+        let mut misnamed = camera("Virtual Reality Headset Camera", 3);
+        misnamed.set_reported_virtual(Some(false));
+
+        assert_eq!(filter_real_cameras(vec![obs, webcam.clone(), reported, misnamed.clone()]), [webcam, misnamed]);
+    }
+
+    #[test]
+    fn camera_indices_parse_only_numbers() {
+        let string = |s: &str| CameraIndex::String(s.to_string());
+
+        assert_eq!(CameraIndex::Index(3).as_index().unwrap(), 3);
+        assert_eq!(string("2").as_index().unwrap(), 2);
+        assert_eq!(string("2").numeric_or(7), 2);
+
+        for not_numeric in ["/dev/video0", "", "-1", " 2"] {
+            assert!(
+                matches!(string(not_numeric).as_index(), Err(NokhwaError::NonNumericIndex(s)) if s == not_numeric),
+                "{not_numeric:?}"
+            );
+            assert_eq!(string(not_numeric).numeric_or(7), 7);
+        }
+        assert!(matches!(string("4294967296").as_index(), Err(NokhwaError::ConversionError(_))));
+        assert_eq!(string("4294967296").numeric_or(7), 7);
+    }
+
+    #[test]
+    fn frame_rates_reject_zero_denominators() {
+        assert!(matches!(
+            FrameRate::try_new(30, 0),
+            Err(NokhwaError::StructureError { error, .. }) if error == "30/0 has a zero denominator"
+        ));
+        assert!(FrameRate::try_from(Rational32::new_raw(30, 0)).is_err());
+        assert_eq!(FrameRate::try_from(Rational32::new_raw(30, 1)).unwrap(), FrameRate::frame_rate(30));
+    }
+
+    #[test]
+    fn frame_rate_constructors_reduce_alike() {
+        let parts = |rate: FrameRate| (*rate.numerator(), *rate.denominator());
+        let non_zero = |value| NonZeroI32::new(value).unwrap();
+
+        assert_eq!(parts(FrameRate::new(60, non_zero(2))), (30, 1));
+        assert_eq!(parts(FrameRate::try_new(60, 2).unwrap()), (30, 1));
+        assert_eq!(parts(FrameRate::try_from(Rational32::new_raw(60, 2)).unwrap()), (30, 1));
+        assert_eq!(parts(FrameRate::new(60000, non_zero(2002))), (30000, 1001));
+        assert_eq!(parts(FrameRate::new(30, non_zero(-2))), (-15, 1));
+        assert_eq!(parts(FrameRate::try_new(-30, -2).unwrap()), (15, 1));
+        assert_eq!(parts(FrameRate::new(0, non_zero(5))), (0, 1));
+        assert_eq!(parts(FrameRate::new(i32::MIN, non_zero(i32::MIN))), (1, 1));
+    }
+
+    #[test]
+    fn crop_to_aspect_centers_the_largest_region() {
+        let hd = Resolution::new(1920, 1080);
+        assert_eq!(hd.crop_to_aspect(1.0), ((420, 0), Resolution::new(1080, 1080)));
+        assert_eq!(hd.crop_to_aspect(4.0 / 3.0), ((240, 0), Resolution::new(1440, 1080)));
+        assert_eq!(hd.crop_to_aspect(16.0 / 9.0), ((0, 0), hd));
+        assert_eq!(Resolution::new(1080, 1920).crop_to_aspect(1.0), ((0, 420), Resolution::new(1080, 1080)));
+        assert_eq!(hd.crop_to_aspect(0.0), ((0, 0), hd));
+        assert_eq!(hd.crop_to_aspect(f64::NAN), ((0, 0), hd));
+    }
+
+    #[test]
+    fn uncompressed_4k_does_not_fit_usb2() {
+        let format = |width, height, frame_format| {
+            CameraFormat::new(Resolution::new(width, height), frame_format, FrameRate::frame_rate(30))
+        };
+
+        let uhd = format(3840, 2160, FrameFormat::Yuyv422);
+        assert!(!uhd.fits_within_bandwidth(BusSpeed::High));
+        assert!(uhd.fits_within_bandwidth(BusSpeed::Unknown));
+        assert!(format(3840, 2160, FrameFormat::MJpeg).fits_within_bandwidth(BusSpeed::High));
+        assert!(format(640, 480, FrameFormat::Yuyv422).fits_within_bandwidth(BusSpeed::High));
+        assert!(format(1920, 1080, FrameFormat::Yuyv422).fits_within_bandwidth(BusSpeed::Super));
+    }
+
+    #[test]
+    fn anamorphic_pixels_widen_the_display_resolution() {
+        let dvd = Resolution::new(720, 480);
+        assert_eq!(dvd.display_resolution(Rational32::new(32, 27)), Resolution::new(853, 480));
+        assert_eq!(dvd.display_resolution(Rational32::new(1, 1)), dvd);
+
+        let mut format = CameraFormat::new(dvd, FrameFormat::Yuyv422, FrameRate::frame_rate(30));
+        assert_eq!(format.display_resolution(), dvd);
+        format.set_pixel_aspect_ratio(Some(Rational32::new(32, 27)));
+        assert_eq!(format.display_resolution(), Resolution::new(853, 480));
+    }
+
+    #[test]
+    fn display_order_does_not_depend_on_enumeration_order() {
+        let format = |format, width, height, fps| {
+            CameraFormat::new(Resolution::new(width, height), format, FrameRate::frame_rate(fps))
+        };
+        let sorted = [
+            format(FrameFormat::Yuyv422, 1280, 720, 30),
+            format(FrameFormat::Yuyv422, 640, 480, 60),
+            format(FrameFormat::Yuyv422, 640, 480, 30),
+            format(FrameFormat::Nv12, 1920, 1080, 30),
+            format(FrameFormat::MJpeg, 1920, 1080, 60),
+            format(FrameFormat::MJpeg, 1920, 1080, 30),
+            format(FrameFormat::Custom(*b"AAAA\0\0\0\0"), 640, 480, 30),
+            format(FrameFormat::Custom(*b"ZZZZ\0\0\0\0"), 640, 480, 30),
+        ];
+
+        let mut reversed = sorted;
+        reversed.reverse();
+        for shift in 0..sorted.len() {
+            for mut shuffled in [sorted, reversed] {
+                shuffled.rotate_left(shift);
+                sort_camera_formats_for_display(&mut shuffled);
+                assert_eq!(shuffled, sorted, "rotated by {shift}");
+            }
+        }
+    }
+}
@@ -1,21 +1,32 @@
-use core::ops::AddAssign;
+use num_traits::CheckedAdd;
 
-pub fn min_max_range<N: Copy + PartialOrd + AddAssign<N> + Sized>(
+/// The most values [`min_max_range`] will return, so a tiny step over a huge range cannot exhaust memory.
+pub const MIN_MAX_RANGE_LIMIT: usize = 1 << 16;
+
+/// Returns every value from `min` to `max` (inclusive) in increments of `step`.
+///
+/// Degenerate ranges terminate instead of looping: if `min > max` this is empty, and if `step` is not positive
+/// this is just `[min]`. At most [`MIN_MAX_RANGE_LIMIT`] values are returned.
+pub fn min_max_range<N: Copy + PartialOrd + CheckedAdd + Default>(
     min: N,
     max: N,
     step: N,
 ) -> Vec<N> {
-    let mut counter = min;
-    let mut nums = vec![min];
+    if min > max {
+        return vec![];
+    }
+    if step <= N::default() {
+        return vec![min];
+    }
 
-    loop {
-        counter += step;
+    let mut nums = vec![min];
 
-        if counter > max {
-            break;
+    while nums.len() < MIN_MAX_RANGE_LIMIT {
+        // Stepping past the type's maximum ends the range instead of overflowing.
+        match nums[nums.len() - 1].checked_add(&step) {
+            Some(next) if next <= max => nums.push(next),
+            _ => break,
         }
-
-        nums.push(counter);
     }
 
     nums
@@ -27,3 +38,44 @@ where
 {
     fn distance_from(&self, other: &Self) -> T;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_range_includes_both_ends() {
+        assert_eq!(min_max_range(0, 10, 5), vec![0, 5, 10]);
+        assert_eq!(min_max_range(1, 10, 4), vec![1, 5, 9]);
+    }
+
+    #[test]
+    fn min_max_range_spans_zero_for_signed_types() {
+        assert_eq!(min_max_range(-4i8, 4, 2), vec![-4, -2, 0, 2, 4]);
+        assert_eq!(min_max_range(i8::MIN, i8::MAX, 100), vec![-128, -28, 72]);
+    }
+
+    #[test]
+    fn min_max_range_does_not_overflow_near_the_type_maximum() {
+        assert_eq!(min_max_range(250u8, 255, 10), vec![250]);
+        assert_eq!(min_max_range(250u8, 255, 5), vec![250, 255]);
+        assert_eq!(min_max_range(i32::MAX - 1, i32::MAX, 1), vec![i32::MAX - 1, i32::MAX]);
+
+        let wide = min_max_range(-10i32, i32::MAX, 1);
+        assert_eq!(wide.len(), MIN_MAX_RANGE_LIMIT);
+        assert_eq!(wide[..3], [-10, -9, -8]);
+    }
+
+    #[test]
+    fn min_max_range_degenerate_ranges_terminate() {
+        // min > max
+        assert!(min_max_range(10, 0, 1).is_empty());
+        assert!(min_max_range(-1i64, -5, 1).is_empty());
+        // step = 0
+        assert_eq!(min_max_range(3, 10, 0), vec![3]);
+        // negative step
+        assert_eq!(min_max_range(3, 10, -1), vec![3]);
+        assert_eq!(min_max_range(-10i16, 10, i16::MIN), vec![-10]);
+        assert_eq!(min_max_range(0u32, u32::MAX, 1).len(), MIN_MAX_RANGE_LIMIT);
+    }
+}
@@ -17,11 +17,11 @@
 use flume::{Receiver, Sender};
 #[cfg(target_os = "macos")]
 use nokhwa_bindings_macos::{
-    AVCaptureDevice, AVCaptureDeviceInput, AVCaptureSession, AVCaptureVideoCallback,
+    host_time_now, AVCaptureDevice, AVCaptureDeviceInput, AVCaptureSession, AVCaptureVideoCallback,
     AVCaptureVideoDataOutput,
 };
 use nokhwa_core::{
-    frame_buffer::FrameBuffer,
+    frame_buffer::{FrameBuffer, RawTimestamp, TimestampClock},
     error::NokhwaError,
     pixel_format::RgbFormat,
     traits::CaptureTrait,
@@ -31,7 +31,7 @@ use nokhwa_core::{
     },
 };
 #[cfg(target_os = "macos")]
-use std::{ffi::CString, sync::Arc};
+use std::{ffi::CString, sync::Arc, time::Duration};
 
 use std::{borrow::Cow, collections::HashMap};
 use nokhwa_core::properties::{CameraControl, ControlValue, KnownCameraControl};
@@ -55,8 +55,9 @@ pub struct AVFoundationCaptureDevice {
     info: CameraInformation,
     buffer_name: CString,
     format: CameraFormat,
-    frame_buffer_receiver: Arc<Receiver<(Vec<u8>, FrameFormat)>>,
-    fbufsnd: Arc<Sender<(Vec<u8>, FrameFormat)>>,
+    // Each frame's bytes, format and presentation time on the host time clock.
+    frame_buffer_receiver: Arc<Receiver<(Vec<u8>, FrameFormat, Option<Duration>)>>,
+    fbufsnd: Arc<Sender<(Vec<u8>, FrameFormat, Option<Duration>)>>,
 }
 
 #[cfg(target_os = "macos")]
@@ -281,8 +282,15 @@ impl CaptureTrait for AVFoundationCaptureDevice {
     fn frame(&mut self) -> Result<FrameBuffer, NokhwaError> {
         self.refresh_camera_format()?;
         let cfmt = self.camera_format();
-        let b = self.frame_raw()?;
-        let buffer = FrameBuffer::new(cfmt.resolution(), b.as_ref(), cfmt.format());
+        let (bytes, _, presentation_time) = self
+            .frame_buffer_receiver
+            .recv()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+        let buffer = FrameBuffer::new(cfmt.resolution(), &bytes, cfmt.format());
+        let buffer = match presentation_time {
+            Some(time) => buffer.with_raw_timestamp(RawTimestamp::new(TimestampClock::HostTime, time), host_time_now()),
+            None => buffer,
+        };
         let _ = self.frame_buffer_receiver.drain();
         Ok(buffer)
     }
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use nokhwa_bindings_linux::{
     v4l2::{
+        camera_information,
         DeviceInner,
         FrameFormatIntermediate,
         format::{Format, FourCC},
@@ -32,7 +33,7 @@ impl Open for V4L2CaptureDevice {
     fn open(index: CameraIndex) -> NokhwaResult<Self> {
         let device = DeviceInner::new(index.as_index()? as usize).map_err(|why| NokhwaError::OpenDeviceError(index.to_string(), why.to_string()))?;
         let caps = device.inner().query_caps().map_err(|why| NokhwaError::OpenDeviceError(index.to_string(), why.to_string()))?;
-        let camera_info = CameraInformation::new(caps.card, caps.bus, caps.driver, index);
+        let camera_info = camera_information(index.as_index()? as usize, &caps);
         Ok(Self {
             device_inner: Arc::new(device),
             camera_info,
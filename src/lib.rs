@@ -56,6 +56,7 @@ pub use query::*;
 pub use threaded::CallbackCamera;
 
 pub mod utils {
+    pub use nokhwa_core::format_request::{RequestedFormat, RequestedFormatType};
     pub use nokhwa_core::types::*;
 }
 
@@ -70,3 +71,7 @@ pub mod camera_traits {
 pub mod buffer {
     pub use nokhwa_core::frame_buffer::*;
 }
+
+pub mod pixel_format {
+    pub use nokhwa_core::pixel_format::*;
+}